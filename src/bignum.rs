@@ -0,0 +1,326 @@
+// Licensed under the Apache-2.0 license
+
+//! Multi-precision arithmetic on little-endian `u32` limb slices, for the
+//! steps the RSA and ECC engines cannot do themselves: RSA blinding and CRT
+//! recombination, key generation and elliptic-curve arithmetic.
+//! Operand sizes come from the slices and nothing allocates; scratch space
+//! is sized for [`MAX_LIMBS`] limb moduli.
+
+use core::cmp::Ordering;
+use core::sync::atomic::{compiler_fence, Ordering as AtomicOrdering};
+
+/// Limbs in the largest modulus, 4096 bits
+pub const MAX_LIMBS: usize = 128;
+
+/// Low half of a double-width limb
+#[allow(clippy::cast_possible_truncation)]
+fn lo(x: u64) -> u32 {
+    x as u32
+}
+
+/// Zero limbs that held secrets, in a way the compiler cannot elide
+pub fn wipe(a: &mut [u32]) {
+    for x in a {
+        unsafe { core::ptr::write_volatile(x, 0) };
+    }
+    compiler_fence(AtomicOrdering::SeqCst);
+}
+
+/// Limb buffer for secret values, wiped when dropped
+pub struct Scratch<const N: usize>(pub [u32; N]);
+
+impl<const N: usize> Default for Scratch<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> Drop for Scratch<N> {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+/// Load a big-endian integer into `out`, zero-extending it. Returns `false`
+/// if the value does not fit.
+pub fn from_be_bytes(bytes: &[u8], out: &mut [u32]) -> bool {
+    out.fill(0);
+    for (i, &b) in bytes.iter().rev().enumerate() {
+        if b == 0 {
+            continue;
+        }
+        let Some(limb) = out.get_mut(i / 4) else {
+            return false;
+        };
+        *limb |= u32::from(b) << (8 * (i % 4));
+    }
+    true
+}
+
+/// Store the low `out.len()` bytes of `limbs` big-endian into `out`
+pub fn to_be_bytes(limbs: &[u32], out: &mut [u8]) {
+    for (i, b) in out.iter_mut().rev().enumerate() {
+        *b = limbs.get(i / 4).map_or(0, |l| l.to_le_bytes()[i % 4]);
+    }
+}
+
+/// Compare two values of possibly different lengths
+pub fn cmp(a: &[u32], b: &[u32]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .rev()
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Number of significant bits
+pub fn bit_len(a: &[u32]) -> usize {
+    a.iter()
+        .rposition(|&l| l != 0)
+        .map_or(0, |i| 32 * (i + 1) - a[i].leading_zeros() as usize)
+}
+
+pub fn is_zero(a: &[u32]) -> bool {
+    a.iter().all(|&l| l == 0)
+}
+
+pub fn is_one(a: &[u32]) -> bool {
+    a.first() == Some(&1) && is_zero(&a[1..])
+}
+
+/// `a += b`, returning the carry out of `a`. `b` may be shorter than `a`.
+pub fn add(a: &mut [u32], b: &[u32]) -> u32 {
+    let mut carry = 0u64;
+    for (i, x) in a.iter_mut().enumerate() {
+        let sum = u64::from(*x) + u64::from(b.get(i).copied().unwrap_or(0)) + carry;
+        *x = lo(sum);
+        carry = sum >> 32;
+    }
+    lo(carry)
+}
+
+/// `a -= b`, returning the borrow out of `a`. `b` may be shorter than `a`.
+pub fn sub(a: &mut [u32], b: &[u32]) -> u32 {
+    let mut borrow = 0u64;
+    for (i, x) in a.iter_mut().enumerate() {
+        let diff = u64::from(*x)
+            .wrapping_sub(u64::from(b.get(i).copied().unwrap_or(0)))
+            .wrapping_sub(borrow);
+        *x = lo(diff);
+        borrow = diff >> 63;
+    }
+    lo(borrow)
+}
+
+/// `out = a * b`; `out` must hold `a.len() + b.len()` limbs
+pub fn mul(a: &[u32], b: &[u32], out: &mut [u32]) {
+    out.fill(0);
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = u64::from(x) * u64::from(y) + u64::from(out[i + j]) + carry;
+            out[i + j] = lo(t);
+            carry = t >> 32;
+        }
+        out[i + b.len()] = lo(carry);
+    }
+}
+
+/// Shift `a` right by one bit, shifting `top` (0 or 1) into the top bit
+pub fn shr1(a: &mut [u32], top: u32) {
+    let mut carry = top;
+    for x in a.iter_mut().rev() {
+        let next = *x & 1;
+        *x = (*x >> 1) | (carry << 31);
+        carry = next;
+    }
+}
+
+/// `a /= d` for a non-zero single-limb `d`, returning the remainder
+pub fn div_small(a: &mut [u32], d: u32) -> u32 {
+    let mut r = 0u64;
+    for x in a.iter_mut().rev() {
+        let t = (r << 32) | u64::from(*x);
+        *x = lo(t / u64::from(d));
+        r = t % u64::from(d);
+    }
+    lo(r)
+}
+
+/// `a mod d` for a non-zero single-limb `d`
+pub fn rem_small(a: &[u32], d: u32) -> u32 {
+    let r = a
+        .iter()
+        .rev()
+        .fold(0u64, |r, &x| ((r << 32) | u64::from(x)) % u64::from(d));
+    lo(r)
+}
+
+/// `out = a mod m`, where `out` is as long as `m` and `m` is non-zero.
+///
+/// Shift-and-subtract, one bit of `a` at a time; every step does the same
+/// work whatever the values.
+pub fn rem(a: &[u32], m: &[u32], out: &mut [u32]) {
+    let n = m.len();
+    let mut r_buf = [0u32; MAX_LIMBS + 1];
+    let mut t_buf = [0u32; MAX_LIMBS + 1];
+    let r = &mut r_buf[..=n];
+    let t = &mut t_buf[..=n];
+
+    for bit in (0..a.len() * 32).rev() {
+        // r = 2r + bit, which stays below 2m
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for x in r.iter_mut() {
+            let next = *x >> 31;
+            *x = (*x << 1) | carry;
+            carry = next;
+        }
+        // r -= m if that does not borrow
+        t.copy_from_slice(r);
+        let keep = sub(t, m).wrapping_neg();
+        for (x, y) in r.iter_mut().zip(t.iter()) {
+            *x = (*x & keep) | (*y & !keep);
+        }
+    }
+    out.copy_from_slice(&r[..n]);
+    wipe(r);
+    wipe(t);
+}
+
+/// `out = a * b mod m`, where `out` is as long as `m`
+pub fn mul_mod(a: &[u32], b: &[u32], m: &[u32], out: &mut [u32]) {
+    let mut product = [0u32; 2 * MAX_LIMBS];
+    let product = &mut product[..a.len() + b.len()];
+    mul(a, b, product);
+    rem(product, m, out);
+    wipe(product);
+}
+
+/// `-m^-1 mod 2^32` for the low limb `m0` of an odd modulus, the constant
+/// [`mont_mul`] takes
+pub fn mont_neg_inv(m0: u32) -> u32 {
+    // Newton's iteration; every step doubles the number of correct bits
+    let mut inv = 1u32;
+    for _ in 0..5 {
+        inv = inv.wrapping_mul(2u32.wrapping_sub(m0.wrapping_mul(inv)));
+    }
+    inv.wrapping_neg()
+}
+
+/// Montgomery product `out = a * b / 2^(32n) mod m` for `a, b < m`, all of
+/// `n = m.len()` limbs and `m` odd, with `m_inv = mont_neg_inv(m[0])`.
+///
+/// The running time does not depend on the values.
+pub fn mont_mul(a: &[u32], b: &[u32], m: &[u32], m_inv: u32, out: &mut [u32]) {
+    let n = m.len();
+    let mut t_buf = [0u32; MAX_LIMBS + 2];
+    let t = &mut t_buf[..n + 2];
+
+    for &x in &a[..n] {
+        // t += x * b
+        let mut carry = 0u64;
+        for (tj, &y) in t.iter_mut().zip(b) {
+            let sum = u64::from(*tj) + u64::from(x) * u64::from(y) + carry;
+            *tj = lo(sum);
+            carry = sum >> 32;
+        }
+        let sum = u64::from(t[n]) + carry;
+        t[n] = lo(sum);
+        t[n + 1] = lo(sum >> 32);
+
+        // t = (t + u * m) / 2^32, with u chosen to clear the low limb
+        let u = t[0].wrapping_mul(m_inv);
+        let mut carry = (u64::from(t[0]) + u64::from(u) * u64::from(m[0])) >> 32;
+        for (j, &y) in m.iter().enumerate().skip(1) {
+            let sum = u64::from(t[j]) + u64::from(u) * u64::from(y) + carry;
+            t[j - 1] = lo(sum);
+            carry = sum >> 32;
+        }
+        let sum = u64::from(t[n]) + carry;
+        t[n - 1] = lo(sum);
+        t[n] = t[n + 1] + lo(sum >> 32);
+        t[n + 1] = 0;
+    }
+
+    // t < 2m: subtract m unless t is already below it
+    out.copy_from_slice(&t[..n]);
+    let borrow = sub(out, m);
+    let keep = (borrow & !t[n] & 1).wrapping_neg();
+    for (x, y) in out.iter_mut().zip(t.iter()) {
+        *x = (*y & keep) | (*x & !keep);
+    }
+    wipe(t);
+}
+
+/// `out = a^-1 mod m` for odd `m` and `a < m`, where `a` is no longer than
+/// `m` and `out` is as long as `m`. Returns `false` if `a` has no inverse.
+///
+/// Binary extended Euclid; the running time depends on the values, so only
+/// use it on values that are random and discarded afterwards.
+pub fn inv_mod(a: &[u32], m: &[u32], out: &mut [u32]) -> bool {
+    let n = m.len();
+    let mut u_buf = [0u32; MAX_LIMBS];
+    let mut v_buf = [0u32; MAX_LIMBS];
+    let mut x1_buf = [0u32; MAX_LIMBS];
+    let mut x2_buf = [0u32; MAX_LIMBS];
+    let (u, v) = (&mut u_buf[..n], &mut v_buf[..n]);
+    let (x1, x2) = (&mut x1_buf[..n], &mut x2_buf[..n]);
+    for (x, y) in u.iter_mut().zip(a) {
+        *x = *y;
+    }
+    v.copy_from_slice(m);
+    x1[0] = 1;
+
+    // Halve `x` modulo the odd `m`
+    let halve = |x: &mut [u32]| {
+        if x[0] & 1 == 0 {
+            shr1(x, 0);
+        } else {
+            let carry = add(x, m);
+            shr1(x, carry);
+        }
+    };
+    // `x -= y` modulo `m`
+    let sub_mod = |x: &mut [u32], y: &[u32]| {
+        if sub(x, y) != 0 {
+            add(x, m);
+        }
+    };
+
+    let invertible = loop {
+        if is_zero(u) {
+            break false;
+        }
+        if is_one(u) || is_one(v) {
+            break true;
+        }
+        while u[0] & 1 == 0 {
+            shr1(u, 0);
+            halve(x1);
+        }
+        while v[0] & 1 == 0 {
+            shr1(v, 0);
+            halve(x2);
+        }
+        if cmp(u, v).is_ge() {
+            sub(u, v);
+            sub_mod(x1, x2);
+        } else {
+            sub(v, u);
+            sub_mod(x2, x1);
+        }
+    };
+
+    if invertible {
+        out.copy_from_slice(if is_one(u) { x1 } else { x2 });
+    }
+    for buf in [u, v, x1, x2] {
+        wipe(buf);
+    }
+    invertible
+}
@@ -1,5 +1,7 @@
 // Licensed under the Apache-2.0 license
 
+use core::sync::atomic::{compiler_fence, Ordering};
+
 pub struct DummyDelay;
 
 impl embedded_hal::delay::DelayNs for DummyDelay {
@@ -10,6 +12,37 @@ impl embedded_hal::delay::DelayNs for DummyDelay {
     }
 }
 
+/// Overwrite secrets in a way the compiler cannot elide
+pub fn wipe(buf: &mut [u8]) {
+    for b in buf {
+        unsafe { core::ptr::write_volatile(b, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Compare two byte strings without an early exit on the first difference.
+/// Only the lengths are allowed to leak.
+#[must_use]
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    unsafe { core::ptr::read_volatile(&diff) == 0 }
+}
+
+/// Number of significant bits in a big-endian magnitude
+#[must_use]
+pub fn bit_len(bytes: &[u8]) -> u32 {
+    let Some(start) = bytes.iter().position(|&b| b != 0) else {
+        return 0;
+    };
+    let bits = u32::try_from(bytes.len() - start)
+        .unwrap_or(u32::MAX)
+        .saturating_mul(8);
+    bits - bytes[start].leading_zeros()
+}
+
 #[repr(align(32))]
 pub struct DmaBuffer<const N: usize> {
     pub buf: [u8; N],
@@ -0,0 +1,719 @@
+// Licensed under the Apache-2.0 license
+
+//! AES driver for the crypto half of the HACE engine.
+//!
+//! The engine runs ECB, CBC and CTR passes through bounce buffers in
+//! non-cacheable RAM. Chaining state (the CBC IV, the CTR counter and any unused
+//! keystream) is kept by the driver between passes, so a streaming operation
+//! can be suspended and another one run on the engine in between. GCM is
+//! built on top of the hardware CTR and ECB passes with GHASH computed here.
+
+use crate::common::wipe;
+use crate::hace_controller::{
+    hace_engine_status, HaceError, HACE_DEFAULT_TIMEOUT_POLLS, HACE_DMA_ALIGN,
+};
+use ast1060_pac::Hace;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+const HACE_CMD_AES192: u32 = 1 << 2;
+const HACE_CMD_AES256: u32 = 1 << 3;
+const HACE_CMD_ECB: u32 = 0;
+const HACE_CMD_CBC: u32 = 1 << 4;
+const HACE_CMD_CTR: u32 = 4 << 4;
+const HACE_CMD_ENCRYPT: u32 = 1 << 7;
+const HACE_CMD_AES_KEY_HW_EXP: u32 = 1 << 13;
+const HACE_CMD_MBUS_REQ_SYNC_EN: u32 = 1 << 20;
+
+/// Crypto completion flag in the HACE status register
+const HACE_CRYPTO_ISR: u32 = 1 << 12;
+
+pub const AES_BLOCK_SIZE: usize = 16;
+pub const AES_GCM_TAG_SIZE: usize = 16;
+
+/// Largest amount of data moved through the engine in one pass
+pub const AES_DMA_CHUNK: usize = 1024;
+
+/// The engine reads the IV followed by the raw key from the context buffer
+const AES_CTX_KEY_OFFSET: usize = 16;
+
+const AES_MAX_KEY_LEN: usize = 32;
+
+#[repr(C)]
+#[repr(align(64))]
+struct AesDmaArea {
+    ctx: [u8; 64],
+    src: [u8; AES_DMA_CHUNK],
+    dst: [u8; AES_DMA_CHUNK],
+}
+
+/// Safe wrapper for the section-placed DMA area
+struct SectionPlacedArea(UnsafeCell<AesDmaArea>);
+
+unsafe impl Sync for SectionPlacedArea {}
+
+impl SectionPlacedArea {
+    const fn new() -> Self {
+        Self(UnsafeCell::new(AesDmaArea {
+            ctx: [0; 64],
+            src: [0; AES_DMA_CHUNK],
+            dst: [0; AES_DMA_CHUNK],
+        }))
+    }
+
+    fn get(&self) -> *mut AesDmaArea {
+        self.0.get()
+    }
+}
+
+/// Context and bounce buffers in the non-cacheable RAM section
+#[link_section = ".ram_nc"]
+static AES_DMA_AREA: SectionPlacedArea = SectionPlacedArea::new();
+
+/// Set while a pass owns the DMA area and the crypto engine
+static AES_ENGINE_BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Forget a pass that never completed; called once HACE has been reset.
+pub(crate) fn release_engine(hace: &Hace) {
+    unsafe {
+        hace.hace10().write(|w| w.bits(0));
+        hace.hace1c().write(|w| w.bits(HACE_CRYPTO_ISR));
+    }
+    AES_ENGINE_BUSY.store(false, Ordering::Release);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoError {
+    /// Key is not 16, 24 or 32 bytes long
+    InvalidKeyLength,
+    /// IV length does not suit the mode
+    InvalidIvLength,
+    /// Input is not made of whole blocks, or the output is too short
+    InvalidInputLength,
+    /// GCM tag shorter than 4 or longer than 16 bytes
+    InvalidTagLength,
+    /// Additional authenticated data given after the message
+    InvalidState,
+    /// GCM tag does not match
+    AuthenticationFailed,
+    /// Failure reported by the crypto engine
+    Engine(HaceError),
+}
+
+impl From<HaceError> for CryptoError {
+    fn from(err: HaceError) -> Self {
+        CryptoError::Engine(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesMode {
+    Ecb,
+    Cbc,
+    Ctr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+/// AES-128, AES-192 or AES-256 key, wiped on drop
+struct AesKey {
+    bytes: [u8; AES_MAX_KEY_LEN],
+    len: usize,
+}
+
+impl AesKey {
+    fn new(key: &[u8]) -> Result<Self, CryptoError> {
+        if !matches!(key.len(), 16 | 24 | 32) {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+        let mut bytes = [0u8; AES_MAX_KEY_LEN];
+        bytes[..key.len()].copy_from_slice(key);
+        Ok(Self {
+            bytes,
+            len: key.len(),
+        })
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    fn cmd(&self) -> u32 {
+        match self.len {
+            24 => HACE_CMD_AES192,
+            32 => HACE_CMD_AES256,
+            _ => 0,
+        }
+    }
+}
+
+impl Drop for AesKey {
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+    }
+}
+
+pub struct AesController<'ctrl> {
+    pub hace: &'ctrl Hace,
+    timeout_polls: u32,
+}
+
+impl<'ctrl> AesController<'ctrl> {
+    #[must_use]
+    pub fn new(hace: &'ctrl Hace) -> Self {
+        Self {
+            hace,
+            timeout_polls: HACE_DEFAULT_TIMEOUT_POLLS,
+        }
+    }
+
+    /// Number of status polls a pass waits before giving up with
+    /// [`HaceError::Timeout`]. Use `HaceController::recover` to reset the
+    /// engine after a timeout.
+    pub fn set_timeout(&mut self, polls: u32) {
+        self.timeout_polls = polls;
+    }
+
+    /// Start a streaming ECB, CBC or CTR operation.
+    ///
+    /// ECB takes an empty IV; CBC takes the 16-byte IV and CTR the 16-byte
+    /// initial counter block, which is incremented as a 128-bit big-endian
+    /// integer.
+    pub fn init(
+        &mut self,
+        key: &[u8],
+        mode: AesMode,
+        direction: Direction,
+        iv: &[u8],
+    ) -> Result<AesOp<'_, 'ctrl>, CryptoError> {
+        let key = AesKey::new(key)?;
+        let mut block = [0u8; AES_BLOCK_SIZE];
+        match mode {
+            AesMode::Ecb if !iv.is_empty() => return Err(CryptoError::InvalidIvLength),
+            AesMode::Ecb => {}
+            AesMode::Cbc | AesMode::Ctr => {
+                if iv.len() != AES_BLOCK_SIZE {
+                    return Err(CryptoError::InvalidIvLength);
+                }
+                block.copy_from_slice(iv);
+            }
+        }
+
+        Ok(AesOp {
+            controller: self,
+            key,
+            mode,
+            direction,
+            iv: block,
+            inc32: false,
+            keystream: [0; AES_BLOCK_SIZE],
+            ks_used: AES_BLOCK_SIZE,
+        })
+    }
+
+    /// Encrypt `input` in one go. See [`AesController::init`] for the IV.
+    pub fn encrypt(
+        &mut self,
+        key: &[u8],
+        mode: AesMode,
+        iv: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), CryptoError> {
+        self.init(key, mode, Direction::Encrypt, iv)?
+            .update(input, output)
+    }
+
+    /// Decrypt `input` in one go. See [`AesController::init`] for the IV.
+    pub fn decrypt(
+        &mut self,
+        key: &[u8],
+        mode: AesMode,
+        iv: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), CryptoError> {
+        self.init(key, mode, Direction::Decrypt, iv)?
+            .update(input, output)
+    }
+
+    /// Start a streaming AES-GCM operation with an IV of any non-zero length.
+    /// A 12-byte IV is recommended.
+    pub fn init_gcm(
+        &mut self,
+        key: &[u8],
+        direction: Direction,
+        iv: &[u8],
+    ) -> Result<GcmOp<'_, 'ctrl>, CryptoError> {
+        if iv.is_empty() {
+            return Err(CryptoError::InvalidIvLength);
+        }
+        let key = AesKey::new(key)?;
+        // ECB encryption of single blocks
+        let ecb = HACE_CMD_ENCRYPT | key.cmd();
+
+        // Hash subkey H = E(K, 0^128)
+        let mut h = [0u8; AES_BLOCK_SIZE];
+        self.run_pass(
+            &key,
+            ecb,
+            &[0; AES_BLOCK_SIZE],
+            &[0; AES_BLOCK_SIZE],
+            &mut h,
+        )?;
+
+        let mut j0 = [0u8; AES_BLOCK_SIZE];
+        if iv.len() == 12 {
+            j0[..12].copy_from_slice(iv);
+            j0[15] = 1;
+        } else {
+            let mut ghash = Ghash::new(&h);
+            ghash.update(iv);
+            j0 = ghash.finish(0, iv.len() as u64);
+        }
+
+        let mut ek_j0 = [0u8; AES_BLOCK_SIZE];
+        self.run_pass(&key, ecb, &[0; AES_BLOCK_SIZE], &j0, &mut ek_j0)?;
+
+        let mut counter = j0;
+        ctr_advance(&mut counter, 1, true);
+        let ghash = Ghash::new(&h);
+        wipe(&mut h);
+
+        Ok(GcmOp {
+            ctr: AesOp {
+                controller: self,
+                key,
+                mode: AesMode::Ctr,
+                direction,
+                iv: counter,
+                inc32: true,
+                keystream: [0; AES_BLOCK_SIZE],
+                ks_used: AES_BLOCK_SIZE,
+            },
+            ghash,
+            ek_j0,
+            aad_len: 0,
+            text_len: 0,
+            aad_done: false,
+        })
+    }
+
+    /// Encrypt and authenticate `input`, writing the full 16-byte tag.
+    pub fn gcm_encrypt(
+        &mut self,
+        key: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+        tag: &mut [u8; AES_GCM_TAG_SIZE],
+    ) -> Result<(), CryptoError> {
+        let mut op = self.init_gcm(key, Direction::Encrypt, iv)?;
+        op.update_aad(aad)?;
+        op.update(input, output)?;
+        *tag = op.finish();
+        Ok(())
+    }
+
+    /// Decrypt `input` and check it against `tag`, which may be truncated
+    /// to no less than 4 bytes. The output is wiped if the tag does not match.
+    pub fn gcm_decrypt(
+        &mut self,
+        key: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), CryptoError> {
+        let mut op = self.init_gcm(key, Direction::Decrypt, iv)?;
+        op.update_aad(aad)?;
+        op.update(input, output)?;
+        let result = op.verify(tag);
+        if result.is_err() {
+            wipe(&mut output[..input.len()]);
+        }
+        result
+    }
+
+    /// Run one engine pass over whole blocks through the bounce buffers
+    fn run_pass(
+        &mut self,
+        key: &AesKey,
+        cmd: u32,
+        iv: &[u8; AES_BLOCK_SIZE],
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), CryptoError> {
+        let len = input.len();
+        if len % AES_BLOCK_SIZE != 0 || len > AES_DMA_CHUNK || output.len() < len {
+            return Err(CryptoError::InvalidInputLength);
+        }
+        hace_engine_status()?;
+
+        if AES_ENGINE_BUSY
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(HaceError::Busy.into());
+        }
+
+        let area = unsafe { &mut *AES_DMA_AREA.get() };
+        let ctx_addr = area.ctx.as_ptr() as u32;
+        let src_addr = area.src.as_ptr() as u32;
+        let dst_addr = area.dst.as_ptr() as u32;
+        if (ctx_addr | src_addr | dst_addr) % HACE_DMA_ALIGN != 0 {
+            AES_ENGINE_BUSY.store(false, Ordering::Release);
+            return Err(HaceError::BadAlignment.into());
+        }
+
+        let key_bytes = key.as_bytes();
+        area.ctx[..AES_BLOCK_SIZE].copy_from_slice(iv);
+        area.ctx[AES_CTX_KEY_OFFSET..AES_CTX_KEY_OFFSET + key_bytes.len()]
+            .copy_from_slice(key_bytes);
+        area.src[..len].copy_from_slice(input);
+
+        let cmd = cmd | HACE_CMD_AES_KEY_HW_EXP | HACE_CMD_MBUS_REQ_SYNC_EN;
+        unsafe {
+            self.hace.hace1c().write(|w| w.bits(HACE_CRYPTO_ISR));
+            self.hace.hace00().write(|w| w.bits(src_addr));
+            self.hace.hace04().write(|w| w.bits(dst_addr));
+            self.hace.hace08().write(|w| w.bits(ctx_addr));
+            self.hace
+                .hace0c()
+                .write(|w| w.bits(u32::try_from(len).unwrap()));
+            self.hace.hace10().write(|w| w.bits(cmd));
+        }
+
+        let done = self.wait_done();
+        if done.is_ok() {
+            output[..len].copy_from_slice(&area.dst[..len]);
+            unsafe {
+                self.hace.hace1c().write(|w| w.bits(HACE_CRYPTO_ISR));
+            }
+        }
+
+        wipe(&mut area.ctx);
+        wipe(&mut area.src[..len]);
+        wipe(&mut area.dst[..len]);
+
+        // After a timeout the engine stays claimed until HACE is reset
+        if done.is_ok() {
+            AES_ENGINE_BUSY.store(false, Ordering::Release);
+        }
+        done
+    }
+
+    fn wait_done(&self) -> Result<(), CryptoError> {
+        for _ in 0..self.timeout_polls {
+            if self.hace.hace1c().read().bits() & HACE_CRYPTO_ISR != 0 {
+                return Ok(());
+            }
+            cortex_m::asm::nop();
+        }
+        if self.hace.hace1c().read().bits() & HACE_CRYPTO_ISR != 0 {
+            Ok(())
+        } else {
+            Err(HaceError::Timeout.into())
+        }
+    }
+}
+
+/// Add `blocks` to a big-endian counter block, either as a whole 128-bit
+/// integer or, for GCM, on the low 32 bits only.
+fn ctr_advance(counter: &mut [u8; AES_BLOCK_SIZE], blocks: u32, inc32: bool) {
+    if inc32 {
+        let low = u32::from_be_bytes(counter[12..].try_into().unwrap());
+        counter[12..].copy_from_slice(&low.wrapping_add(blocks).to_be_bytes());
+    } else {
+        let value = u128::from_be_bytes(*counter);
+        *counter = value.wrapping_add(u128::from(blocks)).to_be_bytes();
+    }
+}
+
+/// A streaming ECB, CBC or CTR operation.
+///
+/// ECB and CBC updates must be whole blocks. CTR updates can be any length;
+/// keystream left over from a partial block is used by the next update.
+pub struct AesOp<'a, 'ctrl> {
+    controller: &'a mut AesController<'ctrl>,
+    key: AesKey,
+    mode: AesMode,
+    direction: Direction,
+    iv: [u8; AES_BLOCK_SIZE],
+    inc32: bool,
+    keystream: [u8; AES_BLOCK_SIZE],
+    ks_used: usize,
+}
+
+impl AesOp<'_, '_> {
+    /// Process `input` into the front of `output`
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<(), CryptoError> {
+        if output.len() < input.len() {
+            return Err(CryptoError::InvalidInputLength);
+        }
+        match self.mode {
+            AesMode::Ecb | AesMode::Cbc => {
+                if input.len() % AES_BLOCK_SIZE != 0 {
+                    return Err(CryptoError::InvalidInputLength);
+                }
+                self.crypt_blocks(input, output)
+            }
+            AesMode::Ctr => self.ctr_update(input, output),
+        }
+    }
+
+    fn ctr_update(&mut self, input: &[u8], output: &mut [u8]) -> Result<(), CryptoError> {
+        let mut off = 0;
+        while self.ks_used < AES_BLOCK_SIZE && off < input.len() {
+            output[off] = input[off] ^ self.keystream[self.ks_used];
+            self.ks_used += 1;
+            off += 1;
+        }
+
+        let whole = (input.len() - off) / AES_BLOCK_SIZE * AES_BLOCK_SIZE;
+        self.crypt_blocks(&input[off..off + whole], &mut output[off..off + whole])?;
+        off += whole;
+
+        let tail = input.len() - off;
+        if tail != 0 {
+            // Encrypting zeros past the tail leaves the unused keystream
+            let mut block = [0u8; AES_BLOCK_SIZE];
+            block[..tail].copy_from_slice(&input[off..]);
+            let mut out = [0u8; AES_BLOCK_SIZE];
+            let result = self.crypt_blocks(&block, &mut out);
+            if result.is_ok() {
+                output[off..input.len()].copy_from_slice(&out[..tail]);
+                self.keystream = out;
+                self.ks_used = tail;
+            }
+            wipe(&mut block);
+            wipe(&mut out);
+            result?;
+        }
+        Ok(())
+    }
+
+    fn crypt_blocks(&mut self, input: &[u8], output: &mut [u8]) -> Result<(), CryptoError> {
+        let cmd = self.cmd();
+        let mut off = 0;
+        while off < input.len() {
+            let mut len = (input.len() - off).min(AES_DMA_CHUNK);
+            if self.mode == AesMode::Ctr {
+                // Keep the low counter word from wrapping inside a pass, so
+                // the result doesn't depend on the engine's carry width
+                let low = u32::from_be_bytes(self.iv[12..].try_into().unwrap());
+                let until_wrap = (u64::from(u32::MAX) - u64::from(low) + 1) * AES_BLOCK_SIZE as u64;
+                len = len.min(usize::try_from(until_wrap).unwrap_or(usize::MAX));
+            }
+
+            let src = &input[off..off + len];
+            let dst = &mut output[off..off + len];
+            let iv = self.iv;
+            self.controller.run_pass(&self.key, cmd, &iv, src, dst)?;
+
+            let last = len - AES_BLOCK_SIZE;
+            match (self.mode, self.direction) {
+                (AesMode::Ecb, _) => {}
+                (AesMode::Cbc, Direction::Encrypt) => self.iv.copy_from_slice(&dst[last..]),
+                (AesMode::Cbc, Direction::Decrypt) => self.iv.copy_from_slice(&src[last..]),
+                (AesMode::Ctr, _) => {
+                    let blocks = u32::try_from(len / AES_BLOCK_SIZE).unwrap();
+                    ctr_advance(&mut self.iv, blocks, self.inc32);
+                }
+            }
+            off += len;
+        }
+        Ok(())
+    }
+
+    fn cmd(&self) -> u32 {
+        let mode = match self.mode {
+            AesMode::Ecb => HACE_CMD_ECB,
+            AesMode::Cbc => HACE_CMD_CBC,
+            AesMode::Ctr => HACE_CMD_CTR,
+        };
+        // CTR decryption is the same keystream XOR as encryption
+        let encrypt = if self.direction == Direction::Encrypt || self.mode == AesMode::Ctr {
+            HACE_CMD_ENCRYPT
+        } else {
+            0
+        };
+        mode | encrypt | self.key.cmd()
+    }
+}
+
+impl Drop for AesOp<'_, '_> {
+    fn drop(&mut self) {
+        wipe(&mut self.iv);
+        wipe(&mut self.keystream);
+    }
+}
+
+/// GHASH over GF(2^128), computed in constant time
+struct Ghash {
+    h: u128,
+    y: u128,
+    buf: [u8; AES_BLOCK_SIZE],
+    buf_len: usize,
+}
+
+impl Ghash {
+    fn new(h: &[u8; AES_BLOCK_SIZE]) -> Self {
+        Self {
+            h: u128::from_be_bytes(*h),
+            y: 0,
+            buf: [0; AES_BLOCK_SIZE],
+            buf_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        if self.buf_len != 0 {
+            let take = data.len().min(AES_BLOCK_SIZE - self.buf_len);
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+            if self.buf_len < AES_BLOCK_SIZE {
+                return;
+            }
+            self.absorb(self.buf);
+            self.buf_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(AES_BLOCK_SIZE);
+        for block in &mut blocks {
+            self.absorb(block.try_into().unwrap());
+        }
+        let rest = blocks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+    }
+
+    /// Zero-pad a partial block so the next field starts on a block boundary
+    fn pad(&mut self) {
+        if self.buf_len != 0 {
+            self.buf[self.buf_len..].fill(0);
+            self.absorb(self.buf);
+            self.buf_len = 0;
+        }
+    }
+
+    fn absorb(&mut self, block: [u8; AES_BLOCK_SIZE]) {
+        self.y = gf128_mul(self.y ^ u128::from_be_bytes(block), self.h);
+    }
+
+    /// Close the hash with the bit lengths of the two fields
+    fn finish(&mut self, aad_len: u64, text_len: u64) -> [u8; AES_BLOCK_SIZE] {
+        self.pad();
+        let lengths = (u128::from(aad_len * 8) << 64) | u128::from(text_len * 8);
+        self.absorb(lengths.to_be_bytes());
+        self.y.to_be_bytes()
+    }
+}
+
+impl Drop for Ghash {
+    fn drop(&mut self) {
+        unsafe {
+            core::ptr::write_volatile(&mut self.h, 0);
+            core::ptr::write_volatile(&mut self.y, 0);
+        }
+        wipe(&mut self.buf);
+    }
+}
+
+/// Multiply in GF(2^128) with the GCM bit order, without data-dependent
+/// branches
+fn gf128_mul(x: u128, y: u128) -> u128 {
+    const R: u128 = 0xe1 << 120;
+    let mut z = 0u128;
+    let mut v = y;
+    for i in 0..128 {
+        let bit = (x >> (127 - i)) & 1;
+        z ^= v & 0u128.wrapping_sub(bit);
+        v = (v >> 1) ^ (R & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+/// A streaming AES-GCM operation.
+///
+/// Feed all additional data with `update_aad` before the message. In
+/// streaming decryption the plaintext is released before the tag is checked,
+/// so it must not be used until `verify` succeeds.
+pub struct GcmOp<'a, 'ctrl> {
+    ctr: AesOp<'a, 'ctrl>,
+    ghash: Ghash,
+    ek_j0: [u8; AES_BLOCK_SIZE],
+    aad_len: u64,
+    text_len: u64,
+    aad_done: bool,
+}
+
+impl GcmOp<'_, '_> {
+    pub fn update_aad(&mut self, aad: &[u8]) -> Result<(), CryptoError> {
+        if self.aad_done {
+            return Err(CryptoError::InvalidState);
+        }
+        self.ghash.update(aad);
+        self.aad_len += aad.len() as u64;
+        Ok(())
+    }
+
+    /// Encrypt or decrypt `input` into the front of `output`
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<(), CryptoError> {
+        if output.len() < input.len() {
+            return Err(CryptoError::InvalidInputLength);
+        }
+        if !self.aad_done {
+            self.ghash.pad();
+            self.aad_done = true;
+        }
+
+        self.ctr.ctr_update(input, output)?;
+        match self.ctr.direction {
+            Direction::Encrypt => self.ghash.update(&output[..input.len()]),
+            Direction::Decrypt => self.ghash.update(input),
+        }
+        self.text_len += input.len() as u64;
+        Ok(())
+    }
+
+    /// Compute the 16-byte authentication tag
+    #[must_use]
+    pub fn finish(mut self) -> [u8; AES_GCM_TAG_SIZE] {
+        let mut tag = self.ghash.finish(self.aad_len, self.text_len);
+        for (t, k) in tag.iter_mut().zip(self.ek_j0.iter()) {
+            *t ^= k;
+        }
+        tag
+    }
+
+    /// Check the message against `tag` in constant time
+    pub fn verify(self, tag: &[u8]) -> Result<(), CryptoError> {
+        if !(4..=AES_GCM_TAG_SIZE).contains(&tag.len()) {
+            return Err(CryptoError::InvalidTagLength);
+        }
+        let expected = self.finish();
+        let diff = expected
+            .iter()
+            .zip(tag.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        if diff == 0 {
+            Ok(())
+        } else {
+            Err(CryptoError::AuthenticationFailed)
+        }
+    }
+}
+
+impl Drop for GcmOp<'_, '_> {
+    fn drop(&mut self) {
+        wipe(&mut self.ek_j0);
+    }
+}
@@ -0,0 +1,282 @@
+// Licensed under the Apache-2.0 license
+
+//! Minimal DER reader and PEM decoder for the key and certificate formats
+//! the crypto drivers consume. Only definite, minimally encoded lengths are
+//! accepted; values are returned as slices of the input.
+
+pub const TAG_BOOLEAN: u8 = 0x01;
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_NULL: u8 = 0x05;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_UTC_TIME: u8 = 0x17;
+pub const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+/// Context-specific constructed tag `[n]`
+#[must_use]
+pub const fn tag_context(n: u8) -> u8 {
+    0xa0 | n
+}
+
+/// Context-specific primitive tag `[n] IMPLICIT`
+#[must_use]
+pub const fn tag_implicit(n: u8) -> u8 {
+    0x80 | n
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerError {
+    /// The input ends inside an element
+    Truncated,
+    /// An element has a different tag than the structure requires
+    UnexpectedTag,
+    /// A length is indefinite, not minimally encoded or too large
+    InvalidLength,
+    /// An INTEGER is empty, negative or not minimally encoded
+    InvalidInteger,
+    /// A BIT STRING has unused bits where whole bytes are required
+    InvalidBitString,
+    /// A BOOLEAN is neither `00` nor `ff`
+    InvalidBoolean,
+    /// Bytes remain after the outermost element
+    TrailingData,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PemError {
+    /// No `-----BEGIN <label>-----` / `-----END <label>-----` pair
+    MissingLabel,
+    /// The body is not valid base64
+    InvalidBase64,
+    /// The output buffer cannot hold the decoded body
+    BufferTooSmall,
+}
+
+/// Cursor over a run of DER elements
+#[derive(Clone, Copy)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Tag of the next element, if any
+    #[must_use]
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Fail unless every element has been consumed
+    pub fn finish(&self) -> Result<(), DerError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(DerError::TrailingData)
+        }
+    }
+
+    /// Next element as `(tag, contents, whole encoding)`
+    pub fn read_any(&mut self) -> Result<(u8, &'a [u8], &'a [u8]), DerError> {
+        let (&tag, rest) = self.data.split_first().ok_or(DerError::Truncated)?;
+        let (&first, rest) = rest.split_first().ok_or(DerError::Truncated)?;
+
+        let (len, rest) = match first {
+            0..=0x7f => (usize::from(first), rest),
+            0x81 => {
+                let (&b, rest) = rest.split_first().ok_or(DerError::Truncated)?;
+                if b < 0x80 {
+                    return Err(DerError::InvalidLength);
+                }
+                (usize::from(b), rest)
+            }
+            0x82 => {
+                let bytes = rest.get(..2).ok_or(DerError::Truncated)?;
+                let len = usize::from(u16::from_be_bytes([bytes[0], bytes[1]]));
+                if len < 0x100 {
+                    return Err(DerError::InvalidLength);
+                }
+                (len, &rest[2..])
+            }
+            // Indefinite lengths and elements beyond 64 KiB
+            _ => return Err(DerError::InvalidLength),
+        };
+
+        let contents = rest.get(..len).ok_or(DerError::Truncated)?;
+        let header_len = self.data.len() - rest.len();
+        let whole = &self.data[..header_len + len];
+        self.data = &rest[len..];
+        Ok((tag, contents, whole))
+    }
+
+    /// Contents of the next element, which must carry `tag`
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], DerError> {
+        let (found, contents, _) = self.read_any()?;
+        if found == tag {
+            Ok(contents)
+        } else {
+            Err(DerError::UnexpectedTag)
+        }
+    }
+
+    /// Contents of the next element if it carries `tag`
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, DerError> {
+        if self.peek_tag() == Some(tag) {
+            self.read(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reader over the contents of the next SEQUENCE
+    pub fn read_sequence(&mut self) -> Result<Reader<'a>, DerError> {
+        self.read(TAG_SEQUENCE).map(Reader::new)
+    }
+
+    /// Next non-negative INTEGER as big-endian magnitude bytes, without the
+    /// leading zero byte DER adds when the top bit is set
+    pub fn read_unsigned(&mut self) -> Result<&'a [u8], DerError> {
+        let bytes = self.read(TAG_INTEGER)?;
+        match bytes {
+            [] => Err(DerError::InvalidInteger),
+            [first, ..] if first & 0x80 != 0 => Err(DerError::InvalidInteger),
+            [0] => Ok(bytes),
+            [0, second, ..] if second & 0x80 == 0 => Err(DerError::InvalidInteger),
+            [0, rest @ ..] => Ok(rest),
+            _ => Ok(bytes),
+        }
+    }
+
+    /// Next INTEGER, which must fit in a `u32`
+    pub fn read_u32(&mut self) -> Result<u32, DerError> {
+        let bytes = self.read_unsigned()?;
+        if bytes.len() > 4 {
+            return Err(DerError::InvalidInteger);
+        }
+        Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b)))
+    }
+
+    /// Next BIT STRING, which must hold whole bytes
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], DerError> {
+        match self.read(TAG_BIT_STRING)? {
+            [0, bits @ ..] => Ok(bits),
+            _ => Err(DerError::InvalidBitString),
+        }
+    }
+
+    /// Next BOOLEAN
+    pub fn read_bool(&mut self) -> Result<bool, DerError> {
+        match self.read(TAG_BOOLEAN)? {
+            [0x00] => Ok(false),
+            [0xff] => Ok(true),
+            _ => Err(DerError::InvalidBoolean),
+        }
+    }
+
+    /// Next NULL
+    pub fn read_null(&mut self) -> Result<(), DerError> {
+        if self.read(TAG_NULL)?.is_empty() {
+            Ok(())
+        } else {
+            Err(DerError::InvalidLength)
+        }
+    }
+}
+
+/// Parse `data` as exactly one element with `tag`, returning its contents
+pub fn parse_single(data: &[u8], tag: u8) -> Result<&[u8], DerError> {
+    let mut reader = Reader::new(data);
+    let contents = reader.read(tag)?;
+    reader.finish()?;
+    Ok(contents)
+}
+
+fn base64_value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Find `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// `<label>-----` at the start of `data`
+fn starts_with_label(data: &[u8], label: &[u8]) -> bool {
+    data.starts_with(label) && data[label.len()..].starts_with(b"-----")
+}
+
+/// Decode the body of the first PEM block labelled `label` (for example
+/// `"PUBLIC KEY"`) into `out`, returning the DER bytes.
+///
+/// Whitespace inside the body is ignored; PEM headers are not supported.
+pub fn pem_decode<'o>(pem: &[u8], label: &str, out: &'o mut [u8]) -> Result<&'o [u8], PemError> {
+    const BEGIN: &[u8] = b"-----BEGIN ";
+    const END: &[u8] = b"-----END ";
+    let label = label.as_bytes();
+
+    let mut offset = 0;
+    let begin = loop {
+        let pos = find(&pem[offset..], BEGIN).ok_or(PemError::MissingLabel)?;
+        offset += pos + BEGIN.len();
+        if starts_with_label(&pem[offset..], label) {
+            break offset + label.len() + 5;
+        }
+    };
+    let body = &pem[begin..];
+    let end = find(body, END).ok_or(PemError::MissingLabel)?;
+    if !starts_with_label(&body[end + END.len()..], label) {
+        return Err(PemError::MissingLabel);
+    }
+    let body = &body[..end];
+
+    let mut len = 0;
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for &c in body {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if c == b'=' {
+            padding += 1;
+            continue;
+        }
+        // Data after padding
+        if padding > 0 {
+            return Err(PemError::InvalidBase64);
+        }
+        let value = base64_value(c).ok_or(PemError::InvalidBase64)?;
+        acc = (acc << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            *out.get_mut(len).ok_or(PemError::BufferTooSmall)? = (acc >> bits).to_le_bytes()[0];
+            len += 1;
+        }
+    }
+
+    // A final quantum of two or three characters leaves four or two bits,
+    // which must be zero and matched by the padding
+    let complete = matches!((bits, padding), (0, 0) | (2, 1) | (4, 2));
+    if !complete || acc & ((1 << bits) - 1) != 0 {
+        return Err(PemError::InvalidBase64);
+    }
+    Ok(&out[..len])
+}
@@ -0,0 +1,462 @@
+// Licensed under the Apache-2.0 license
+
+//! Arithmetic on the NIST curves P-256 and P-384 for ECDSA signing, ECDH
+//! and public-key derivation.
+//!
+//! Field elements are Montgomery residues in 384-bit limb arrays, wide
+//! enough for either curve. Points are projective and added with the
+//! complete formulas of Renes, Costello and Batina (2016, algorithm 4 for
+//! a = -3), so the scalar ladder has no special cases to branch on and runs
+//! in time independent of the scalar.
+
+use crate::bignum;
+use hex_literal::hex;
+
+/// Limbs of a field element, enough for P-384
+pub const LIMBS: usize = 12;
+
+type Fe = [u32; LIMBS];
+
+const ONE: Fe = {
+    let mut one = [0; LIMBS];
+    one[0] = 1;
+    one
+};
+
+/// Short Weierstrass curve with a = -3, as big-endian byte strings of the
+/// curve's size
+pub struct CurveParams {
+    pub len: usize,
+    pub p: &'static [u8],
+    pub n: &'static [u8],
+    pub b: &'static [u8],
+    pub gx: &'static [u8],
+    pub gy: &'static [u8],
+}
+
+pub static P256: CurveParams = CurveParams {
+    len: 32,
+    p: &hex!("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff"),
+    n: &hex!("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+    b: &hex!("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+    gx: &hex!("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+    gy: &hex!("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+};
+
+pub static P384: CurveParams = CurveParams {
+    len: 48,
+    p: &hex!(
+        "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe"
+        "ffffffff0000000000000000ffffffff"
+    ),
+    n: &hex!(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf"
+        "581a0db248b0a77aecec196accc52973"
+    ),
+    b: &hex!(
+        "b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875a"
+        "c656398d8a2ed19d2a85c8edd3ec2aef"
+    ),
+    gx: &hex!(
+        "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a38"
+        "5502f25dbf55296c3a545e3872760ab7"
+    ),
+    gy: &hex!(
+        "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c0"
+        "0a60b1ce1d7e819d7a431d7c90ea0e5f"
+    ),
+};
+
+/// Parameters of the supported curve with `len`-byte scalars
+#[must_use]
+pub fn params_for_len(len: usize) -> Option<&'static CurveParams> {
+    [&P256, &P384].into_iter().find(|params| params.len == len)
+}
+
+/// `a` where `mask` is all ones, `b` where it is zero
+fn select(mask: u32, a: &Fe, b: &Fe) -> Fe {
+    let mut out = [0; LIMBS];
+    for ((o, x), y) in out.iter_mut().zip(a).zip(b) {
+        *o = (x & mask) | (y & !mask);
+    }
+    out
+}
+
+fn from_be_bytes(bytes: &[u8]) -> Fe {
+    let mut out = [0; LIMBS];
+    bignum::from_be_bytes(bytes, &mut out);
+    out
+}
+
+/// Montgomery arithmetic modulo an odd modulus below 2^384
+pub struct Field {
+    m: Fe,
+    m_inv: u32,
+    /// 2^768 mod m, to convert into the Montgomery domain
+    r2: Fe,
+}
+
+impl Field {
+    #[must_use]
+    pub fn new(modulus: &[u8]) -> Self {
+        let m = from_be_bytes(modulus);
+        let mut wide = [0u32; 2 * LIMBS + 1];
+        wide[2 * LIMBS] = 1;
+        let mut r2 = [0; LIMBS];
+        bignum::rem(&wide, &m, &mut r2);
+        Self {
+            m,
+            m_inv: bignum::mont_neg_inv(m[0]),
+            r2,
+        }
+    }
+
+    #[must_use]
+    pub fn mul(&self, a: &Fe, b: &Fe) -> Fe {
+        let mut out = [0; LIMBS];
+        bignum::mont_mul(a, b, &self.m, self.m_inv, &mut out);
+        out
+    }
+
+    #[must_use]
+    pub fn add(&self, a: &Fe, b: &Fe) -> Fe {
+        let mut sum = *a;
+        let carry = bignum::add(&mut sum, b);
+        let mut diff = sum;
+        let borrow = bignum::sub(&mut diff, &self.m);
+        // Keep the sum only if it is already below m
+        select((borrow & !carry & 1).wrapping_neg(), &sum, &diff)
+    }
+
+    #[must_use]
+    pub fn sub(&self, a: &Fe, b: &Fe) -> Fe {
+        let mut diff = *a;
+        let mask = bignum::sub(&mut diff, b).wrapping_neg();
+        let mut m = self.m;
+        for x in &mut m {
+            *x &= mask;
+        }
+        bignum::add(&mut diff, &m);
+        diff
+    }
+
+    /// `a mod m` for `a < 2m`
+    #[must_use]
+    pub fn reduce(&self, a: &Fe) -> Fe {
+        self.add(a, &[0; LIMBS])
+    }
+
+    /// Montgomery form of `a < m`
+    #[must_use]
+    pub fn to_mont(&self, a: &Fe) -> Fe {
+        self.mul(a, &self.r2)
+    }
+
+    #[must_use]
+    pub fn from_mont(&self, a: &Fe) -> Fe {
+        self.mul(a, &ONE)
+    }
+
+    /// `a^e` in the Montgomery domain for a public exponent `e`. The
+    /// square-and-multiply chain depends only on `e`.
+    fn pow(&self, a: &Fe, e: &Fe) -> Fe {
+        let mut acc = self.to_mont(&ONE);
+        for bit in (0..LIMBS * 32).rev() {
+            acc = self.mul(&acc, &acc);
+            if (e[bit / 32] >> (bit % 32)) & 1 == 1 {
+                acc = self.mul(&acc, a);
+            }
+        }
+        acc
+    }
+
+    /// `a^-1` in the Montgomery domain by Fermat's little theorem, for a
+    /// prime modulus
+    #[must_use]
+    pub fn inv(&self, a: &Fe) -> Fe {
+        let mut e = self.m;
+        bignum::sub(&mut e, &[2]);
+        self.pow(a, &e)
+    }
+
+    /// Whether `a` is below the modulus, from the borrow of `a - m` so the
+    /// time taken does not depend on `a`
+    #[must_use]
+    pub fn contains(&self, a: &Fe) -> bool {
+        let mut diff = *a;
+        let borrow = bignum::sub(&mut diff, &self.m);
+        bignum::wipe(&mut diff);
+        borrow == 1
+    }
+}
+
+/// Projective point `(X : Y : Z)` with Montgomery coordinates
+#[derive(Clone, Copy)]
+struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+}
+
+impl Point {
+    /// Swap `a` and `b` where `mask` is all ones
+    fn cswap(mask: u32, a: &mut Point, b: &mut Point) {
+        for (u, v) in [
+            (&mut a.x, &mut b.x),
+            (&mut a.y, &mut b.y),
+            (&mut a.z, &mut b.z),
+        ] {
+            for (s, t) in u.iter_mut().zip(v.iter_mut()) {
+                let d = (*s ^ *t) & mask;
+                *s ^= d;
+                *t ^= d;
+            }
+        }
+    }
+
+    fn wipe(&mut self) {
+        bignum::wipe(&mut self.x);
+        bignum::wipe(&mut self.y);
+        bignum::wipe(&mut self.z);
+    }
+}
+
+/// A curve with its field and scalar arithmetic set up
+pub struct EcGroup {
+    params: &'static CurveParams,
+    field: Field,
+    scalars: Field,
+    /// `b` in Montgomery form
+    b: Fe,
+}
+
+impl EcGroup {
+    #[must_use]
+    pub fn new(params: &'static CurveParams) -> Self {
+        let field = Field::new(params.p);
+        let b = field.to_mont(&from_be_bytes(params.b));
+        Self {
+            params,
+            field,
+            scalars: Field::new(params.n),
+            b,
+        }
+    }
+
+    /// Whether the big-endian `k` is a valid private scalar, `1 <= k < n`.
+    /// Runs in constant time, as `k` may be a key or a nonce.
+    #[must_use]
+    pub fn is_valid_scalar(&self, k: &[u8]) -> bool {
+        if k.len() != self.params.len {
+            return false;
+        }
+        let mut value = from_be_bytes(k);
+        let nonzero = value.iter().fold(0, |acc, &l| acc | l) != 0;
+        let below_n = self.scalars.contains(&value);
+        bignum::wipe(&mut value);
+        nonzero & below_n
+    }
+
+    /// Whether the big-endian `(x, y)` is a point on the curve, with both
+    /// coordinates of the curve's size and below p. The cofactor is 1, so
+    /// this is a full public-key check; the point at infinity has no affine
+    /// form and cannot pass.
+    #[must_use]
+    pub fn is_on_curve(&self, x: &[u8], y: &[u8]) -> bool {
+        if x.len() != self.params.len || y.len() != self.params.len {
+            return false;
+        }
+        let field = &self.field;
+        let x = from_be_bytes(x);
+        let y = from_be_bytes(y);
+        if !field.contains(&x) || !field.contains(&y) {
+            return false;
+        }
+        let x = field.to_mont(&x);
+        let y = field.to_mont(&y);
+        field.mul(&y, &y) == self.curve_rhs(&x)
+    }
+
+    /// `x^3 - 3x + b` for a Montgomery-form `x`, which is `y^2` on the curve
+    fn curve_rhs(&self, x: &Fe) -> Fe {
+        let field = &self.field;
+        let x3 = field.mul(&field.mul(x, x), x);
+        let three_x = field.add(&field.add(x, x), x);
+        field.add(&field.sub(&x3, &three_x), &self.b)
+    }
+
+    /// Recover the y-coordinate with parity `y_odd` of the point with the
+    /// big-endian x-coordinate `x` (SEC 1 section 2.3.4) into `out_y`.
+    /// Returns `false` if no such point exists. Both curves have
+    /// p = 3 mod 4, so the square root is `rhs^((p + 1) / 4)`.
+    pub fn decompress(&self, x: &[u8], y_odd: bool, out_y: &mut [u8]) -> bool {
+        if x.len() != self.params.len {
+            return false;
+        }
+        let field = &self.field;
+        let x = from_be_bytes(x);
+        if !field.contains(&x) {
+            return false;
+        }
+        let rhs = self.curve_rhs(&field.to_mont(&x));
+        let mut e = field.m;
+        bignum::add(&mut e, &ONE);
+        bignum::shr1(&mut e, 0);
+        bignum::shr1(&mut e, 0);
+        let root = field.pow(&rhs, &e);
+        if field.mul(&root, &root) != rhs {
+            return false;
+        }
+
+        let mut y = field.from_mont(&root);
+        if (y[0] & 1 == 1) != y_odd {
+            y = field.sub(&[0; LIMBS], &y);
+        }
+        // Fails only for y = 0 with an odd y requested
+        if (y[0] & 1 == 1) != y_odd {
+            return false;
+        }
+        bignum::to_be_bytes(&y, out_y);
+        true
+    }
+
+    /// `h mod n` for a big-endian `h` of the curve's size, into `out`
+    pub fn reduce_scalar(&self, h: &[u8], out: &mut [u8]) {
+        let h = self.scalars.reduce(&from_be_bytes(h));
+        bignum::to_be_bytes(&h, out);
+    }
+
+    /// Complete addition, RCB algorithm 4; also correct for `p == q` and
+    /// for the point at infinity. Names follow the paper.
+    #[allow(clippy::similar_names)]
+    fn add(&self, p: &Point, q: &Point) -> Point {
+        let f = &self.field;
+        let t0 = f.mul(&p.x, &q.x);
+        let t1 = f.mul(&p.y, &q.y);
+        let t2 = f.mul(&p.z, &q.z);
+        let t3 = f.add(&p.x, &p.y);
+        let t4 = f.add(&q.x, &q.y);
+        let t3 = f.mul(&t3, &t4);
+        let t4 = f.add(&t0, &t1);
+        let t3 = f.sub(&t3, &t4);
+        let t4 = f.add(&p.y, &p.z);
+        let x3 = f.add(&q.y, &q.z);
+        let t4 = f.mul(&t4, &x3);
+        let x3 = f.add(&t1, &t2);
+        let t4 = f.sub(&t4, &x3);
+        let x3 = f.add(&p.x, &p.z);
+        let y3 = f.add(&q.x, &q.z);
+        let x3 = f.mul(&x3, &y3);
+        let y3 = f.add(&t0, &t2);
+        let y3 = f.sub(&x3, &y3);
+        let z3 = f.mul(&self.b, &t2);
+        let x3 = f.sub(&y3, &z3);
+        let z3 = f.add(&x3, &x3);
+        let x3 = f.add(&x3, &z3);
+        let z3 = f.sub(&t1, &x3);
+        let x3 = f.add(&t1, &x3);
+        let y3 = f.mul(&self.b, &y3);
+        let t1 = f.add(&t2, &t2);
+        let t2 = f.add(&t1, &t2);
+        let y3 = f.sub(&y3, &t2);
+        let y3 = f.sub(&y3, &t0);
+        let t1 = f.add(&y3, &y3);
+        let y3 = f.add(&t1, &y3);
+        let t1 = f.add(&t0, &t0);
+        let t0 = f.add(&t1, &t0);
+        let t0 = f.sub(&t0, &t2);
+        let t1 = f.mul(&t4, &y3);
+        let t2 = f.mul(&t0, &y3);
+        let y3 = f.mul(&x3, &z3);
+        let y3 = f.add(&y3, &t2);
+        let x3 = f.mul(&t3, &x3);
+        let x3 = f.sub(&x3, &t1);
+        let z3 = f.mul(&t4, &z3);
+        let t1 = f.mul(&t3, &t0);
+        let z3 = f.add(&z3, &t1);
+        Point {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// `k * (x, y)` for a big-endian scalar `k` and an affine point on the
+    /// curve, with big-endian coordinates of the curve's size. The result is
+    /// written to `out_x` and `out_y`; returns `false` if it is the point at
+    /// infinity.
+    pub fn mul(&self, k: &[u8], x: &[u8], y: &[u8], out_x: &mut [u8], out_y: &mut [u8]) -> bool {
+        let field = &self.field;
+        let mut r0 = Point {
+            x: [0; LIMBS],
+            y: field.to_mont(&ONE),
+            z: [0; LIMBS],
+        };
+        let mut r1 = Point {
+            x: field.to_mont(&from_be_bytes(x)),
+            y: field.to_mont(&from_be_bytes(y)),
+            z: field.to_mont(&ONE),
+        };
+
+        // Montgomery ladder over every bit of k, leading zeros included
+        for byte in k {
+            for shift in (0..8).rev() {
+                let mask = u32::from((byte >> shift) & 1).wrapping_neg();
+                Point::cswap(mask, &mut r0, &mut r1);
+                r1 = self.add(&r0, &r1);
+                r0 = self.add(&r0, &r0);
+                Point::cswap(mask, &mut r0, &mut r1);
+            }
+        }
+
+        let finite = !bignum::is_zero(&r0.z);
+        let z_inv = field.inv(&r0.z);
+        let ax = field.from_mont(&field.mul(&r0.x, &z_inv));
+        let ay = field.from_mont(&field.mul(&r0.y, &z_inv));
+        bignum::to_be_bytes(&ax, out_x);
+        bignum::to_be_bytes(&ay, out_y);
+        r0.wipe();
+        r1.wipe();
+        finite
+    }
+
+    /// `k * G`, as for [`EcGroup::mul`]
+    pub fn mul_base(&self, k: &[u8], out_x: &mut [u8], out_y: &mut [u8]) -> bool {
+        self.mul(k, self.params.gx, self.params.gy, out_x, out_y)
+    }
+
+    /// ECDSA signature of the scalar-sized digest `e` with private scalar
+    /// `d` and nonce `k`, both valid scalars. Returns `false` if `r` or `s`
+    /// comes out zero, in which case a new nonce is needed.
+    pub fn ecdsa_sign(
+        &self,
+        d: &[u8],
+        e: &[u8],
+        k: &[u8],
+        r_out: &mut [u8],
+        s_out: &mut [u8],
+    ) -> bool {
+        let scalars = &self.scalars;
+        let len = self.params.len;
+        let mut kg_x = [0u8; LIMBS * 4];
+        let mut kg_y = [0u8; LIMBS * 4];
+        self.mul_base(k, &mut kg_x[..len], &mut kg_y[..len]);
+
+        // r = x mod n, where x < p < 2n
+        let r_value = scalars.reduce(&from_be_bytes(&kg_x[..len]));
+        // s = k^-1 (e + r d) mod n
+        let mut d_mont = scalars.to_mont(&from_be_bytes(d));
+        let mut k_mont = scalars.to_mont(&from_be_bytes(k));
+        let e_mont = scalars.to_mont(&scalars.reduce(&from_be_bytes(e)));
+        let rd = scalars.mul(&scalars.to_mont(&r_value), &d_mont);
+        let mut k_inv = scalars.inv(&k_mont);
+        let s_value = scalars.from_mont(&scalars.mul(&k_inv, &scalars.add(&e_mont, &rd)));
+
+        bignum::to_be_bytes(&r_value, r_out);
+        bignum::to_be_bytes(&s_value, s_out);
+        for secret in [&mut d_mont, &mut k_mont, &mut k_inv] {
+            bignum::wipe(secret);
+        }
+        !bignum::is_zero(&r_value) && !bignum::is_zero(&s_value)
+    }
+}
@@ -0,0 +1,130 @@
+// Licensed under the Apache-2.0 license
+
+//! Elliptic-curve Diffie-Hellman (SEC 1 section 3.3.1) and public-key
+//! derivation on P-256 and P-384, chosen by the scalar type as for
+//! [`crate::ecdsa`].
+//!
+//! Both are methods of [`AspeedEcdsa`] and multiply with the constant-time
+//! ladder in [`crate::ecc`], leaving the engine free for other operations.
+//! Peer keys are checked to lie on the curve before they are multiplied,
+//! and every result is checked to lie on the curve before it is released,
+//! which catches faults in the computation.
+
+use crate::common::wipe;
+use crate::ecc::{params_for_len, EcGroup};
+use crate::ecdsa::{AspeedEcdsa, PrivateKey, PublicKey};
+use embedded_hal::delay::DelayNs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcdhError {
+    /// The scalar size matches neither P-256 nor P-384
+    UnsupportedCurve,
+    /// The private scalar is not in `[1, n - 1]`
+    InvalidPrivateKey,
+    /// The peer's point is not on the curve
+    InvalidPublicKey,
+    /// The computed point is not on the curve
+    FaultDetected,
+}
+
+/// Shared secret, the x-coordinate of `d * Q`. Wiped on drop.
+pub struct SharedSecret<S: AsMut<[u8]>> {
+    pub z: S,
+}
+
+impl<S: AsMut<[u8]>> Drop for SharedSecret<S> {
+    fn drop(&mut self) {
+        wipe(self.z.as_mut());
+    }
+}
+
+fn group_for<S: AsRef<[u8]>>(scalar: &S) -> Result<EcGroup, EcdhError> {
+    params_for_len(scalar.as_ref().len())
+        .map(EcGroup::new)
+        .ok_or(EcdhError::UnsupportedCurve)
+}
+
+fn check_private_key<S>(group: &EcGroup, key: &PrivateKey<S>) -> Result<(), EcdhError>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    if group.is_valid_scalar(key.d.as_ref()) {
+        Ok(())
+    } else {
+        Err(EcdhError::InvalidPrivateKey)
+    }
+}
+
+/// Check that a peer's public key is a point on its curve
+pub fn validate_public_key<S>(peer: &PublicKey<S>) -> Result<(), EcdhError>
+where
+    S: AsRef<[u8]>,
+{
+    let group = group_for(&peer.qx)?;
+    if group.is_on_curve(peer.qx.as_ref(), peer.qy.as_ref()) {
+        Ok(())
+    } else {
+        Err(EcdhError::InvalidPublicKey)
+    }
+}
+
+impl<D: DelayNs> AspeedEcdsa<'_, D> {
+    /// Public key `d * G` of a private key, for provisioning
+    pub fn public_key<S>(&mut self, key: &PrivateKey<S>) -> Result<PublicKey<S>, EcdhError>
+    where
+        S: AsRef<[u8]> + AsMut<[u8]> + Default,
+    {
+        let group = group_for(&key.d)?;
+        check_private_key(&group, key)?;
+
+        let mut public_key = PublicKey {
+            qx: S::default(),
+            qy: S::default(),
+        };
+        let finite = group.mul_base(
+            key.d.as_ref(),
+            public_key.qx.as_mut(),
+            public_key.qy.as_mut(),
+        );
+        if !finite || !group.is_on_curve(public_key.qx.as_ref(), public_key.qy.as_ref()) {
+            return Err(EcdhError::FaultDetected);
+        }
+        Ok(public_key)
+    }
+
+    /// Shared secret of our private key and the peer's public key, which
+    /// must be on the same curve. Feed the result through a KDF such as
+    /// [`hkdf`](crate::kdf::hkdf) rather than using it as a key directly.
+    pub fn shared_secret<S>(
+        &mut self,
+        key: &PrivateKey<S>,
+        peer: &PublicKey<S>,
+    ) -> Result<SharedSecret<S>, EcdhError>
+    where
+        S: AsRef<[u8]> + AsMut<[u8]> + Default,
+    {
+        let group = group_for(&key.d)?;
+        check_private_key(&group, key)?;
+        validate_public_key(peer)?;
+
+        let mut secret = SharedSecret { z: S::default() };
+        let mut y = S::default();
+        let finite = group.mul(
+            key.d.as_ref(),
+            peer.qx.as_ref(),
+            peer.qy.as_ref(),
+            secret.z.as_mut(),
+            y.as_mut(),
+        );
+        let on_curve = group.is_on_curve(secret.z.as_ref(), y.as_ref());
+        wipe(y.as_mut());
+        // Unreachable for a valid key and peer point of prime order n
+        if !finite {
+            return Err(EcdhError::InvalidPublicKey);
+        }
+        if !on_curve {
+            return Err(EcdhError::FaultDetected);
+        }
+        Ok(secret)
+    }
+}
@@ -1,6 +1,12 @@
 // Licensed under the Apache-2.0 license
 
-use ast1060_pac::Secure;
+use crate::common::wipe;
+use crate::ecc::{CurveParams, EcGroup, P256, P384};
+use crate::hace_controller::HaceController;
+use crate::hmac::{MacError, Sha384 as HmacSha384};
+use crate::kdf::{hmac_parts, KdfError};
+use crate::secure_engine::EngineLock;
+use ast1060_pac::{Hace, Secure};
 use core::ptr::{read_volatile, write_volatile, NonNull};
 use embedded_hal::delay::DelayNs;
 use proposed_traits::common::{
@@ -9,9 +15,10 @@ use proposed_traits::common::{
 };
 use proposed_traits::digest::DigestAlgorithm;
 use proposed_traits::ecdsa::{
-    Curve, EcdsaVerify, Error, ErrorKind, ErrorType as EcdsaErrorType, PubKeyForCurve,
+    Curve, EcdsaSign, EcdsaVerify, Error, ErrorKind, ErrorType as EcdsaErrorType, PubKeyForCurve,
     SignatureForCurve,
 };
+use rand_core::{CryptoRng, RngCore};
 
 const ECDSA_BASE: usize = 0x7e6f_2000; // SBC base address
 const ECDSA_SRAM_BASE: usize = 0x7900_0000; // SRAM base address for ECDSA
@@ -31,6 +38,23 @@ const SRAM_DST_R: usize = 0x21c0;
 const SRAM_DST_S: usize = 0x2200;
 const SRAM_DST_M: usize = 0x2240;
 
+/// End of the operand slots, the last being `SRAM_DST_M`
+const SRAM_OPERANDS_END: usize = 0x2280;
+
+/// SRAM access control, relative to `ECDSA_BASE`
+pub(crate) const SEC_SRAM_CTRL: usize = 0x7c;
+/// Access while the engine loads the curve parameters
+const SEC_SRAM_CTRL_PARAMS: u32 = 0x0100_f00b;
+/// CPU writes to the operand slots
+pub(crate) const SEC_SRAM_CTRL_WRITE: u32 = 0x0300_f00b;
+/// SRAM closed to the CPU
+pub(crate) const SEC_SRAM_CTRL_LOCKED: u32 = 0;
+/// Instruction word the engine executes on trigger
+const SRAM_INSTR: usize = 0x23c0;
+/// Verify `(r, s)` over `m` against `(qx, qy)`; the only instruction this
+/// driver issues, so signing and key agreement use [`crate::ecc`]
+const ECC_INSTR_VERIFY: u32 = 1;
+
 #[derive(Debug)]
 pub enum SerdeError {
     NotSupported,
@@ -46,6 +70,19 @@ impl CommonSerdeError for SerdeError {
     }
 }
 
+/// Copy a scalar into the front of `dest`. Scalars are held big-endian;
+/// `Endian::Little` reverses the byte order.
+fn copy_scalar(dest: &mut [u8], src: &[u8], endian: Endian) -> Result<(), SerdeError> {
+    let dest = dest
+        .get_mut(..src.len())
+        .ok_or(SerdeError::BufferTooSmall)?;
+    dest.copy_from_slice(src);
+    if matches!(endian, Endian::Little) {
+        dest.reverse();
+    }
+    Ok(())
+}
+
 pub struct Scalar48(pub [u8; 48]);
 
 impl Scalar48 {
@@ -57,17 +94,17 @@ impl CommonErrorType for Scalar48 {
 }
 
 impl ToBytes for Scalar48 {
-    fn to_bytes(&self, dest: &mut [u8], _: Endian) -> Result<(), Self::Error> {
-        dest[..Self::LEN].copy_from_slice(&self.0);
-        Ok(())
+    fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<(), Self::Error> {
+        copy_scalar(dest, &self.0, endian)
     }
 }
 
 impl FromBytes for Scalar48 {
-    fn from_bytes(bytes: &[u8], _: Endian) -> Result<Self, Self::Error> {
-        let mut out = [0u8; Scalar48::LEN];
-        out.copy_from_slice(&bytes[..Self::LEN]);
-        Ok(Scalar48(out))
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Result<Self, Self::Error> {
+        let bytes = bytes.get(..Self::LEN).ok_or(SerdeError::BufferTooSmall)?;
+        let mut out = Scalar48::default();
+        copy_scalar(&mut out.0, bytes, endian)?;
+        Ok(out)
     }
 }
 
@@ -89,6 +126,49 @@ impl Default for Scalar48 {
     }
 }
 
+pub struct Scalar32(pub [u8; 32]);
+
+impl Scalar32 {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+}
+
+impl CommonErrorType for Scalar32 {
+    type Error = SerdeError;
+}
+
+impl ToBytes for Scalar32 {
+    fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<(), Self::Error> {
+        copy_scalar(dest, &self.0, endian)
+    }
+}
+
+impl FromBytes for Scalar32 {
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Result<Self, Self::Error> {
+        let bytes = bytes.get(..Self::LEN).ok_or(SerdeError::BufferTooSmall)?;
+        let mut out = Scalar32::default();
+        copy_scalar(&mut out.0, bytes, endian)?;
+        Ok(out)
+    }
+}
+
+impl AsRef<[u8]> for Scalar32 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for Scalar32 {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl Default for Scalar32 {
+    fn default() -> Self {
+        Scalar32([0u8; Scalar32::LEN])
+    }
+}
+
 pub struct Sha384;
 
 impl DigestAlgorithm for Sha384 {
@@ -96,6 +176,13 @@ impl DigestAlgorithm for Sha384 {
     type DigestOutput = Scalar48;
 }
 
+pub struct Sha256;
+
+impl DigestAlgorithm for Sha256 {
+    const OUTPUT_BITS: usize = 256;
+    type DigestOutput = Scalar32;
+}
+
 pub struct Secp384r1Curve;
 
 impl Curve for Secp384r1Curve {
@@ -103,83 +190,103 @@ impl Curve for Secp384r1Curve {
     type DigestType = Sha384;
 }
 
-pub struct PublicKey {
-    pub qx: Scalar48,
-    pub qy: Scalar48,
+pub struct Secp256r1Curve;
+
+impl Curve for Secp256r1Curve {
+    type Scalar = Scalar32;
+    type DigestType = Sha256;
+}
+
+/// Affine public key; the scalar type sets the curve size, P-384 by default
+pub struct PublicKey<S = Scalar48> {
+    pub qx: S,
+    pub qy: S,
 }
 
-impl CommonErrorType for PublicKey {
+impl<S> CommonErrorType for PublicKey<S> {
     type Error = SerdeError;
 }
 
-impl ToBytes for PublicKey {
-    fn to_bytes(&self, dest: &mut [u8], _endian: Endian) -> Result<(), Self::Error> {
-        const LEN: usize = Scalar48::LEN;
-        if dest.len() < 2 * LEN {
+/// Raw `qx || qy`; see [`PublicKey::from_sec1`] for the SEC 1 encodings
+impl<S: AsRef<[u8]>> ToBytes for PublicKey<S> {
+    fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<(), Self::Error> {
+        let len = self.qx.as_ref().len();
+        if dest.len() < 2 * len {
             return Err(SerdeError::BufferTooSmall);
         }
-        dest[..LEN].copy_from_slice(&self.qx.0);
-        dest[LEN..2 * LEN].copy_from_slice(&self.qy.0);
-        Ok(())
+        let (x, y) = dest.split_at_mut(len);
+        copy_scalar(x, self.qx.as_ref(), endian)?;
+        copy_scalar(y, self.qy.as_ref(), endian)
     }
 }
 
-impl FromBytes for PublicKey {
-    fn from_bytes(bytes: &[u8], _endian: Endian) -> Result<Self, Self::Error> {
-        const LEN: usize = Scalar48::LEN;
-        if bytes.len() < 2 * LEN {
+impl<S: AsMut<[u8]> + Default> FromBytes for PublicKey<S> {
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Result<Self, Self::Error> {
+        let mut qx = S::default();
+        let mut qy = S::default();
+        let len = qx.as_mut().len();
+        if bytes.len() < 2 * len {
             return Err(SerdeError::BufferTooSmall);
         }
-        let mut qx = [0u8; LEN];
-        let mut qy = [0u8; LEN];
-        qx.copy_from_slice(&bytes[..LEN]);
-        qy.copy_from_slice(&bytes[LEN..2 * LEN]);
-        Ok(PublicKey {
-            qx: Scalar48(qx),
-            qy: Scalar48(qy),
-        })
+        copy_scalar(qx.as_mut(), &bytes[..len], endian)?;
+        copy_scalar(qy.as_mut(), &bytes[len..2 * len], endian)?;
+        Ok(PublicKey { qx, qy })
     }
 }
 
-pub struct Signature {
-    pub r: Scalar48,
-    pub s: Scalar48,
+/// ECDSA signature; the scalar type sets the curve size, P-384 by default
+pub struct Signature<S = Scalar48> {
+    pub r: S,
+    pub s: S,
 }
 
-impl CommonErrorType for Signature {
+impl<S> CommonErrorType for Signature<S> {
     type Error = SerdeError;
 }
 
-impl ToBytes for Signature {
-    fn to_bytes(&self, dest: &mut [u8], _endian: Endian) -> Result<(), Self::Error> {
-        const LEN: usize = Scalar48::LEN;
-        if dest.len() < 2 * LEN {
+/// Raw fixed-width `r || s`, with no range check; see
+/// [`Signature::from_fixed`] and [`Signature::from_der`] for checked parsing
+impl<S: AsRef<[u8]>> ToBytes for Signature<S> {
+    fn to_bytes(&self, dest: &mut [u8], endian: Endian) -> Result<(), Self::Error> {
+        let len = self.r.as_ref().len();
+        if dest.len() < 2 * len {
             return Err(SerdeError::BufferTooSmall);
         }
-        dest[..LEN].copy_from_slice(&self.r.0);
-        dest[LEN..2 * LEN].copy_from_slice(&self.s.0);
-        Ok(())
+        let (r, s) = dest.split_at_mut(len);
+        copy_scalar(r, self.r.as_ref(), endian)?;
+        copy_scalar(s, self.s.as_ref(), endian)
     }
 }
 
-impl FromBytes for Signature {
-    fn from_bytes(bytes: &[u8], _endian: Endian) -> Result<Self, Self::Error> {
-        const LEN: usize = Scalar48::LEN;
-        if bytes.len() < 2 * LEN {
+impl<S: AsMut<[u8]> + Default> FromBytes for Signature<S> {
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Result<Self, Self::Error> {
+        let mut r = S::default();
+        let mut s = S::default();
+        let len = r.as_mut().len();
+        if bytes.len() < 2 * len {
             return Err(SerdeError::BufferTooSmall);
         }
-        let mut r = [0u8; LEN];
-        let mut s = [0u8; LEN];
-        r.copy_from_slice(&bytes[..LEN]);
-        s.copy_from_slice(&bytes[LEN..2 * LEN]);
-        Ok(Signature {
-            r: Scalar48(r),
-            s: Scalar48(s),
-        })
+        copy_scalar(r.as_mut(), &bytes[..len], endian)?;
+        copy_scalar(s.as_mut(), &bytes[len..2 * len], endian)?;
+        Ok(Signature { r, s })
+    }
+}
+
+impl<C: Curve<Scalar = Scalar48>> SignatureForCurve<C> for Signature<Scalar48> {
+    fn r(&self) -> &C::Scalar {
+        &self.r
+    }
+
+    fn s(&self) -> &C::Scalar {
+        &self.s
+    }
+
+    fn new(r: C::Scalar, s: C::Scalar) -> Self {
+        Signature { r, s }
     }
 }
 
-impl<C: Curve<Scalar = Scalar48>> SignatureForCurve<C> for Signature {
+impl<C: Curve<Scalar = Scalar32>> SignatureForCurve<C> for Signature<Scalar32> {
     fn r(&self) -> &C::Scalar {
         &self.r
     }
@@ -193,7 +300,21 @@ impl<C: Curve<Scalar = Scalar48>> SignatureForCurve<C> for Signature {
     }
 }
 
-impl<C: Curve<Scalar = Scalar48>> PubKeyForCurve<C> for PublicKey {
+impl<C: Curve<Scalar = Scalar48>> PubKeyForCurve<C> for PublicKey<Scalar48> {
+    fn x(&self) -> &C::Scalar {
+        &self.qx
+    }
+
+    fn y(&self) -> &C::Scalar {
+        &self.qy
+    }
+
+    fn new(x: C::Scalar, y: C::Scalar) -> Self {
+        PublicKey { qx: x, qy: y }
+    }
+}
+
+impl<C: Curve<Scalar = Scalar32>> PubKeyForCurve<C> for PublicKey<Scalar32> {
     fn x(&self) -> &C::Scalar {
         &self.qx
     }
@@ -207,11 +328,27 @@ impl<C: Curve<Scalar = Scalar48>> PubKeyForCurve<C> for PublicKey {
     }
 }
 
+/// Private scalar, big-endian; the scalar type sets the curve size, P-384
+/// by default. Wiped on drop.
+pub struct PrivateKey<S: AsMut<[u8]> = Scalar48> {
+    pub d: S,
+}
+
+impl<S: AsMut<[u8]>> Drop for PrivateKey<S> {
+    fn drop(&mut self) {
+        wipe(self.d.as_mut());
+    }
+}
+
 #[derive(Debug)]
 pub enum AspeedEcdsaError {
     InvalidSignature,
     Busy,
     BadInput,
+    /// The HMAC behind the RFC 6979 nonce failed
+    Mac(MacError),
+    /// A fresh signature did not verify against the signing key
+    FaultDetected,
 }
 
 impl Error for AspeedEcdsaError {
@@ -219,9 +356,76 @@ impl Error for AspeedEcdsaError {
         match self {
             Self::InvalidSignature => ErrorKind::InvalidSignature,
             Self::Busy => ErrorKind::Busy,
-            Self::BadInput => ErrorKind::Other,
+            Self::BadInput | Self::Mac(_) | Self::FaultDetected => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<KdfError> for AspeedEcdsaError {
+    fn from(err: KdfError) -> Self {
+        match err {
+            KdfError::Mac(err) => Self::Mac(err),
+            KdfError::InvalidOutputLength => Self::BadInput,
+        }
+    }
+}
+
+/// HMAC-SHA-384 state of the RFC 6979 nonce generator, wiped on drop
+struct Rfc6979 {
+    hace: HaceController<'static>,
+    k: [u8; Scalar48::LEN],
+    v: [u8; Scalar48::LEN],
+    /// HMAC output, as the key and output cannot alias
+    t: [u8; Scalar48::LEN],
+}
+
+impl Rfc6979 {
+    fn new() -> Self {
+        Self {
+            hace: HaceController::new(unsafe { &*Hace::ptr() }),
+            k: [0x00; Scalar48::LEN],
+            v: [0x01; Scalar48::LEN],
+            t: [0; Scalar48::LEN],
         }
     }
+
+    /// `K = HMAC_K(V || sep || parts)`, then `V = HMAC_K(V)`
+    fn update(&mut self, sep: u8, parts: &[&[u8]]) -> Result<(), AspeedEcdsaError> {
+        let mut input: [&[u8]; 4] = [&self.v, &[sep], &[], &[]];
+        let len = 2 + parts.len();
+        input[2..len].copy_from_slice(parts);
+        hmac_parts::<HmacSha384>(&mut self.hace, &self.k, &input[..len], &mut self.t)?;
+        self.k = self.t;
+        hmac_parts::<HmacSha384>(&mut self.hace, &self.k, &[&self.v], &mut self.t)?;
+        self.v = self.t;
+        Ok(())
+    }
+
+    /// Steps b to g, from the private key and the reduced digest
+    fn seed(&mut self, d: &[u8], h: &[u8]) -> Result<(), AspeedEcdsaError> {
+        self.update(0x00, &[d, h])?;
+        self.update(0x01, &[d, h])
+    }
+
+    /// Next candidate nonce, step h.2
+    fn candidate(&mut self) -> Result<&[u8], AspeedEcdsaError> {
+        hmac_parts::<HmacSha384>(&mut self.hace, &self.k, &[&self.v], &mut self.t)?;
+        self.v = self.t;
+        Ok(&self.v)
+    }
+
+    /// Step h.3, after a candidate is rejected
+    fn reseed(&mut self) -> Result<(), AspeedEcdsaError> {
+        self.update(0x00, &[])
+    }
+}
+
+impl Drop for Rfc6979 {
+    fn drop(&mut self) {
+        wipe(&mut self.k);
+        wipe(&mut self.v);
+        wipe(&mut self.t);
+    }
 }
 
 pub struct AspeedEcdsa<'a, D: DelayNs> {
@@ -264,9 +468,14 @@ impl<'a, D: DelayNs> AspeedEcdsa<'a, D> {
         }
     }
 
-    fn sram_wr(&self, offset: usize, data: &[u8; Scalar48::LEN]) {
+    /// Write a big-endian operand to a 384-bit SRAM slot. Shorter P-256
+    /// values are zero-extended to the engine's operand width.
+    fn sram_wr(&self, offset: usize, data: &[u8]) {
+        let mut operand = [0u8; Scalar48::LEN];
+        operand[Scalar48::LEN - data.len()..].copy_from_slice(data);
         for i in (0..Scalar48::LEN).step_by(4) {
-            let val = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+            let val =
+                u32::from_le_bytes([operand[i], operand[i + 1], operand[i + 2], operand[i + 3]]);
             unsafe {
                 write_volatile(self.sram_base.as_ptr().add((offset + i) / 4), val);
             }
@@ -280,6 +489,13 @@ impl<'a, D: DelayNs> AspeedEcdsa<'a, D> {
         }
     }
 
+    /// Both curves have a = -3, which the engine takes as a zero `a`
+    fn clear_param_a(&self) {
+        for i in (0..Scalar48::LEN).step_by(4) {
+            self.sram_wr_u32(SRAM_DST_A + i, 0);
+        }
+    }
+
     fn load_secp384r1_params(&self) {
         // (1) Gx
         self.load_param(ASPEED_ECDSA_PAR_GX, SRAM_DST_GX);
@@ -290,36 +506,39 @@ impl<'a, D: DelayNs> AspeedEcdsa<'a, D> {
         // (4) n
         self.load_param(ASPEED_ECDSA_PAR_N, SRAM_DST_N);
         // (5) a
-        for i in (0..Scalar48::LEN).step_by(4) {
-            self.sram_wr_u32(SRAM_DST_A + i, 0);
-        }
+        self.clear_param_a();
     }
-}
 
-impl<D> EcdsaVerify<Secp384r1Curve> for AspeedEcdsa<'_, D>
-where
-    D: DelayNs,
-{
-    type PublicKey = PublicKey;
-    type Signature = Signature;
+    fn load_secp256r1_params(&self) {
+        // The ROM parameter table only holds secp384r1
+        self.sram_wr(SRAM_DST_GX, P256.gx);
+        self.sram_wr(SRAM_DST_GY, P256.gy);
+        self.sram_wr(SRAM_DST_P, P256.p);
+        self.sram_wr(SRAM_DST_N, P256.n);
+        self.clear_param_a();
+    }
 
-    fn verify(
+    /// Run the engine's verification on big-endian operands of the curve's
+    /// size, after `load_params` has written the curve to SRAM. The public
+    /// key must be on the curve and `r` and `s` in `[1, n - 1]`; operands
+    /// that are not are refused before they reach the engine.
+    fn verify_with(
         &mut self,
-        public_key: &Self::PublicKey,
-        digest: <<Secp384r1Curve as Curve>::DigestType as DigestAlgorithm>::DigestOutput,
-        signature: &Self::Signature,
-    ) -> Result<(), Self::Error> {
+        curve: &'static CurveParams,
+        load_params: fn(&Self),
+        operands: [&[u8]; 5],
+    ) -> Result<(), AspeedEcdsaError> {
+        let [qx, qy, r, s, m] = operands;
+        let group = EcGroup::new(curve);
+        if !group.is_on_curve(qx, qy) {
+            return Err(AspeedEcdsaError::BadInput);
+        }
+        if !group.is_valid_scalar(r) || !group.is_valid_scalar(s) {
+            return Err(AspeedEcdsaError::InvalidSignature);
+        }
+        let _lock = EngineLock::acquire().ok_or(AspeedEcdsaError::Busy)?;
         unsafe {
-            let digest_bytes = digest.as_ref();
-            if digest_bytes.len() != 48 {
-                return Err(AspeedEcdsaError::BadInput);
-            }
-
-            let digest_array: &[u8; 48] = digest_bytes
-                .try_into()
-                .map_err(|_| AspeedEcdsaError::BadInput)?;
-
-            self.sec_wr(0x7c, 0x0100_f00b);
+            self.sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_PARAMS);
 
             // Reset Engine
             self.secure.secure0b4().write(|w| w.bits(0));
@@ -328,21 +547,21 @@ where
                 .write(|w| w.sec_boot_ecceng_enbl().set_bit());
             self.delay.delay_ns(5000);
 
-            self.load_secp384r1_params();
+            load_params(self);
 
-            self.sec_wr(0x7c, 0x0300_f00b);
+            self.sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_WRITE);
 
             // Write qx, qy, r, s
-            self.sram_wr(SRAM_DST_QX, &public_key.qx.0);
-            self.sram_wr(SRAM_DST_QY, &public_key.qy.0);
-            self.sram_wr(SRAM_DST_R, &signature.r.0);
-            self.sram_wr(SRAM_DST_S, &signature.s.0);
-            self.sram_wr(SRAM_DST_M, digest_array);
+            self.sram_wr(SRAM_DST_QX, qx);
+            self.sram_wr(SRAM_DST_QY, qy);
+            self.sram_wr(SRAM_DST_R, r);
+            self.sram_wr(SRAM_DST_S, s);
+            self.sram_wr(SRAM_DST_M, m);
 
-            self.sec_wr(0x7c, 0);
+            self.sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_LOCKED);
 
             // Write ECDSA instruction command
-            self.sram_wr_u32(0x23c0, 1);
+            self.sram_wr_u32(SRAM_INSTR, ECC_INSTR_VERIFY);
 
             // Trigger ECDSA Engine
             self.secure
@@ -371,4 +590,166 @@ where
             Err(AspeedEcdsaError::Busy)
         }
     }
+
+    /// Zero every operand slot in engine SRAM
+    fn clear_operands(&self) {
+        self.sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_WRITE);
+        for offset in (SRAM_DST_GX..SRAM_OPERANDS_END).step_by(4) {
+            self.sram_wr_u32(offset, 0);
+        }
+        self.sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_LOCKED);
+    }
+
+    /// P-384 signature of `digest` with private scalar `d` and the RFC 6979
+    /// nonce, using HMAC-SHA-384 on the HACE.
+    ///
+    /// `k * G` is computed by the constant-time ladder in [`crate::ecc`], so
+    /// `d` never reaches engine SRAM. Before the signature is released the
+    /// engine verifies it against `d * G`, which catches faults in the
+    /// computation; its operand slots are zeroed afterwards.
+    fn sign_p384(
+        &mut self,
+        d: &[u8],
+        digest: &[u8],
+        signature: &mut Signature,
+    ) -> Result<(), AspeedEcdsaError> {
+        let group = EcGroup::new(&P384);
+        if !group.is_valid_scalar(d) || digest.len() != Scalar48::LEN {
+            return Err(AspeedEcdsaError::BadInput);
+        }
+
+        let mut result = Self::rfc6979_sign(&group, d, digest, signature);
+        if result.is_ok() {
+            let mut public_key = PublicKey {
+                qx: Scalar48::default(),
+                qy: Scalar48::default(),
+            };
+            group.mul_base(d, &mut public_key.qx.0, &mut public_key.qy.0);
+            let mut m = Scalar48::default();
+            m.0.copy_from_slice(digest);
+            let verified = EcdsaVerify::<Secp384r1Curve>::verify(self, &public_key, m, signature);
+            self.clear_operands();
+            match verified {
+                Ok(()) => {}
+                Err(AspeedEcdsaError::InvalidSignature) => {
+                    result = Err(AspeedEcdsaError::FaultDetected);
+                }
+                Err(err) => result = Err(err),
+            }
+        }
+        if result.is_err() {
+            wipe(&mut signature.r.0);
+            wipe(&mut signature.s.0);
+        }
+        result
+    }
+
+    /// ECDSA with the deterministic nonce of RFC 6979 section 3.2. The
+    /// digest and the group order are both 384 bits, so `bits2int` is the
+    /// identity and `bits2octets` reduces the digest mod n.
+    fn rfc6979_sign(
+        group: &EcGroup,
+        d: &[u8],
+        digest: &[u8],
+        signature: &mut Signature,
+    ) -> Result<(), AspeedEcdsaError> {
+        let mut h = [0u8; Scalar48::LEN];
+        group.reduce_scalar(digest, &mut h);
+        let mut nonces = Rfc6979::new();
+        let result = nonces.seed(d, &h);
+        wipe(&mut h);
+        result?;
+
+        loop {
+            let k = nonces.candidate()?;
+            if group.is_valid_scalar(k)
+                && group.ecdsa_sign(d, digest, k, &mut signature.r.0, &mut signature.s.0)
+            {
+                return Ok(());
+            }
+            nonces.reseed()?;
+        }
+    }
+}
+
+impl<D> EcdsaVerify<Secp384r1Curve> for AspeedEcdsa<'_, D>
+where
+    D: DelayNs,
+{
+    type PublicKey = PublicKey;
+    type Signature = Signature;
+
+    fn verify(
+        &mut self,
+        public_key: &Self::PublicKey,
+        digest: <<Secp384r1Curve as Curve>::DigestType as DigestAlgorithm>::DigestOutput,
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error> {
+        let digest_bytes = digest.as_ref();
+        if digest_bytes.len() != 48 {
+            return Err(AspeedEcdsaError::BadInput);
+        }
+
+        self.verify_with(
+            &P384,
+            Self::load_secp384r1_params,
+            [
+                &public_key.qx.0,
+                &public_key.qy.0,
+                &signature.r.0,
+                &signature.s.0,
+                digest_bytes,
+            ],
+        )
+    }
+}
+
+impl<D> EcdsaVerify<Secp256r1Curve> for AspeedEcdsa<'_, D>
+where
+    D: DelayNs,
+{
+    type PublicKey = PublicKey<Scalar32>;
+    type Signature = Signature<Scalar32>;
+
+    fn verify(
+        &mut self,
+        public_key: &Self::PublicKey,
+        digest: <<Secp256r1Curve as Curve>::DigestType as DigestAlgorithm>::DigestOutput,
+        signature: &Self::Signature,
+    ) -> Result<(), Self::Error> {
+        self.verify_with(
+            &P256,
+            Self::load_secp256r1_params,
+            [
+                &public_key.qx.0,
+                &public_key.qy.0,
+                &signature.r.0,
+                &signature.s.0,
+                &digest.0,
+            ],
+        )
+    }
+}
+
+impl<D> EcdsaSign<Secp384r1Curve> for AspeedEcdsa<'_, D>
+where
+    D: DelayNs,
+{
+    type PrivateKey = PrivateKey;
+    type Signature = Signature;
+
+    /// Nonces are derived per RFC 6979, so `rng` is not used
+    fn sign<R: RngCore + CryptoRng>(
+        &mut self,
+        private_key: &Self::PrivateKey,
+        digest: <<Secp384r1Curve as Curve>::DigestType as DigestAlgorithm>::DigestOutput,
+        _rng: &mut R,
+    ) -> Result<Self::Signature, Self::Error> {
+        let mut signature = Signature {
+            r: Scalar48::default(),
+            s: Scalar48::default(),
+        };
+        self.sign_p384(&private_key.d.0, &digest.0, &mut signature)?;
+        Ok(signature)
+    }
 }
@@ -1,9 +1,21 @@
 // Licensed under the Apache-2.0 license
 
-use ast1060_pac::Hace;
-use core::convert::Infallible;
-use proposed_traits::digest::ErrorType as DigestErrorType;
+use crate::hash::HashError;
+use crate::hmac::MacError;
+use crate::syscon::{ClockId, Error as SysConError, ResetId, SysCon};
+use ast1060_pac::{Hace, Scu};
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use cortex_m::interrupt::Mutex;
+use embedded_hal::delay::DelayNs;
+use proposed_traits::digest::{ErrorKind as DigestErrorKind, ErrorType as DigestErrorType};
 use proposed_traits::mac::ErrorType as MacErrorType;
+use proposed_traits::system_control::{ClockControl, ResetControl};
 
 const SHA1_IV: [u32; 8] = [
     0x0123_4567,
@@ -116,9 +128,22 @@ const SHA512_256_IV: [u32; 16] = [
 
 const HACE_SHA_BE_EN: u32 = 1 << 3;
 const HACE_CMD_ACC_MODE: u32 = 1 << 8;
+const HACE_CMD_INT_EN: u32 = 1 << 9;
 pub const HACE_SG_EN: u32 = 1 << 18;
 pub const HACE_SG_LAST: u32 = 1 << 31;
 
+/// Number of scatter-gather descriptors held in each hash context
+pub const HACE_SG_MAX_ENTRIES: usize = 16;
+
+/// Status polls allowed before a hash operation is declared hung
+pub const HACE_DEFAULT_TIMEOUT_POLLS: u32 = 10_000_000;
+
+/// Required alignment of every address handed to the engine
+pub(crate) const HACE_DMA_ALIGN: u32 = 8;
+
+/// How long HACE is held in reset by [`HaceController::recover`]
+const HACE_RESET_PULSE: Duration = Duration::from_micros(10);
+
 const HACE_ALGO_SHA1: u32 = 1 << 5;
 const HACE_ALGO_SHA224: u32 = 1 << 6;
 const HACE_ALGO_SHA256: u32 = (1 << 4) | (1 << 6);
@@ -139,9 +164,18 @@ impl ContextCleanup for crate::hace_controller::HaceController<'_> {
         ctx.buffer.fill(0);
         ctx.digest.fill(0);
         ctx.digcnt = [0; 2];
-
-        unsafe {
-            self.hace.hace30().write(|w| w.bits(0));
+        ctx.poisoned = false;
+
+        // Only clear the command while no operation is in flight, which may
+        // belong to another session
+        if HACE_HASH_BUSY
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            unsafe {
+                self.hace.hace30().write(|w| w.bits(0));
+            }
+            HACE_HASH_BUSY.store(false, Ordering::Release);
         }
     }
 }
@@ -162,7 +196,7 @@ impl AspeedSg {
 #[repr(C)]
 #[repr(align(64))]
 pub struct AspeedHashContext {
-    pub sg: [AspeedSg; 2],
+    pub sg: [AspeedSg; HACE_SG_MAX_ENTRIES],
     pub digest: [u8; 64],
     pub method: u32,
     pub block_size: u32,
@@ -174,12 +208,14 @@ pub struct AspeedHashContext {
     pub bufcnt: u32,
     pub buffer: [u8; 256],
     pub iv_size: u8,
+    /// Set when an update failed part way, so the running digest is lost
+    pub poisoned: bool,
 }
 
 impl Default for AspeedHashContext {
     fn default() -> Self {
         Self {
-            sg: [AspeedSg::default(); 2],
+            sg: [AspeedSg::default(); HACE_SG_MAX_ENTRIES],
             digest: [0; 64],
             method: 0,
             block_size: 0,
@@ -191,6 +227,7 @@ impl Default for AspeedHashContext {
             bufcnt: 0,
             buffer: [0; 256],
             iv_size: 0,
+            poisoned: false,
         }
     }
 }
@@ -199,7 +236,7 @@ impl AspeedHashContext {
     #[must_use]
     pub const fn new() -> Self {
         Self {
-            sg: [AspeedSg::new(), AspeedSg::new()],
+            sg: [AspeedSg::new(); HACE_SG_MAX_ENTRIES],
             digest: [0; 64],
             method: 0,
             block_size: 0,
@@ -211,6 +248,7 @@ impl AspeedHashContext {
             bufcnt: 0,
             buffer: [0; 256],
             iv_size: 0,
+            poisoned: false,
         }
     }
 }
@@ -232,11 +270,95 @@ impl SectionPlacedContext {
     }
 }
 
-/// Context specifically allocated in non-cacheable RAM section
+/// Number of hash contexts available for concurrent sessions
+pub const HASH_CTX_POOL_SIZE: usize = 4;
+
+/// Contexts specifically allocated in non-cacheable RAM section
 #[link_section = ".ram_nc"]
-static SHARED_HASH_CTX: SectionPlacedContext = SectionPlacedContext::new();
+static HASH_CTX_POOL: [SectionPlacedContext; HASH_CTX_POOL_SIZE] =
+    [const { SectionPlacedContext::new() }; HASH_CTX_POOL_SIZE];
+
+static HASH_CTX_IN_USE: [AtomicBool; HASH_CTX_POOL_SIZE] =
+    [const { AtomicBool::new(false) }; HASH_CTX_POOL_SIZE];
 
-#[derive(Copy, Clone)]
+/// Set while a hash operation has been started and not yet collected
+static HACE_HASH_BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Set by [`HaceController::handle_interrupt`] when the engine signals completion
+static HACE_HASH_DONE: AtomicBool = AtomicBool::new(false);
+
+/// Task waiting on the in-flight hash operation
+static HACE_HASH_WAKER: Mutex<RefCell<Option<Waker>>> = Mutex::new(RefCell::new(None));
+
+/// Exclusive ownership of one context slot from the non-cacheable pool.
+///
+/// The running digest, byte counters and partial block of a hash live in the
+/// slot, so the engine can be handed to another session between operations
+/// and picked up again later. The slot returns to the pool on drop.
+pub struct HashSession {
+    slot: usize,
+}
+
+impl HashSession {
+    /// Reserve a free context slot, or `None` if the pool is exhausted.
+    #[must_use]
+    pub fn acquire() -> Option<Self> {
+        HASH_CTX_IN_USE
+            .iter()
+            .position(|in_use| {
+                in_use
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            })
+            .map(|slot| Self { slot })
+    }
+
+    #[must_use]
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    fn ctx(&self) -> NonNull<AspeedHashContext> {
+        unsafe { NonNull::new_unchecked(HASH_CTX_POOL[self.slot].get()) }
+    }
+}
+
+impl Drop for HashSession {
+    fn drop(&mut self) {
+        HASH_CTX_IN_USE[self.slot].store(false, Ordering::Release);
+    }
+}
+
+/// Failures reported by the hash engine itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaceError {
+    /// The engine did not signal completion within the polling budget
+    Timeout,
+    /// An address handed to the engine is not 8-byte aligned
+    BadAlignment,
+    /// The engine clock is gated or the block is held in reset
+    EngineDisabled,
+    /// Another operation is still in flight on the engine
+    Busy,
+    /// An earlier update of this hash failed, so its state is lost
+    Poisoned,
+}
+
+/// Check that HACE is clocked and out of reset. Both the hash and the
+/// crypto engine sit behind the same clock and reset line.
+pub(crate) fn hace_engine_status() -> Result<(), HaceError> {
+    let scu = unsafe { &*Scu::ptr() };
+    let clock_stopped = scu.scu080().read().bits() & (1 << ClockId::ClkYCLK as u8) != 0;
+    let in_reset = scu.scu040().read().bits() & (1 << ResetId::RstHACE as u8) != 0;
+
+    if clock_stopped || in_reset {
+        Err(HaceError::EngineDisabled)
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum HashAlgo {
     SHA1,
     SHA224,
@@ -318,7 +440,9 @@ impl HashAlgo {
 pub struct HaceController<'ctrl> {
     pub hace: &'ctrl Hace,
     pub algo: HashAlgo,
-    pub aspeed_hash_ctx: AspeedHashContext, // Own the context instead of using a pointer
+    ctx: Option<NonNull<AspeedHashContext>>, // Context of the bound session
+    irq_mode: bool,
+    timeout_polls: u32,
 }
 
 impl<'ctrl> HaceController<'ctrl> {
@@ -327,31 +451,110 @@ impl<'ctrl> HaceController<'ctrl> {
         Self {
             hace,
             algo: HashAlgo::SHA256,
-            aspeed_hash_ctx: AspeedHashContext::new(), // Create a new context instance
+            ctx: None,
+            irq_mode: false,
+            timeout_polls: HACE_DEFAULT_TIMEOUT_POLLS,
+        }
+    }
+
+    /// Number of status polls a blocking operation waits before giving up
+    /// with [`HaceError::Timeout`].
+    pub fn set_timeout(&mut self, polls: u32) {
+        self.timeout_polls = polls;
+    }
+
+    /// Check that the engine is clocked and out of reset.
+    pub fn check_engine(&self) -> Result<(), HaceError> {
+        hace_engine_status()
+    }
+
+    /// Bring the engine back after a timeout or a hardware fault.
+    ///
+    /// Ungates the HACE clock, pulses the HACE reset and releases the hash
+    /// and crypto engines so new operations can be started. Any hash or
+    /// cipher operation in progress is lost and has to be started again.
+    pub fn recover<D: DelayNs>(&mut self, syscon: &mut SysCon<D>) -> Result<(), HashError> {
+        match syscon.enable(&ClockId::ClkYCLK) {
+            Ok(()) | Err(SysConError::ClockAlreadyEnabled) => {}
+            Err(_) => return Err(HaceError::EngineDisabled.into()),
         }
+        syscon
+            .reset_pulse(&ResetId::RstHACE, HACE_RESET_PULSE)
+            .map_err(|_| HaceError::EngineDisabled)?;
+
+        unsafe {
+            self.hace.hace30().write(|w| w.bits(0));
+        }
+        self.hace.hace1c().write(|w| w.hash_intflag().set_bit());
+        HACE_HASH_DONE.store(false, Ordering::Relaxed);
+        HACE_HASH_BUSY.store(false, Ordering::Release);
+        cortex_m::interrupt::free(|cs| {
+            HACE_HASH_WAKER.borrow(cs).borrow_mut().take();
+        });
+        crate::crypto::release_engine(self.hace);
+
+        self.check_engine()?;
+        Ok(())
+    }
+
+    /// Have the engine raise the HACE interrupt when an operation completes.
+    ///
+    /// The application must unmask the HACE IRQ and call
+    /// [`HaceController::handle_interrupt`] from its handler. Without
+    /// interrupt mode, completion is detected by polling the status flag.
+    pub fn set_interrupt_mode(&mut self, enabled: bool) {
+        self.irq_mode = enabled;
+    }
+
+    /// Acknowledge a hash completion interrupt and wake the waiting task.
+    ///
+    /// Call this from the HACE interrupt handler.
+    pub fn handle_interrupt(hace: &Hace) {
+        if hace.hace1c().read().hash_intflag().bit_is_set() {
+            hace.hace1c().write(|w| w.hash_intflag().set_bit());
+            HACE_HASH_DONE.store(true, Ordering::Release);
+            cortex_m::interrupt::free(|cs| {
+                if let Some(waker) = HACE_HASH_WAKER.borrow(cs).borrow_mut().take() {
+                    waker.wake();
+                }
+            });
+        }
+    }
+
+    /// Point the controller at the context slot owned by `session`
+    pub(crate) fn bind_session(&mut self, session: &HashSession) {
+        self.ctx = Some(session.ctx());
     }
 
-    /// Get a mutable reference to the shared context in `.ram_nc` section
-    /// This approach uses the section-placed context directly
-    pub fn shared_ctx() -> *mut AspeedHashContext {
-        SHARED_HASH_CTX.get()
+    /// Detach the controller from its session before the slot is released
+    pub(crate) fn unbind_session(&mut self) {
+        self.ctx = None;
     }
 }
 
 impl DigestErrorType for HaceController<'_> {
-    type Error = Infallible;
+    type Error = HashError;
 }
 
 impl MacErrorType for HaceController<'_> {
-    type Error = Infallible;
+    type Error = MacError;
 }
 
-impl HaceController<'_> {
+impl<'ctrl> HaceController<'ctrl> {
+    /// Get a mutable reference to the context of the bound session
+    ///
+    /// # Panics
+    /// Panics if no session is bound to the controller.
     pub fn ctx_mut(&mut self) -> &mut AspeedHashContext {
-        unsafe { &mut *Self::shared_ctx() }
+        let ctx = self.ctx.expect("no hash session bound to controller");
+        unsafe { &mut *ctx.as_ptr() }
     }
 
-    pub fn start_hash_operation(&mut self, len: u32) {
+    /// Program the engine for `len` bytes and start it without waiting
+    pub fn kick_hash_operation(&mut self, len: u32) -> Result<(), HashError> {
+        self.check_engine()?;
+
+        let irq_mode = self.irq_mode;
         let ctx = self.ctx_mut();
 
         let src_addr = if (ctx.method & HACE_SG_EN) != 0 {
@@ -361,7 +564,23 @@ impl HaceController<'_> {
         };
 
         let digest_addr = ctx.digest.as_ptr() as u32;
-        let method = ctx.method;
+        let method = if irq_mode {
+            ctx.method | HACE_CMD_INT_EN
+        } else {
+            ctx.method
+        };
+
+        if (src_addr | digest_addr) % HACE_DMA_ALIGN != 0 {
+            return Err(HaceError::BadAlignment.into());
+        }
+
+        if HACE_HASH_BUSY
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(HaceError::Busy.into());
+        }
+        HACE_HASH_DONE.store(false, Ordering::Relaxed);
 
         unsafe {
             self.hace.hace1c().write(|w| w.hash_intflag().set_bit());
@@ -370,14 +589,176 @@ impl HaceController<'_> {
             self.hace.hace28().write(|w| w.bits(digest_addr));
             self.hace.hace2c().write(|w| w.bits(len));
             self.hace.hace30().write(|w| w.bits(method));
-            // blocking wait until hash engine ready
-            while self.hace.hace1c().read().hash_intflag().bit_is_clear() {
-                // wait for the hash operation to complete
-                cortex_m::asm::nop();
+        }
+        Ok(())
+    }
+
+    /// Whether the operation started by `kick_hash_operation` has finished
+    #[must_use]
+    pub fn is_hash_done(&self) -> bool {
+        HACE_HASH_DONE.load(Ordering::Acquire)
+            || self.hace.hace1c().read().hash_intflag().bit_is_set()
+    }
+
+    /// Acknowledge a finished operation and release the engine
+    pub fn complete_hash_operation(&mut self) {
+        self.hace.hace1c().write(|w| w.hash_intflag().set_bit());
+        HACE_HASH_DONE.store(false, Ordering::Relaxed);
+        HACE_HASH_BUSY.store(false, Ordering::Release);
+    }
+
+    /// Poll until the running operation finishes or the timeout expires.
+    ///
+    /// On timeout the engine stays claimed, since it may still be reading
+    /// memory; further operations fail with [`HaceError::Busy`] until
+    /// [`HaceController::recover`] has reset it.
+    pub fn wait_hash_done(&self) -> Result<(), HashError> {
+        for _ in 0..self.timeout_polls {
+            if self.is_hash_done() {
+                return Ok(());
             }
+            cortex_m::asm::nop();
+        }
+        if self.is_hash_done() {
+            Ok(())
+        } else {
+            Err(HaceError::Timeout.into())
         }
     }
 
+    pub fn start_hash_operation(&mut self, len: u32) -> Result<(), HashError> {
+        self.kick_hash_operation(len)?;
+        // blocking wait until hash engine ready
+        self.wait_hash_done()?;
+        self.complete_hash_operation();
+        Ok(())
+    }
+
+    /// Begin a new hash of `self.algo` in the bound context
+    pub fn start_digest(&mut self) {
+        let method = self.algo.hash_cmd();
+        let block_size = u32::try_from(self.algo.block_size()).unwrap();
+        self.copy_iv_to_digest();
+
+        let ctx = self.ctx_mut();
+        ctx.method = method;
+        ctx.block_size = block_size;
+        ctx.bufcnt = 0;
+        ctx.digcnt = [0; 2];
+        ctx.poisoned = false;
+    }
+
+    /// Pad the buffered tail and run the last block(s) through the engine.
+    /// The final digest is left in the context's `digest` field.
+    pub fn finish_digest(&mut self) -> Result<(), HashError> {
+        if self.ctx_mut().poisoned {
+            return Err(HaceError::Poisoned.into());
+        }
+        self.fill_padding(0);
+
+        let bufcnt = {
+            let ctx = self.ctx_mut();
+
+            ctx.sg[0].addr = ctx.buffer.as_ptr() as u32;
+            ctx.sg[0].len = ctx.bufcnt | HACE_SG_LAST;
+            ctx.method |= HACE_SG_EN;
+
+            ctx.bufcnt
+        };
+
+        self.start_hash_operation(bufcnt)
+    }
+
+    /// Hash a list of non-contiguous buffers in one engine pass.
+    ///
+    /// The partial block left over from earlier updates and the given slices
+    /// are chained into the context's scatter-gather list. Only whole blocks
+    /// go to the engine; the tail is kept in the context buffer for the next
+    /// update or for padding. At most `HACE_SG_MAX_ENTRIES - 1` slices fit in
+    /// one pass, since the buffered block takes the first descriptor.
+    pub fn update_sg(&mut self, inputs: &[&[u8]]) -> Result<(), HashError> {
+        self.start_update_sg(inputs)?.wait()
+    }
+
+    /// Start hashing `inputs` like [`HaceController::update_sg`] and return
+    /// without waiting for the engine.
+    ///
+    /// The returned [`PendingUpdate`] borrows the inputs until the engine has
+    /// read them; it can be polled with `is_done`/`try_finish`, blocked on with
+    /// `wait`, or awaited from an async executor.
+    pub fn start_update_sg<'p>(
+        &'p mut self,
+        inputs: &'p [&'p [u8]],
+    ) -> Result<PendingUpdate<'p, 'ctrl>, HashError> {
+        if inputs.len() >= HACE_SG_MAX_ENTRIES {
+            return Err(DigestErrorKind::InvalidInputLength.into());
+        }
+
+        let input_len: u64 = inputs.iter().map(|input| input.len() as u64).sum();
+        let ctx = self.ctx_mut();
+        if ctx.poisoned {
+            return Err(HaceError::Poisoned.into());
+        }
+
+        let bufcnt = u64::from(ctx.bufcnt);
+        let total = bufcnt + input_len;
+        let total_len = total - total % u64::from(ctx.block_size);
+
+        // Bytes at the front of `inputs` consumed by the engine pass
+        let mut skip = 0;
+
+        if total_len != 0 {
+            let len = u32::try_from(total_len).map_err(|_| DigestErrorKind::InvalidInputLength)?;
+            let mut i = 0;
+            if bufcnt != 0 {
+                ctx.sg[0].addr = ctx.buffer.as_ptr() as u32;
+                ctx.sg[0].len = ctx.bufcnt;
+                i += 1;
+            }
+
+            let mut left = total_len - bufcnt;
+            skip = usize::try_from(left).map_err(|_| DigestErrorKind::InvalidInputLength)?;
+            for input in inputs {
+                if left == 0 {
+                    break;
+                }
+                let take = left.min(input.len() as u64);
+                if take == 0 {
+                    continue;
+                }
+                ctx.sg[i].addr = input.as_ptr() as u32;
+                ctx.sg[i].len =
+                    u32::try_from(take).map_err(|_| DigestErrorKind::InvalidInputLength)?;
+                left -= take;
+                i += 1;
+            }
+            ctx.sg[i - 1].len |= HACE_SG_LAST;
+            ctx.method |= HACE_SG_EN;
+
+            self.kick_hash_operation(len)?;
+        }
+
+        let ctx = self.ctx_mut();
+        let (new_len, carry) = ctx.digcnt[0].overflowing_add(input_len);
+        ctx.digcnt[0] = new_len;
+        if carry {
+            ctx.digcnt[1] += 1;
+        }
+        if total_len != 0 {
+            ctx.bufcnt = 0;
+        }
+
+        Ok(PendingUpdate {
+            controller: self,
+            inputs,
+            skip,
+            in_flight: total_len != 0,
+            finished: false,
+            failed: false,
+            polls: 0,
+        })
+    }
+
     pub fn copy_iv_to_digest(&mut self) {
         let iv = self.algo.iv();
         let iv_bytes =
@@ -386,28 +767,20 @@ impl HaceController<'_> {
         self.ctx_mut().digest[..iv_bytes.len()].copy_from_slice(iv_bytes);
     }
 
-    pub fn hash_key(&mut self, key: &impl AsRef<[u8]>) {
-        let key_bytes = key.as_ref();
-        let key_len = key_bytes.len();
+    /// Replace an HMAC key longer than the block size by its digest
+    pub fn hash_key(&mut self, key: &impl AsRef<[u8]>) -> Result<(), HashError> {
         let digest_len = self.algo.digest_size();
 
-        self.ctx_mut().digcnt[0] = key_len as u64;
-        self.ctx_mut().bufcnt = u32::try_from(key_len).expect("key_len too large to fit in u32");
-        self.ctx_mut().buffer[..key_len].copy_from_slice(key_bytes);
-        self.ctx_mut().method &= !HACE_SG_EN; // Disable SG mode for key hashing
-        self.copy_iv_to_digest();
-        self.fill_padding(0);
-        let bufcnt = self.ctx_mut().bufcnt;
-        self.start_hash_operation(bufcnt);
-
-        let slice =
-            unsafe { core::slice::from_raw_parts(self.ctx_mut().digest.as_ptr(), digest_len) };
+        self.start_digest();
+        self.update_sg(&[key.as_ref()])?;
+        self.finish_digest()?;
 
-        self.ctx_mut().key[..digest_len].copy_from_slice(slice);
-        self.ctx_mut().ipad[..digest_len].copy_from_slice(slice);
-        self.ctx_mut().opad[..digest_len].copy_from_slice(slice);
-        self.ctx_mut().key_len =
-            u32::try_from(digest_len).expect("digest_len too large to fit in u32");
+        let ctx = self.ctx_mut();
+        ctx.key[..digest_len].copy_from_slice(&ctx.digest[..digest_len]);
+        ctx.ipad[..digest_len].copy_from_slice(&ctx.digest[..digest_len]);
+        ctx.opad[..digest_len].copy_from_slice(&ctx.digest[..digest_len]);
+        ctx.key_len = u32::try_from(digest_len).expect("digest_len too large to fit in u32");
+        Ok(())
     }
 
     pub fn fill_padding(&mut self, remaining: usize) {
@@ -446,3 +819,145 @@ impl HaceController<'_> {
         }
     }
 }
+
+/// A hash update that has been handed to the engine.
+///
+/// Dropping it before completion blocks until the engine is done, since the
+/// engine may still be reading the borrowed input. If the engine does not
+/// finish, the hash context is poisoned and later updates and the final
+/// digest fail with [`HaceError::Poisoned`].
+pub struct PendingUpdate<'p, 'ctrl> {
+    controller: &'p mut HaceController<'ctrl>,
+    inputs: &'p [&'p [u8]],
+    skip: usize,
+    in_flight: bool,
+    finished: bool,
+    failed: bool,
+    /// Times the future has been polled while the engine was busy
+    polls: u32,
+}
+
+impl PendingUpdate<'_, '_> {
+    /// Whether the engine has finished with the inputs
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.finished || !self.in_flight || self.controller.is_hash_done()
+    }
+
+    /// Complete the update if the engine is done. Returns `true` once the
+    /// update has been fully applied to the hash context.
+    pub fn try_finish(&mut self) -> bool {
+        if self.failed {
+            return false;
+        }
+        if self.finished {
+            return true;
+        }
+        if !self.is_done() {
+            return false;
+        }
+        if self.in_flight {
+            self.controller.complete_hash_operation();
+        }
+
+        // Keep whatever did not make up a whole block
+        let ctx = self.controller.ctx_mut();
+        let mut skip = self.skip;
+        for input in self.inputs {
+            if skip >= input.len() {
+                skip -= input.len();
+                continue;
+            }
+            let tail = &input[skip..];
+            let start = ctx.bufcnt as usize;
+            ctx.buffer[start..start + tail.len()].copy_from_slice(tail);
+            ctx.bufcnt += u32::try_from(tail.len()).expect("tail shorter than a block");
+            skip = 0;
+        }
+
+        self.finished = true;
+        true
+    }
+
+    /// Give up on an update the engine did not finish. The tail of the
+    /// input is lost, so the context is poisoned.
+    fn abandon(&mut self) {
+        self.failed = true;
+        self.controller.ctx_mut().poisoned = true;
+    }
+
+    /// Block until the update has been applied, or fail with
+    /// [`HaceError::Timeout`] if the engine does not finish in time.
+    pub fn wait(mut self) -> Result<(), HashError> {
+        if self.failed {
+            return Err(HaceError::Poisoned.into());
+        }
+        let result = if self.finished || !self.in_flight {
+            Ok(())
+        } else {
+            self.controller.wait_hash_done()
+        };
+
+        match result {
+            Ok(()) => {
+                self.try_finish();
+                Ok(())
+            }
+            Err(e) => {
+                // Don't block again on drop
+                self.abandon();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Future for PendingUpdate<'_, '_> {
+    type Output = Result<(), HashError>;
+
+    /// Each poll checks the status flag, and the update fails with
+    /// [`HaceError::Timeout`] after as many polls as a blocking wait allows.
+    /// The task is woken again at once so the limit runs out even when no
+    /// interrupt arrives; in interrupt mode the handler wakes it as well.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.failed {
+            return Poll::Ready(Err(HaceError::Poisoned.into()));
+        }
+        if this.try_finish() {
+            return Poll::Ready(Ok(()));
+        }
+        if this.polls >= this.controller.timeout_polls {
+            this.abandon();
+            return Poll::Ready(Err(HaceError::Timeout.into()));
+        }
+        this.polls += 1;
+
+        if this.controller.irq_mode {
+            cortex_m::interrupt::free(|cs| {
+                HACE_HASH_WAKER.borrow(cs).replace(Some(cx.waker().clone()));
+            });
+
+            // Completion may have landed before the waker was stored
+            if this.try_finish() {
+                return Poll::Ready(Ok(()));
+            }
+        }
+
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+impl Drop for PendingUpdate<'_, '_> {
+    fn drop(&mut self) {
+        if self.finished || self.failed {
+            return;
+        }
+        if self.controller.wait_hash_done().is_ok() {
+            self.try_finish();
+        } else {
+            self.abandon();
+        }
+    }
+}
@@ -1,6 +1,10 @@
 // Licensed under the Apache-2.0 license
 
-use crate::hace_controller::{ContextCleanup, HaceController, HashAlgo, HACE_SG_LAST};
+use crate::common::wipe;
+use crate::hace_controller::{
+    AspeedHashContext, ContextCleanup, HaceController, HaceError, HashAlgo, HashSession,
+    PendingUpdate, HACE_SG_MAX_ENTRIES,
+};
 use proposed_traits::digest::{DigestAlgorithm, DigestInit, DigestOp, Error, ErrorKind, ErrorType};
 
 // DigestAlgorithm implementation for HashAlgo
@@ -57,6 +61,8 @@ pub struct Sha224;
 pub struct Sha256;
 pub struct Sha384;
 pub struct Sha512;
+pub struct Sha512_224;
+pub struct Sha512_256;
 
 impl DigestAlgorithm for Sha1 {
     const OUTPUT_BITS: usize = 160;
@@ -83,6 +89,28 @@ impl DigestAlgorithm for Sha512 {
     type DigestOutput = Digest64; // Use Digest64 for 512 bits
 }
 
+impl DigestAlgorithm for Sha512_224 {
+    const OUTPUT_BITS: usize = 224;
+    type DigestOutput = [u8; 28];
+}
+
+impl DigestAlgorithm for Sha512_256 {
+    const OUTPUT_BITS: usize = 256;
+    type DigestOutput = [u8; 32];
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Sha1
+    }
+}
+
+impl Default for Sha224 {
+    fn default() -> Self {
+        Sha224
+    }
+}
+
 impl Default for Sha256 {
     fn default() -> Self {
         Sha256
@@ -101,6 +129,30 @@ impl Default for Sha512 {
     }
 }
 
+impl Default for Sha512_224 {
+    fn default() -> Self {
+        Sha512_224
+    }
+}
+
+impl Default for Sha512_256 {
+    fn default() -> Self {
+        Sha512_256
+    }
+}
+
+impl IntoHashAlgo for Sha1 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA1
+    }
+}
+
+impl IntoHashAlgo for Sha224 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA224
+    }
+}
+
 impl IntoHashAlgo for Sha256 {
     fn to_hash_algo() -> HashAlgo {
         HashAlgo::SHA256
@@ -119,6 +171,18 @@ impl IntoHashAlgo for Sha512 {
     }
 }
 
+impl IntoHashAlgo for Sha512_224 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA512_224
+    }
+}
+
+impl IntoHashAlgo for Sha512_256 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA512_256
+    }
+}
+
 impl<'ctrl, A> DigestInit<A> for HaceController<'ctrl>
 where
     A: DigestAlgorithm + IntoHashAlgo,
@@ -130,15 +194,45 @@ where
         Self: 'a; // Define your OpContext type here
 
     fn init(&mut self, _algo: A) -> Result<Self::OpContext<'_>, Self::Error> {
+        let session =
+            HashSession::acquire().ok_or(HashError::Kind(ErrorKind::MemoryAllocationFailure))?;
+        self.bind_session(&session);
         self.algo = A::to_hash_algo();
-        self.ctx_mut().method = self.algo.hash_cmd();
-        self.copy_iv_to_digest();
-        self.ctx_mut().block_size = u32::try_from(self.algo.block_size()).unwrap();
-        self.ctx_mut().bufcnt = 0;
-        self.ctx_mut().digcnt = [0; 2];
+        self.start_digest();
 
         Ok(OpContextImpl {
             controller: self,
+            session,
+            _phantom: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<'ctrl> HaceController<'ctrl> {
+    /// Continue a hash from a state captured with
+    /// [`OpContextImpl::export_state`].
+    ///
+    /// The returned operation behaves as if the data hashed before the export
+    /// had been fed to it. `A` must be the algorithm the state was taken from.
+    pub fn resume<A>(&mut self, state: &HashState) -> Result<OpContextImpl<'_, 'ctrl, A>, HashError>
+    where
+        A: DigestAlgorithm + IntoHashAlgo,
+    {
+        let algo = A::to_hash_algo();
+        if state.algo() != algo {
+            return Err(HashStateError::AlgorithmMismatch.into());
+        }
+
+        let session =
+            HashSession::acquire().ok_or(HashError::Kind(ErrorKind::MemoryAllocationFailure))?;
+        self.bind_session(&session);
+        self.algo = algo;
+        self.start_digest();
+        state.restore(self.ctx_mut());
+
+        Ok(OpContextImpl {
+            controller: self,
+            session,
             _phantom: core::marker::PhantomData,
         })
     }
@@ -146,107 +240,289 @@ where
 
 pub struct OpContextImpl<'a, 'ctrl, A: DigestAlgorithm + IntoHashAlgo> {
     pub controller: &'a mut HaceController<'ctrl>,
+    session: HashSession,
     _phantom: core::marker::PhantomData<A>,
 }
 
-#[derive(Debug)]
-pub struct HashError(pub ErrorKind);
+impl<'ctrl, A: DigestAlgorithm + IntoHashAlgo> OpContextImpl<'_, 'ctrl, A> {
+    /// Context slot held by this digest operation
+    #[must_use]
+    pub fn session(&self) -> &HashSession {
+        &self.session
+    }
+
+    /// Hash several non-contiguous buffers as if they were one message.
+    ///
+    /// Each slice is handed to the engine in place through a scatter-gather
+    /// descriptor list, so e.g. a header, body and trailer living in
+    /// different memory do not need to be copied together first. Lists with
+    /// more than `HACE_SG_MAX_ENTRIES - 1` slices take several engine passes.
+    pub fn update_sg(&mut self, inputs: &[&[u8]]) -> Result<(), HashError> {
+        for chunk in inputs.chunks(HACE_SG_MAX_ENTRIES - 1) {
+            self.controller.update_sg(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Start hashing `inputs` and return while the engine is still running.
+    ///
+    /// Poll the returned [`PendingUpdate`] with `is_done`/`try_finish`, or
+    /// block on it with `wait`. At most `HACE_SG_MAX_ENTRIES - 1` slices can
+    /// be passed at once.
+    pub fn start_update<'p>(
+        &'p mut self,
+        inputs: &'p [&'p [u8]],
+    ) -> Result<PendingUpdate<'p, 'ctrl>, HashError> {
+        self.controller.start_update_sg(inputs)
+    }
+
+    /// Capture the running digest, byte count and buffered partial block.
+    ///
+    /// The operation can carry on afterwards; the state can be restored any
+    /// number of times with [`HaceController::resume`]. It contains the
+    /// buffered tail of the message, so treat it like the message itself.
+    #[must_use]
+    pub fn export_state(&mut self) -> HashState {
+        let algo = A::to_hash_algo();
+        HashState::capture(algo, self.controller.ctx_mut())
+    }
+
+    /// Hash `input`, yielding to the executor until the engine is done.
+    ///
+    /// The task is polled until the engine is done or the controller's
+    /// timeout runs out. With interrupt mode enabled on the controller,
+    /// [`HaceController::handle_interrupt`] wakes it as well.
+    pub async fn update_async(&mut self, input: &[u8]) -> Result<(), HashError> {
+        let inputs = [input];
+        self.controller.start_update_sg(&inputs)?.await
+    }
+}
+
+impl<A: DigestAlgorithm + IntoHashAlgo> Drop for OpContextImpl<'_, '_, A> {
+    fn drop(&mut self) {
+        self.controller.unbind_session();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HashError {
+    /// Request rejected by the driver
+    Kind(ErrorKind),
+    /// Failure reported by the hash engine
+    Engine(HaceError),
+    /// A saved hash state was rejected
+    State(HashStateError),
+}
 
 impl Error for HashError {
     fn kind(&self) -> ErrorKind {
-        self.0
+        match self {
+            HashError::Kind(kind) => *kind,
+            HashError::Engine(HaceError::Timeout | HaceError::Poisoned) => {
+                ErrorKind::HardwareFailure
+            }
+            HashError::Engine(HaceError::BadAlignment) => ErrorKind::InvalidInputLength,
+            HashError::Engine(HaceError::EngineDisabled) => ErrorKind::NotInitialized,
+            HashError::Engine(HaceError::Busy) => ErrorKind::Busy,
+            HashError::State(_) => ErrorKind::InvalidInputLength,
+        }
     }
 }
 
 impl From<ErrorKind> for HashError {
     fn from(kind: ErrorKind) -> Self {
-        HashError(kind)
+        HashError::Kind(kind)
     }
 }
 
-impl<A> ErrorType for OpContextImpl<'_, '_, A>
-where
-    A: DigestAlgorithm + IntoHashAlgo,
-{
-    type Error = HashError;
+impl From<HaceError> for HashError {
+    fn from(err: HaceError) -> Self {
+        HashError::Engine(err)
+    }
 }
 
-impl<A> DigestOp for OpContextImpl<'_, '_, A>
-where
-    A: DigestAlgorithm + IntoHashAlgo,
-    A::DigestOutput: Default + AsMut<[u8]>,
-{
-    type Output = A::DigestOutput;
+impl From<HashStateError> for HashError {
+    fn from(err: HashStateError) -> Self {
+        HashError::State(err)
+    }
+}
 
-    fn update(&mut self, input: &[u8]) -> Result<(), Self::Error> {
-        let input_len = u32::try_from(input.len()).map_err(|_| ErrorKind::InvalidInputLength)?;
+/// Version of the [`HashState`] blob layout
+pub const HASH_STATE_VERSION: u8 = 1;
+
+/// Size of a serialized [`HashState`]
+pub const HASH_STATE_LEN: usize = HASH_STATE_BUFFER + HASH_STATE_BUFFER_LEN;
+
+const HASH_STATE_MAGIC: [u8; 4] = *b"HACE";
+const HASH_STATE_DIGCNT: usize = 8;
+const HASH_STATE_DIGEST: usize = 24;
+const HASH_STATE_BUFFER: usize = 88;
+const HASH_STATE_BUFFER_LEN: usize = 128;
+
+/// Reasons a serialized [`HashState`] is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashStateError {
+    /// The blob is not `HASH_STATE_LEN` bytes long
+    InvalidLength,
+    /// Bad magic or a layout version this driver does not know
+    UnsupportedVersion,
+    /// Unknown algorithm or inconsistent counters
+    Corrupt,
+    /// The state belongs to a different hash algorithm
+    AlgorithmMismatch,
+}
 
-        let (new_len, carry) =
-            self.controller.ctx_mut().digcnt[0].overflowing_add(u64::from(input_len));
+/// Intermediate state of a hash, serializable as a versioned blob.
+///
+/// Layout (version 1, little endian): magic `"HACE"`, version, algorithm
+/// id, buffered byte count (`u16`), the 128-bit message byte count, the
+/// 64-byte running digest in engine order, and a 128-byte partial block
+/// zero-padded past the buffered count.
+#[derive(Clone)]
+pub struct HashState {
+    algo: HashAlgo,
+    blob: [u8; HASH_STATE_LEN],
+}
 
-        self.controller.ctx_mut().digcnt[0] = new_len;
-        if carry {
-            self.controller.ctx_mut().digcnt[1] += 1;
-        }
+impl HashState {
+    fn capture(algo: HashAlgo, ctx: &AspeedHashContext) -> Self {
+        let mut blob = [0u8; HASH_STATE_LEN];
+        let bufcnt = ctx.bufcnt as usize;
+
+        blob[..4].copy_from_slice(&HASH_STATE_MAGIC);
+        blob[4] = HASH_STATE_VERSION;
+        blob[5] = algo_id(algo);
+        blob[6..8].copy_from_slice(&u16::try_from(bufcnt).unwrap().to_le_bytes());
+        blob[HASH_STATE_DIGCNT..HASH_STATE_DIGCNT + 8]
+            .copy_from_slice(&ctx.digcnt[0].to_le_bytes());
+        blob[HASH_STATE_DIGCNT + 8..HASH_STATE_DIGEST]
+            .copy_from_slice(&ctx.digcnt[1].to_le_bytes());
+        blob[HASH_STATE_DIGEST..HASH_STATE_BUFFER].copy_from_slice(&ctx.digest);
+        blob[HASH_STATE_BUFFER..HASH_STATE_BUFFER + bufcnt].copy_from_slice(&ctx.buffer[..bufcnt]);
+        HashState { algo, blob }
+    }
 
-        let start = self.controller.ctx_mut().bufcnt as usize;
-        let end = start + input_len as usize;
-        if self.controller.ctx_mut().bufcnt + input_len < self.controller.ctx_mut().block_size {
-            self.controller.ctx_mut().buffer[start..end].copy_from_slice(input);
-            self.controller.ctx_mut().bufcnt += input_len;
-            return Ok(());
-        }
+    fn restore(&self, ctx: &mut AspeedHashContext) {
+        let blob = &self.blob;
+        let bufcnt = self.bufcnt();
+
+        ctx.digcnt = [
+            self.le_u64(HASH_STATE_DIGCNT),
+            self.le_u64(HASH_STATE_DIGCNT + 8),
+        ];
+        ctx.digest
+            .copy_from_slice(&blob[HASH_STATE_DIGEST..HASH_STATE_BUFFER]);
+        ctx.buffer[..bufcnt].copy_from_slice(&blob[HASH_STATE_BUFFER..HASH_STATE_BUFFER + bufcnt]);
+        ctx.bufcnt = u32::from(u16::from_le_bytes([blob[6], blob[7]]));
+    }
 
-        let remaining =
-            (input_len + self.controller.ctx_mut().bufcnt) % self.controller.ctx_mut().block_size;
-        let total_len = (input_len + self.controller.ctx_mut().bufcnt) - remaining;
-        let mut i = 0;
-
-        if self.controller.ctx_mut().bufcnt != 0 {
-            self.controller.ctx_mut().sg[0].addr = self.controller.ctx_mut().buffer.as_ptr() as u32;
-            self.controller.ctx_mut().sg[0].len = self.controller.ctx_mut().bufcnt;
-            if total_len == self.controller.ctx_mut().bufcnt {
-                self.controller.ctx_mut().sg[0].addr = input.as_ptr() as u32;
-                self.controller.ctx_mut().sg[0].len |= HACE_SG_LAST;
-            }
-            i += 1;
+    /// Parse and validate a blob produced by [`HashState::as_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HashStateError> {
+        if bytes.len() != HASH_STATE_LEN {
+            return Err(HashStateError::InvalidLength);
+        }
+        if bytes[..4] != HASH_STATE_MAGIC || bytes[4] != HASH_STATE_VERSION {
+            return Err(HashStateError::UnsupportedVersion);
         }
+        let algo = algo_from_id(bytes[5]).ok_or(HashStateError::Corrupt)?;
+
+        let mut state = HashState {
+            algo,
+            blob: [0; HASH_STATE_LEN],
+        };
+        state.blob.copy_from_slice(bytes);
 
-        if total_len != self.controller.ctx_mut().bufcnt {
-            self.controller.ctx_mut().sg[i].addr = input.as_ptr() as u32;
-            self.controller.ctx_mut().sg[i].len =
-                (total_len - self.controller.ctx_mut().bufcnt) | HACE_SG_LAST;
+        let block_size = algo.block_size();
+        let bufcnt = state.bufcnt();
+        if bufcnt >= block_size {
+            return Err(HashStateError::Corrupt);
         }
+        // Every byte the engine consumed was part of a whole block
+        if state.le_u64(HASH_STATE_DIGCNT) % block_size as u64 != bufcnt as u64 {
+            return Err(HashStateError::Corrupt);
+        }
+        if bytes[HASH_STATE_BUFFER + bufcnt..].iter().any(|&b| b != 0) {
+            return Err(HashStateError::Corrupt);
+        }
+        Ok(state)
+    }
 
-        self.controller.start_hash_operation(total_len);
+    /// The serialized state
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; HASH_STATE_LEN] {
+        &self.blob
+    }
 
-        if remaining != 0 {
-            let src_start = (total_len - self.controller.ctx_mut().bufcnt) as usize;
-            let src_end = src_start + remaining as usize;
+    /// Algorithm the state was captured from
+    #[must_use]
+    pub fn algo(&self) -> HashAlgo {
+        self.algo
+    }
 
-            self.controller.ctx_mut().buffer[..(remaining as usize)]
-                .copy_from_slice(&input[src_start..src_end]);
-            self.controller.ctx_mut().bufcnt = remaining;
-        }
-        Ok(())
+    fn bufcnt(&self) -> usize {
+        usize::from(u16::from_le_bytes([self.blob[6], self.blob[7]]))
     }
 
-    fn finalize(self) -> Result<Self::Output, Self::Error> {
-        self.controller.fill_padding(0);
-        let digest_len = self.controller.algo.digest_size();
+    fn le_u64(&self, at: usize) -> u64 {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&self.blob[at..at + 8]);
+        u64::from_le_bytes(word)
+    }
+}
+
+impl Drop for HashState {
+    fn drop(&mut self) {
+        wipe(&mut self.blob);
+    }
+}
 
-        let (digest_ptr, bufcnt) = {
-            let ctx = self.controller.ctx_mut();
+const fn algo_id(algo: HashAlgo) -> u8 {
+    match algo {
+        HashAlgo::SHA1 => 1,
+        HashAlgo::SHA224 => 2,
+        HashAlgo::SHA256 => 3,
+        HashAlgo::SHA384 => 4,
+        HashAlgo::SHA512 => 5,
+        HashAlgo::SHA512_224 => 6,
+        HashAlgo::SHA512_256 => 7,
+    }
+}
 
-            ctx.sg[0].addr = ctx.buffer.as_ptr() as u32;
-            ctx.sg[0].len = ctx.bufcnt | HACE_SG_LAST;
+const fn algo_from_id(id: u8) -> Option<HashAlgo> {
+    match id {
+        1 => Some(HashAlgo::SHA1),
+        2 => Some(HashAlgo::SHA224),
+        3 => Some(HashAlgo::SHA256),
+        4 => Some(HashAlgo::SHA384),
+        5 => Some(HashAlgo::SHA512),
+        6 => Some(HashAlgo::SHA512_224),
+        7 => Some(HashAlgo::SHA512_256),
+        _ => None,
+    }
+}
 
-            (ctx.digest.as_ptr(), ctx.bufcnt)
-        };
+impl<A> ErrorType for OpContextImpl<'_, '_, A>
+where
+    A: DigestAlgorithm + IntoHashAlgo,
+{
+    type Error = HashError;
+}
+
+impl<A> DigestOp for OpContextImpl<'_, '_, A>
+where
+    A: DigestAlgorithm + IntoHashAlgo,
+    A::DigestOutput: Default + AsMut<[u8]>,
+{
+    type Output = A::DigestOutput;
 
-        self.controller.start_hash_operation(bufcnt);
+    fn update(&mut self, input: &[u8]) -> Result<(), Self::Error> {
+        self.controller.update_sg(&[input])
+    }
 
-        let slice = unsafe { core::slice::from_raw_parts(digest_ptr, digest_len) };
+    fn finalize(self) -> Result<Self::Output, Self::Error> {
+        self.controller.finish_digest()?;
+        let digest_len = self.controller.algo.digest_size();
+        let slice = &self.controller.ctx_mut().digest[..digest_len];
 
         let mut output = A::DigestOutput::default();
         output.as_mut()[..digest_len].copy_from_slice(slice);
@@ -1,13 +1,60 @@
 // Licensed under the Apache-2.0 license
 
-use crate::hace_controller::{ContextCleanup, HaceController, HashAlgo, HACE_SG_EN};
+use crate::common::wipe;
+use crate::hace_controller::{ContextCleanup, HaceController, HaceError, HashAlgo, HashSession};
+use crate::hash::HashError;
+use proposed_traits::digest::ErrorKind as DigestErrorKind;
 use proposed_traits::mac::{Error, ErrorKind, ErrorType, MacAlgorithm, MacInit, MacOp};
 
 // MacAlgorithm implementation for HashAlgo
 impl MacAlgorithm for HashAlgo {
     const OUTPUT_BITS: usize = 512; // Maximum size for all variants
     type MacOutput = [u8; 64]; // Use the maximum size for all variants
-    type Key = [u8; 64]; // Use the maximum size for all variants
+    type Key = HmacKey;
+}
+
+/// Longest key that fits in an [`HmacKey`]. Keys of any length can be passed
+/// as a slice to [`HaceController::init_hmac`].
+pub const HMAC_KEY_MAX_LEN: usize = 256;
+
+/// Variable-length HMAC key accepted by `MacInit`
+pub struct HmacKey {
+    bytes: [u8; HMAC_KEY_MAX_LEN],
+    len: usize,
+}
+
+impl HmacKey {
+    pub fn new(key: &[u8]) -> Result<Self, MacError> {
+        if key.len() > HMAC_KEY_MAX_LEN {
+            return Err(MacError::Kind(ErrorKind::InvalidInputLength));
+        }
+        let mut bytes = [0u8; HMAC_KEY_MAX_LEN];
+        bytes[..key.len()].copy_from_slice(key);
+        Ok(Self {
+            bytes,
+            len: key.len(),
+        })
+    }
+}
+
+impl Drop for HmacKey {
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+    }
+}
+
+impl TryFrom<&[u8]> for HmacKey {
+    type Error = MacError;
+
+    fn try_from(key: &[u8]) -> Result<Self, Self::Error> {
+        Self::new(key)
+    }
+}
+
+impl AsRef<[u8]> for HmacKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
 }
 
 pub trait IntoHashAlgo {
@@ -58,35 +105,61 @@ pub struct Sha224;
 pub struct Sha256;
 pub struct Sha384;
 pub struct Sha512;
+pub struct Sha512_224;
+pub struct Sha512_256;
 
 impl MacAlgorithm for Sha1 {
     const OUTPUT_BITS: usize = 160;
     type MacOutput = [u8; 20];
-    type Key = [u8; 64];
+    type Key = HmacKey;
 }
 
 impl MacAlgorithm for Sha224 {
     const OUTPUT_BITS: usize = 224;
     type MacOutput = [u8; 28];
-    type Key = [u8; 64];
+    type Key = HmacKey;
 }
 
 impl MacAlgorithm for Sha256 {
     const OUTPUT_BITS: usize = 256;
     type MacOutput = [u8; 32];
-    type Key = [u8; 32];
+    type Key = HmacKey;
 }
 
 impl MacAlgorithm for Sha384 {
     const OUTPUT_BITS: usize = 384;
     type MacOutput = Digest48; // Use Digest48 for 384 bits
-    type Key = [u8; 48];
+    type Key = HmacKey;
 }
 
 impl MacAlgorithm for Sha512 {
     const OUTPUT_BITS: usize = 512;
     type MacOutput = Digest64; // Use Digest64 for 512 bits
-    type Key = [u8; 64];
+    type Key = HmacKey;
+}
+
+impl MacAlgorithm for Sha512_224 {
+    const OUTPUT_BITS: usize = 224;
+    type MacOutput = [u8; 28];
+    type Key = HmacKey;
+}
+
+impl MacAlgorithm for Sha512_256 {
+    const OUTPUT_BITS: usize = 256;
+    type MacOutput = [u8; 32];
+    type Key = HmacKey;
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Sha1
+    }
+}
+
+impl Default for Sha224 {
+    fn default() -> Self {
+        Sha224
+    }
 }
 
 impl Default for Sha256 {
@@ -107,6 +180,30 @@ impl Default for Sha512 {
     }
 }
 
+impl Default for Sha512_224 {
+    fn default() -> Self {
+        Sha512_224
+    }
+}
+
+impl Default for Sha512_256 {
+    fn default() -> Self {
+        Sha512_256
+    }
+}
+
+impl IntoHashAlgo for Sha1 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA1
+    }
+}
+
+impl IntoHashAlgo for Sha224 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA224
+    }
+}
+
 impl IntoHashAlgo for Sha256 {
     fn to_hash_algo() -> HashAlgo {
         HashAlgo::SHA256
@@ -125,6 +222,18 @@ impl IntoHashAlgo for Sha512 {
     }
 }
 
+impl IntoHashAlgo for Sha512_224 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA512_224
+    }
+}
+
+impl IntoHashAlgo for Sha512_256 {
+    fn to_hash_algo() -> HashAlgo {
+        HashAlgo::SHA512_256
+    }
+}
+
 impl<'ctrl, A> MacInit<A> for HaceController<'ctrl>
 where
     A: MacAlgorithm + IntoHashAlgo,
@@ -137,57 +246,195 @@ where
         Self: 'a; // Define your OpContext type here
 
     fn init<'a>(&'a mut self, _algo: A, key: &A::Key) -> Result<Self::OpContext<'a>, Self::Error> {
+        self.init_hmac(key.as_ref())
+    }
+}
+
+impl<'ctrl> HaceController<'ctrl> {
+    /// Start an HMAC with a key of any length.
+    ///
+    /// Per RFC 2104, a key longer than the block size of `A` is replaced by
+    /// its digest and a shorter key is zero-padded to the block size.
+    pub fn init_hmac<A>(&mut self, key: &[u8]) -> Result<OpContextImpl<'_, 'ctrl, A>, MacError>
+    where
+        A: MacAlgorithm + IntoHashAlgo,
+    {
+        let session =
+            HashSession::acquire().ok_or(MacError::Kind(ErrorKind::MemoryAllocationFailure))?;
+        self.bind_session(&session);
         self.algo = A::to_hash_algo();
-        self.ctx_mut().method = self.algo.hash_cmd();
-        self.copy_iv_to_digest();
-        self.ctx_mut().block_size = u32::try_from(self.algo.block_size()).unwrap();
-        self.ctx_mut().bufcnt = 0;
-        self.ctx_mut().digcnt = [0; 2];
-        self.ctx_mut().buffer.fill(0);
-        self.ctx_mut().digest.fill(0);
-        self.ctx_mut().ipad.fill(0);
-        self.ctx_mut().opad.fill(0);
-        self.ctx_mut().key.fill(0);
-
-        if key.as_ref().len() > self.ctx_mut().key.len() {
-            // hash key if it is too long
-            self.hash_key(key);
-        } else {
-            self.ctx_mut().key[..key.as_ref().len()].copy_from_slice(key.as_ref());
-            self.ctx_mut().ipad[..key.as_ref().len()].copy_from_slice(key.as_ref());
-            self.ctx_mut().opad[..key.as_ref().len()].copy_from_slice(key.as_ref());
-            self.ctx_mut().key_len = u32::try_from(key.as_ref().len()).unwrap();
-        }
 
-        for i in 0..self.ctx_mut().block_size as usize {
-            self.ctx_mut().ipad[i] ^= 0x36;
-            self.ctx_mut().opad[i] ^= 0x5c;
+        if let Err(e) = self.load_hmac_key(key) {
+            self.unbind_session();
+            return Err(e);
         }
+        self.start_hmac();
 
         Ok(OpContextImpl {
             controller: self,
+            session,
             _phantom: core::marker::PhantomData,
         })
     }
+
+    /// Derive the inner and outer padded keys of `self.algo` into the bound
+    /// context.
+    pub(crate) fn load_hmac_key(&mut self, key: &[u8]) -> Result<(), MacError> {
+        let block_size = self.algo.block_size();
+
+        {
+            let ctx = self.ctx_mut();
+            ctx.buffer.fill(0);
+            ctx.digest.fill(0);
+            ctx.ipad.fill(0);
+            ctx.opad.fill(0);
+            ctx.key.fill(0);
+        }
+
+        if key.len() > block_size {
+            // hash key if it is longer than a block
+            if let Err(err) = self.hash_key(&key) {
+                self.wipe_hmac_context();
+                return Err(err.into());
+            }
+        } else {
+            let ctx = self.ctx_mut();
+            ctx.key[..key.len()].copy_from_slice(key);
+            ctx.ipad[..key.len()].copy_from_slice(key);
+            ctx.opad[..key.len()].copy_from_slice(key);
+            ctx.key_len = u32::try_from(key.len()).unwrap();
+        }
+
+        let ctx = self.ctx_mut();
+        let pads = ctx.ipad[..block_size]
+            .iter_mut()
+            .zip(ctx.opad[..block_size].iter_mut());
+        for (ipad, opad) in pads {
+            *ipad ^= 0x36;
+            *opad ^= 0x5c;
+        }
+        Ok(())
+    }
+
+    /// Zero the key, the padded keys and the hash state of the bound
+    /// context, so the next session to get the slot finds no key material
+    pub(crate) fn wipe_hmac_context(&mut self) {
+        self.cleanup_context();
+        let ctx = self.ctx_mut();
+        wipe(&mut ctx.key);
+        wipe(&mut ctx.ipad);
+        wipe(&mut ctx.opad);
+        ctx.key_len = 0;
+    }
+
+    /// Begin the inner hash `H(ipad || message)` with the loaded key. The
+    /// ipad block is left in the buffer and goes to the engine with the first
+    /// whole block of input.
+    pub(crate) fn start_hmac(&mut self) {
+        let block_size = self.algo.block_size();
+        self.start_digest();
+        let ctx = self.ctx_mut();
+        ctx.buffer[..block_size].copy_from_slice(&ctx.ipad[..block_size]);
+        ctx.bufcnt = ctx.block_size;
+        ctx.digcnt[0] = u64::from(ctx.block_size);
+    }
+
+    /// Finish the inner hash, run the outer hash `H(opad || inner)` and write
+    /// the MAC into `out`, which must be the digest size of `self.algo`.
+    pub(crate) fn finish_hmac(&mut self, out: &mut [u8]) -> Result<(), MacError> {
+        let block_size = self.algo.block_size();
+        let digest_size = self.algo.digest_size();
+
+        self.finish_digest()?;
+
+        {
+            let ctx = self.ctx_mut();
+            ctx.buffer[block_size..block_size + digest_size]
+                .copy_from_slice(&ctx.digest[..digest_size]);
+        }
+        self.start_digest();
+        {
+            let ctx = self.ctx_mut();
+            ctx.buffer[..block_size].copy_from_slice(&ctx.opad[..block_size]);
+            ctx.bufcnt = u32::try_from(block_size + digest_size)
+                .map_err(|_| MacError::Kind(ErrorKind::UpdateError))?;
+            ctx.digcnt[0] = u64::from(ctx.bufcnt);
+        }
+        self.finish_digest()?;
+
+        out.copy_from_slice(&self.ctx_mut().digest[..digest_size]);
+        self.cleanup_context();
+        Ok(())
+    }
 }
 
 pub struct OpContextImpl<'a, 'ctrl, A: MacAlgorithm + IntoHashAlgo> {
     pub controller: &'a mut HaceController<'ctrl>,
+    session: HashSession,
     _phantom: core::marker::PhantomData<A>,
 }
 
-#[derive(Debug)]
-pub struct MacError(pub ErrorKind);
+impl<A: MacAlgorithm + IntoHashAlgo> OpContextImpl<'_, '_, A> {
+    /// Context slot held by this MAC operation
+    #[must_use]
+    pub fn session(&self) -> &HashSession {
+        &self.session
+    }
+}
+
+impl<A: MacAlgorithm + IntoHashAlgo> Drop for OpContextImpl<'_, '_, A> {
+    fn drop(&mut self) {
+        self.controller.wipe_hmac_context();
+        self.controller.unbind_session();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MacError {
+    /// Request rejected by the driver
+    Kind(ErrorKind),
+    /// Failure reported by the hash engine
+    Engine(HaceError),
+}
 
 impl Error for MacError {
     fn kind(&self) -> ErrorKind {
-        self.0
+        match self {
+            MacError::Kind(kind) => *kind,
+            MacError::Engine(HaceError::Timeout | HaceError::Poisoned) => {
+                ErrorKind::HardwareFailure
+            }
+            MacError::Engine(HaceError::BadAlignment) => ErrorKind::InvalidInputLength,
+            MacError::Engine(HaceError::EngineDisabled) => ErrorKind::NotInitialized,
+            MacError::Engine(HaceError::Busy) => ErrorKind::Busy,
+        }
     }
 }
 
 impl From<ErrorKind> for MacError {
     fn from(kind: ErrorKind) -> Self {
-        MacError(kind)
+        MacError::Kind(kind)
+    }
+}
+
+impl From<HaceError> for MacError {
+    fn from(err: HaceError) -> Self {
+        MacError::Engine(err)
+    }
+}
+
+impl From<HashError> for MacError {
+    fn from(err: HashError) -> Self {
+        match err {
+            HashError::Engine(err) => MacError::Engine(err),
+            HashError::Kind(DigestErrorKind::InvalidInputLength) => {
+                MacError::Kind(ErrorKind::InvalidInputLength)
+            }
+            HashError::Kind(DigestErrorKind::MemoryAllocationFailure) => {
+                MacError::Kind(ErrorKind::MemoryAllocationFailure)
+            }
+            HashError::Kind(_) | HashError::State(_) => MacError::Kind(ErrorKind::UpdateError),
+        }
     }
 }
 
@@ -206,62 +453,14 @@ where
     type Output = A::MacOutput;
 
     fn update(&mut self, input: &[u8]) -> Result<(), Self::Error> {
-        let ctrl: &mut HaceController = self.controller;
-        let algo = ctrl.algo;
-        let block_size = algo.block_size();
-        let digest_size = algo.digest_size();
-        let mut bufcnt: u32;
-
-        {
-            let ctx = ctrl.ctx_mut();
-            ctx.digcnt[0] = block_size as u64;
-            ctx.bufcnt =
-                u32::try_from(block_size).map_err(|_| MacError(ErrorKind::InvalidInputLength))?;
-
-            // H(ipad + input)
-            let ipad = &ctx.ipad[..block_size];
-            ctx.buffer[..algo.block_size()].copy_from_slice(ipad);
-            ctx.buffer[algo.block_size()..(algo.block_size() + input.len())].copy_from_slice(input);
-            ctx.digcnt[0] += input.len() as u64;
-            ctx.bufcnt +=
-                u32::try_from(input.len()).map_err(|_| MacError(ErrorKind::InvalidInputLength))?;
-            ctx.method &= !HACE_SG_EN; // Disable SG mode for key hashing
-        }
-
-        ctrl.fill_padding(0);
-        bufcnt = ctrl.ctx_mut().bufcnt;
-        ctrl.copy_iv_to_digest();
-        ctrl.start_hash_operation(bufcnt);
-        let slice =
-            unsafe { core::slice::from_raw_parts(ctrl.ctx_mut().digest.as_ptr(), digest_size) };
-
-        // H(opad + H(opad + hash sum))
-        {
-            let ctx = ctrl.ctx_mut();
-            ctx.digcnt[0] = block_size as u64 + digest_size as u64;
-            ctx.bufcnt = u32::try_from(block_size + digest_size)
-                .map_err(|_| MacError(ErrorKind::UpdateError))?;
-            ctx.buffer[..block_size].copy_from_slice(&ctx.opad[..block_size]);
-            ctx.buffer[block_size..(block_size + digest_size)].copy_from_slice(slice);
-        }
-        ctrl.fill_padding(0);
-        bufcnt = ctrl.ctx_mut().bufcnt;
-        ctrl.copy_iv_to_digest();
-        ctrl.start_hash_operation(bufcnt);
-
-        Ok(())
+        self.controller.update_sg(&[input]).map_err(MacError::from)
     }
 
     fn finalize(self) -> Result<Self::Output, Self::Error> {
         let digest_size = self.controller.algo.digest_size();
-        let ctx = self.controller.ctx_mut();
-
-        let slice = unsafe { core::slice::from_raw_parts(ctx.digest.as_ptr(), digest_size) };
-
         let mut output = A::MacOutput::default();
-        output.as_mut()[..digest_size].copy_from_slice(slice);
-
-        self.controller.cleanup_context();
+        self.controller
+            .finish_hmac(&mut output.as_mut()[..digest_size])?;
 
         Ok(output) // Return the final output
     }
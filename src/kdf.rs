@@ -0,0 +1,196 @@
+// Licensed under the Apache-2.0 license
+
+//! Key derivation on top of the HACE HMAC: HKDF (RFC 5869) and the NIST
+//! SP 800-108 counter-mode KDF with HMAC as the PRF.
+
+use crate::common::wipe;
+use crate::hace_controller::HaceController;
+use crate::hmac::{IntoHashAlgo, MacError};
+use proposed_traits::mac::{MacAlgorithm, MacOp};
+
+/// Largest digest of the supported hashes
+const KDF_MAX_HASH_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub enum KdfError {
+    /// Requested more output than the KDF can produce
+    InvalidOutputLength,
+    /// Failure computing the underlying HMAC
+    Mac(MacError),
+}
+
+impl From<MacError> for KdfError {
+    fn from(err: MacError) -> Self {
+        KdfError::Mac(err)
+    }
+}
+
+/// Compute `HMAC(key, parts[0] || parts[1] || ...)` into `out`
+pub(crate) fn hmac_parts<A>(
+    ctrl: &mut HaceController,
+    key: &[u8],
+    parts: &[&[u8]],
+    out: &mut [u8],
+) -> Result<(), KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let mut op = ctrl.init_hmac::<A>(key)?;
+    for part in parts {
+        op.update(part)?;
+    }
+    let mut mac = op.finalize()?;
+    out.copy_from_slice(&mac.as_ref()[..out.len()]);
+    wipe(mac.as_mut());
+    Ok(())
+}
+
+/// HKDF-Extract: `PRK = HMAC(salt, IKM)`.
+///
+/// An empty salt stands for `HashLen` zero bytes; HMAC zero-pads the key, so
+/// both give the same PRK.
+pub fn hkdf_extract<A>(
+    ctrl: &mut HaceController,
+    salt: &[u8],
+    ikm: &[u8],
+) -> Result<A::MacOutput, KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let mut op = ctrl.init_hmac::<A>(salt)?;
+    op.update(ikm)?;
+    op.finalize().map_err(KdfError::from)
+}
+
+/// HKDF-Expand: fill `okm` from `PRK` and `info`, up to `255 * HashLen`
+/// bytes.
+pub fn hkdf_expand<A>(
+    ctrl: &mut HaceController,
+    prk: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) -> Result<(), KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let hash_len = A::OUTPUT_BITS / 8;
+    if okm.len() > 255 * hash_len {
+        return Err(KdfError::InvalidOutputLength);
+    }
+
+    // T(i) = HMAC(PRK, T(i - 1) || info || i), with T(0) empty
+    let mut t = [0u8; KDF_MAX_HASH_LEN];
+    let mut t_len = 0;
+    let mut result = Ok(());
+    for (i, chunk) in okm.chunks_mut(hash_len).enumerate() {
+        let counter = u8::try_from(i + 1).map_err(|_| KdfError::InvalidOutputLength)?;
+        let (prev, _) = t.split_at(t_len);
+        let mut next = [0u8; KDF_MAX_HASH_LEN];
+        result = hmac_parts::<A>(ctrl, prk, &[prev, info, &[counter]], &mut next[..hash_len]);
+        if result.is_err() {
+            break;
+        }
+        t = next;
+        t_len = hash_len;
+        wipe(&mut next);
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+    wipe(&mut t);
+    result
+}
+
+/// HKDF-Extract followed by HKDF-Expand. The PRK is wiped afterwards.
+pub fn hkdf<A>(
+    ctrl: &mut HaceController,
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) -> Result<(), KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let mut prk = hkdf_extract::<A>(ctrl, salt, ikm)?;
+    let hash_len = A::OUTPUT_BITS / 8;
+    let result = hkdf_expand::<A>(ctrl, &prk.as_ref()[..hash_len], info, okm);
+    wipe(prk.as_mut());
+    result
+}
+
+/// NIST SP 800-108 KDF in counter mode with HMAC as the PRF, over
+/// caller-encoded fixed input data.
+///
+/// Block `i` is `HMAC(key, [i]_32 || fixed)`, the layout of the CAVP
+/// `KDFCTR` vectors with the counter before the fixed data.
+pub fn kdf_counter_fixed<A>(
+    ctrl: &mut HaceController,
+    key: &[u8],
+    fixed: &[u8],
+    out: &mut [u8],
+) -> Result<(), KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    counter_blocks::<A>(ctrl, key, &[fixed], out)
+}
+
+/// NIST SP 800-108 KDF in counter mode with HMAC as the PRF.
+///
+/// Block `i` is `HMAC(key, [i]_32 || label || 0x00 || context || [L]_32)`,
+/// where `L` is the output length in bits.
+pub fn kdf_counter<A>(
+    ctrl: &mut HaceController,
+    key: &[u8],
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let out_bits = out
+        .len()
+        .checked_mul(8)
+        .and_then(|bits| u32::try_from(bits).ok())
+        .ok_or(KdfError::InvalidOutputLength)?;
+    let length = out_bits.to_be_bytes();
+    counter_blocks::<A>(ctrl, key, &[label, &[0], context, &length], out)
+}
+
+/// Fill `out` with `HMAC(key, [i]_32 || fixed[0] || fixed[1] || ...)` for
+/// `i = 1, 2, ...`; at most four parts
+fn counter_blocks<A>(
+    ctrl: &mut HaceController,
+    key: &[u8],
+    fixed: &[&[u8]],
+    out: &mut [u8],
+) -> Result<(), KdfError>
+where
+    A: MacAlgorithm + IntoHashAlgo,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// The counter and the four parts of [`kdf_counter`]
+    const MAX_PARTS: usize = 5;
+    let hash_len = A::OUTPUT_BITS / 8;
+
+    let mut block = [0u8; KDF_MAX_HASH_LEN];
+    let mut result = Ok(());
+    for (i, chunk) in (1u32..).zip(out.chunks_mut(hash_len)) {
+        let counter = i.to_be_bytes();
+        let mut parts: [&[u8]; MAX_PARTS] = [&counter, &[], &[], &[], &[]];
+        parts[1..=fixed.len()].copy_from_slice(fixed);
+        result = hmac_parts::<A>(ctrl, key, &parts[..=fixed.len()], &mut block[..hash_len]);
+        if result.is_err() {
+            break;
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    wipe(&mut block);
+    result
+}
@@ -0,0 +1,294 @@
+// Licensed under the Apache-2.0 license
+
+//! Decode RSA and EC keys from their standard DER encodings into the key
+//! structures the RSA and ECDSA drivers take.
+//!
+//! Supported formats are PKCS#1 `RSAPublicKey`, X.509
+//! `SubjectPublicKeyInfo` (RSA, P-256 and P-384) and PKCS#8 `PrivateKeyInfo`
+//! holding an RSA or EC private key. PEM input is decoded with
+//! [`pem_decode`](crate::der::pem_decode) first. The returned keys borrow
+//! their integers from the DER input.
+
+use crate::common::bit_len;
+use crate::der::{
+    parse_single, tag_context, tag_implicit, DerError, Reader, TAG_OCTET_STRING, TAG_OID,
+    TAG_SEQUENCE,
+};
+use crate::ecdsa::{PrivateKey, PublicKey, Scalar32, Scalar48};
+use crate::rsa::{RsaCrtParams, RsaPrivateKey, RsaPublicKey, RSA_MAX_BITS};
+
+/// rsaEncryption, 1.2.840.113549.1.1.1
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+/// id-ecPublicKey, 1.2.840.10045.2.1
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// prime256v1, 1.2.840.10045.3.1.7
+const OID_P256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+/// secp384r1, 1.3.132.0.34
+const OID_P384: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyError {
+    /// The input is not well-formed DER for the expected structure
+    Der(DerError),
+    /// The key algorithm is neither RSA nor EC
+    UnsupportedAlgorithm,
+    /// The EC key is on a curve other than P-256 or P-384
+    UnsupportedCurve,
+    /// The structure version is not one this parser knows
+    UnsupportedVersion,
+    /// The key values are out of range for the algorithm
+    InvalidKey,
+}
+
+impl From<DerError> for KeyError {
+    fn from(err: DerError) -> Self {
+        KeyError::Der(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcCurve {
+    P256,
+    P384,
+}
+
+impl EcCurve {
+    /// Size of a field element or scalar in bytes
+    #[must_use]
+    pub fn scalar_len(self) -> usize {
+        match self {
+            EcCurve::P256 => 32,
+            EcCurve::P384 => 48,
+        }
+    }
+
+    fn from_oid(oid: &[u8]) -> Result<Self, KeyError> {
+        match oid {
+            OID_P256 => Ok(EcCurve::P256),
+            OID_P384 => Ok(EcCurve::P384),
+            _ => Err(KeyError::UnsupportedCurve),
+        }
+    }
+}
+
+/// Affine EC public key, with big-endian coordinates of the curve's size
+pub struct EcPublicKey<'a> {
+    pub curve: EcCurve,
+    pub x: &'a [u8],
+    pub y: &'a [u8],
+}
+
+impl EcPublicKey<'_> {
+    /// The key in the form the P-384 ECDSA verifier takes
+    pub fn to_p384(&self) -> Result<PublicKey, KeyError> {
+        if self.curve != EcCurve::P384 {
+            return Err(KeyError::UnsupportedCurve);
+        }
+        if self.x.len() != Scalar48::LEN || self.y.len() != Scalar48::LEN {
+            return Err(KeyError::InvalidKey);
+        }
+        let mut qx = Scalar48::default();
+        let mut qy = Scalar48::default();
+        qx.0.copy_from_slice(self.x);
+        qy.0.copy_from_slice(self.y);
+        Ok(PublicKey { qx, qy })
+    }
+
+    /// The key in the form the P-256 ECDSA verifier takes
+    pub fn to_p256(&self) -> Result<PublicKey<Scalar32>, KeyError> {
+        if self.curve != EcCurve::P256 {
+            return Err(KeyError::UnsupportedCurve);
+        }
+        if self.x.len() != Scalar32::LEN || self.y.len() != Scalar32::LEN {
+            return Err(KeyError::InvalidKey);
+        }
+        let mut qx = Scalar32::default();
+        let mut qy = Scalar32::default();
+        qx.0.copy_from_slice(self.x);
+        qy.0.copy_from_slice(self.y);
+        Ok(PublicKey { qx, qy })
+    }
+}
+
+/// EC private scalar, big-endian and of the curve's size
+pub struct EcPrivateKey<'a> {
+    pub curve: EcCurve,
+    pub d: &'a [u8],
+}
+
+impl EcPrivateKey<'_> {
+    /// The key in the form the P-384 ECDSA signer takes
+    pub fn to_p384(&self) -> Result<PrivateKey, KeyError> {
+        if self.curve != EcCurve::P384 {
+            return Err(KeyError::UnsupportedCurve);
+        }
+        if self.d.len() != Scalar48::LEN {
+            return Err(KeyError::InvalidKey);
+        }
+        let mut d = Scalar48::default();
+        d.0.copy_from_slice(self.d);
+        Ok(PrivateKey { d })
+    }
+}
+
+/// Public key decoded from a `SubjectPublicKeyInfo`
+pub enum PublicKeyInfo<'a> {
+    Rsa(RsaPublicKey<'a>),
+    Ec(EcPublicKey<'a>),
+}
+
+/// Private key decoded from a PKCS#8 `PrivateKeyInfo`
+pub enum PrivateKeyInfo<'a> {
+    Rsa(RsaPrivateKey<'a>),
+    Ec(EcPrivateKey<'a>),
+}
+
+/// Key algorithm named by an `AlgorithmIdentifier`
+enum Algorithm {
+    Rsa,
+    Ec(EcCurve),
+}
+
+fn read_algorithm(reader: &mut Reader<'_>) -> Result<Algorithm, KeyError> {
+    let mut alg = reader.read_sequence()?;
+    let oid = alg.read(TAG_OID)?;
+    let algorithm = match oid {
+        OID_RSA_ENCRYPTION => {
+            // The parameters are NULL, though some encoders omit them
+            if !alg.is_empty() {
+                alg.read_null()?;
+            }
+            Algorithm::Rsa
+        }
+        OID_EC_PUBLIC_KEY => Algorithm::Ec(EcCurve::from_oid(alg.read(TAG_OID)?)?),
+        _ => return Err(KeyError::UnsupportedAlgorithm),
+    };
+    alg.finish()?;
+    Ok(algorithm)
+}
+
+/// Check an RSA modulus and exponent and compute their bit lengths
+fn rsa_public_key<'a>(m: &'a [u8], e: &'a [u8]) -> Result<RsaPublicKey<'a>, KeyError> {
+    let m_bits = bit_len(m);
+    let e_bits = bit_len(e);
+    // An even modulus or exponent cannot belong to a valid key
+    let odd = |v: &[u8]| v.last().is_some_and(|b| b & 1 == 1);
+    if !(512..=RSA_MAX_BITS).contains(&m_bits) || !odd(m) || !odd(e) || e_bits > m_bits {
+        return Err(KeyError::InvalidKey);
+    }
+    Ok(RsaPublicKey {
+        m,
+        e,
+        m_bits,
+        e_bits,
+    })
+}
+
+/// Decode a PKCS#1 `RSAPublicKey` (`SEQUENCE { modulus, publicExponent }`)
+pub fn parse_rsa_public_key(der: &[u8]) -> Result<RsaPublicKey<'_>, KeyError> {
+    let mut key = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+    let m = key.read_unsigned()?;
+    let e = key.read_unsigned()?;
+    key.finish()?;
+    rsa_public_key(m, e)
+}
+
+/// Split an uncompressed SEC1 point `04 || x || y`
+fn ec_point(curve: EcCurve, point: &[u8]) -> Result<EcPublicKey<'_>, KeyError> {
+    let len = curve.scalar_len();
+    match point {
+        [0x04, coords @ ..] if coords.len() == 2 * len => {
+            let (x, y) = coords.split_at(len);
+            Ok(EcPublicKey { curve, x, y })
+        }
+        _ => Err(KeyError::InvalidKey),
+    }
+}
+
+/// Decode an X.509 `SubjectPublicKeyInfo` holding an RSA key or an
+/// uncompressed P-256 or P-384 point
+pub fn parse_spki(der: &[u8]) -> Result<PublicKeyInfo<'_>, KeyError> {
+    let mut spki = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+    let algorithm = read_algorithm(&mut spki)?;
+    let key = spki.read_bit_string()?;
+    spki.finish()?;
+
+    match algorithm {
+        Algorithm::Rsa => parse_rsa_public_key(key).map(PublicKeyInfo::Rsa),
+        Algorithm::Ec(curve) => ec_point(curve, key).map(PublicKeyInfo::Ec),
+    }
+}
+
+/// Decode a PKCS#1 `RSAPrivateKey`, with its CRT parameters
+fn parse_rsa_private_key(der: &[u8]) -> Result<RsaPrivateKey<'_>, KeyError> {
+    let mut key = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+    // Version 1 adds otherPrimeInfos, which the engine cannot use
+    if key.read_u32()? != 0 {
+        return Err(KeyError::UnsupportedVersion);
+    }
+    let m = key.read_unsigned()?;
+    let e = key.read_unsigned()?;
+    let d = key.read_unsigned()?;
+    let crt = RsaCrtParams {
+        p: key.read_unsigned()?,
+        q: key.read_unsigned()?,
+        dp: key.read_unsigned()?,
+        dq: key.read_unsigned()?,
+        qinv: key.read_unsigned()?,
+    };
+    key.finish()?;
+
+    let public = rsa_public_key(m, e)?;
+    let d_bits = bit_len(d);
+    if d_bits == 0 || d_bits > public.m_bits {
+        return Err(KeyError::InvalidKey);
+    }
+    Ok(RsaPrivateKey::with_crt(m, e, d, public.m_bits, d_bits, crt))
+}
+
+/// Decode an RFC 5915 `ECPrivateKey` on `curve`
+fn parse_ec_private_key(curve: EcCurve, der: &[u8]) -> Result<EcPrivateKey<'_>, KeyError> {
+    let mut key = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+    if key.read_u32()? != 1 {
+        return Err(KeyError::UnsupportedVersion);
+    }
+    let d = key.read(TAG_OCTET_STRING)?;
+    // Optional curve parameters, which must agree with the algorithm
+    if let Some(params) = key.read_optional(tag_context(0))? {
+        if EcCurve::from_oid(parse_single(params, TAG_OID)?)? != curve {
+            return Err(KeyError::InvalidKey);
+        }
+    }
+    // Optional public key, not needed to sign
+    key.read_optional(tag_context(1))?;
+    key.finish()?;
+
+    if d.len() != curve.scalar_len() || d.iter().all(|&b| b == 0) {
+        return Err(KeyError::InvalidKey);
+    }
+    Ok(EcPrivateKey { curve, d })
+}
+
+/// Decode a PKCS#8 `PrivateKeyInfo` holding an RSA or EC private key.
+/// Encrypted PKCS#8 is not supported.
+pub fn parse_pkcs8(der: &[u8]) -> Result<PrivateKeyInfo<'_>, KeyError> {
+    let mut info = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+    // Version 1 (OneAsymmetricKey) may append a public key, ignored here
+    let version = info.read_u32()?;
+    if version > 1 {
+        return Err(KeyError::UnsupportedVersion);
+    }
+    let algorithm = read_algorithm(&mut info)?;
+    let key = info.read(TAG_OCTET_STRING)?;
+    // Optional [0] attributes and, in version 1, [1] public key
+    info.read_optional(tag_context(0))?;
+    if version == 1 {
+        info.read_optional(tag_implicit(1))?;
+    }
+    info.finish()?;
+
+    match algorithm {
+        Algorithm::Rsa => parse_rsa_private_key(key).map(PrivateKeyInfo::Rsa),
+        Algorithm::Ec(curve) => parse_ec_private_key(curve, key).map(PrivateKeyInfo::Ec),
+    }
+}
@@ -2,17 +2,31 @@
 
 #![cfg_attr(not(test), no_std)]
 pub mod astdebug;
+mod bignum;
 pub mod common;
+pub mod crypto;
+pub mod der;
+mod ecc;
+pub mod ecdh;
 pub mod ecdsa;
 pub mod gpio;
 pub mod hace_controller;
 pub mod hash;
 pub mod hmac;
+pub mod kdf;
+pub mod keys;
 pub mod pinctrl;
+pub mod pkcs1;
 pub mod rsa;
+pub mod rsa_keygen;
+#[cfg(feature = "rustcrypto")]
+pub mod rustcrypto;
+pub mod sec1;
+pub mod secure_engine;
 pub mod spi;
 pub mod spimonitor;
 pub mod syscon;
 pub mod tests;
 pub mod uart;
 pub mod watchdog;
+pub mod x509;
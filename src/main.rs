@@ -3,25 +3,44 @@
 #![no_std]
 #![no_main]
 
-use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 // use core::arch::asm;
 use aspeed_ddk::uart::{Config, UartController};
 use aspeed_ddk::watchdog::WdtController;
-use ast1060_pac::Peripherals;
+use ast1060_pac::{interrupt, Hace, Interrupt, Peripherals};
 use ast1060_pac::{Wdt, Wdt1};
 
-use aspeed_ddk::ecdsa::AspeedEcdsa;
+use aspeed_ddk::crypto::AesController;
 use aspeed_ddk::hace_controller::HaceController;
-use aspeed_ddk::rsa::AspeedRsa;
+use aspeed_ddk::secure_engine::SecureEngine;
 use aspeed_ddk::spi;
 use aspeed_ddk::syscon::{ClockId, ResetId, SysCon};
 use fugit::MillisDurationU32 as MilliSeconds;
 
-use aspeed_ddk::tests::functional::ecdsa_test::run_ecdsa_tests;
+use aspeed_ddk::tests::functional::crypto_test::{run_aes_streaming_tests, run_aes_tests};
+use aspeed_ddk::tests::functional::ecdh_test::run_ecdh_tests;
+#[cfg(feature = "test-rng")]
+use aspeed_ddk::tests::functional::ecdsa_test::run_ecdsa_sign_tests;
+use aspeed_ddk::tests::functional::ecdsa_test::{
+    run_ecdsa_encoding_tests, run_ecdsa_p256_tests, run_ecdsa_tests,
+};
 use aspeed_ddk::tests::functional::gpio_test;
-use aspeed_ddk::tests::functional::hash_test::run_hash_tests;
-use aspeed_ddk::tests::functional::hmac_test::run_hmac_tests;
-use aspeed_ddk::tests::functional::rsa_test::run_rsa_tests;
+use aspeed_ddk::tests::functional::hash_test::{
+    run_hash_irq_tests, run_hash_nb_tests, run_hash_recovery_tests, run_hash_session_tests,
+    run_hash_sg_tests, run_hash_state_tests, run_hash_tests,
+};
+use aspeed_ddk::tests::functional::hmac_test::{run_hmac_streaming_tests, run_hmac_tests};
+use aspeed_ddk::tests::functional::kdf_test::run_kdf_tests;
+use aspeed_ddk::tests::functional::keys_test::run_key_parse_tests;
+#[cfg(feature = "test-rng")]
+use aspeed_ddk::tests::functional::rsa_test::{
+    run_rsa_blinding_tests, run_rsa_encryption_tests, run_rsa_keygen_tests, run_rsa_mod_exp_tests,
+    run_rsa_pkcs1_strict_tests, run_rsa_pss_salt_tests, run_rsa_pss_tests, run_rsa_tests,
+};
+use aspeed_ddk::tests::functional::secure_engine_test::run_secure_engine_tests;
+use aspeed_ddk::tests::functional::x509_test::run_x509_tests;
+#[cfg(feature = "test-rng")]
+use aspeed_ddk::tests::functional::TestRng;
 use panic_halt as _;
 
 use proposed_traits::system_control::ResetControl;
@@ -96,6 +115,15 @@ fn test_wdt(uart: &mut UartController<'_>) {
     }
 }
 
+/// HACE interrupts taken, for the interrupt-mode hash tests
+static HACE_IRQS: AtomicU32 = AtomicU32::new(0);
+
+#[interrupt]
+fn HACE() {
+    HACE_IRQS.fetch_add(1, Ordering::Relaxed);
+    HaceController::handle_interrupt(unsafe { &*Hace::ptr() });
+}
+
 #[no_mangle]
 pub static HALT: AtomicBool = AtomicBool::new(true);
 
@@ -152,17 +180,58 @@ fn main() -> ! {
     let mut hace_controller = HaceController::new(&hace);
 
     run_hash_tests(&mut uart_controller, &mut hace_controller);
+    run_hash_session_tests(&mut uart_controller, &hace);
+    run_hash_sg_tests(&mut uart_controller, &mut hace_controller);
+    run_hash_nb_tests(&mut uart_controller, &mut hace_controller);
+    unsafe { cortex_m::peripheral::NVIC::unmask(Interrupt::HACE) };
+    run_hash_irq_tests(&mut uart_controller, &mut hace_controller, &HACE_IRQS);
+    cortex_m::peripheral::NVIC::mask(Interrupt::HACE);
+    run_hash_state_tests(&mut uart_controller, &mut hace_controller);
+    run_hash_recovery_tests(&mut uart_controller, &mut hace_controller, &mut syscon);
 
     run_hmac_tests(&mut uart_controller, &mut hace_controller);
+    run_hmac_streaming_tests(&mut uart_controller, &mut hace_controller);
+    run_kdf_tests(&mut uart_controller, &mut hace_controller);
+    #[cfg(feature = "rustcrypto")]
+    aspeed_ddk::tests::functional::rustcrypto_test::run_rustcrypto_tests(&mut uart_controller);
 
-    // Enable RSA and ECC
-    let _ = syscon.enable_clock(ClockId::ClkRSACLK as u8);
+    let mut aes_controller = AesController::new(&hace);
+    run_aes_tests(&mut uart_controller, &mut aes_controller);
+    run_aes_streaming_tests(&mut uart_controller, &mut aes_controller);
 
-    let mut ecdsa = AspeedEcdsa::new(&secure, delay.clone());
-    run_ecdsa_tests(&mut uart_controller, &mut ecdsa);
+    // Enable RSA and ECC
+    let mut engine = SecureEngine::new(secure, &mut syscon, delay).unwrap();
+
+    {
+        let mut ecdsa = engine.ecc();
+        run_ecdsa_tests(&mut uart_controller, &mut *ecdsa);
+        run_ecdsa_p256_tests(&mut uart_controller, &mut *ecdsa);
+        #[cfg(feature = "test-rng")]
+        run_ecdsa_sign_tests(&mut uart_controller, &mut ecdsa);
+        run_ecdsa_encoding_tests(&mut uart_controller, &mut *ecdsa);
+        run_ecdh_tests(&mut uart_controller, &mut ecdsa);
+    }
 
-    let mut rsa = AspeedRsa::new(&secure, delay);
-    run_rsa_tests(&mut uart_controller, &mut rsa);
+    // Private-key operations need an RNG for blinding and PSS salts, which
+    // only the test-rng feature lets the test generator provide
+    #[cfg(feature = "test-rng")]
+    {
+        run_rsa_blinding_tests(&mut uart_controller, &mut engine, &mut hace_controller);
+        run_rsa_keygen_tests(&mut uart_controller, &mut engine);
+        // Only a stand-in for the hardware RNG, to exercise the blinding
+        let mut blinding = TestRng(0x853c_49e6_748f_ea9b);
+        let mut rsa = engine.rsa();
+        rsa.set_blinding_rng(&mut blinding);
+        run_rsa_tests(&mut uart_controller, &mut *rsa);
+        run_rsa_pss_tests(&mut uart_controller, &mut rsa, &mut hace_controller);
+        run_rsa_pss_salt_tests(&mut uart_controller, &mut rsa, &mut hace_controller);
+        run_rsa_pkcs1_strict_tests(&mut uart_controller, &mut rsa);
+        run_rsa_encryption_tests(&mut uart_controller, &mut rsa, &mut hace_controller);
+        run_rsa_mod_exp_tests(&mut uart_controller, &mut rsa);
+    }
+    run_secure_engine_tests(&mut uart_controller, &mut engine);
+    run_x509_tests(&mut uart_controller, &mut engine, &mut hace_controller);
+    run_key_parse_tests(&mut uart_controller);
     gpio_test::test_gpioa(&mut uart_controller);
     test_wdt(&mut uart_controller);
 
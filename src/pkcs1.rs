@@ -0,0 +1,404 @@
+// Licensed under the Apache-2.0 license
+
+//! PKCS#1 (RFC 8017) message encodings used around the RSA engine. The
+//! hashes inside the encodings (MGF1 and the PSS `M'` hash) run on HACE.
+
+use crate::common::{ct_eq, wipe};
+use crate::hace_controller::HaceController;
+use crate::hash::{HashError, IntoHashAlgo, Sha256, Sha384, Sha512};
+use proposed_traits::digest::{DigestAlgorithm, DigestInit, DigestOp};
+
+/// Longest PSS salt accepted for signing
+pub const PSS_MAX_SALT_LEN: usize = 64;
+
+/// Largest digest of the supported hashes
+const MAX_HASH_LEN: usize = 64;
+
+/// Longest encoded message, for a 4096-bit modulus
+const MAX_EM_LEN: usize = 512;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Pkcs1Error {
+    /// The digest length does not match SHA-256, SHA-384 or SHA-512
+    UnsupportedDigest,
+    /// The modulus is too small for the digest and salt
+    EncodingTooShort,
+    /// The encoded message is malformed or does not match the digest
+    Inconsistent,
+    /// The output buffer cannot hold the decoded message
+    BufferTooSmall,
+    /// Not enough random bytes were supplied for the padding
+    InsufficientRandom,
+    /// Failure hashing on the HACE engine
+    Hash(HashError),
+}
+
+impl From<HashError> for Pkcs1Error {
+    fn from(err: HashError) -> Self {
+        Pkcs1Error::Hash(err)
+    }
+}
+
+fn digest_with<A>(
+    hace: &mut HaceController,
+    parts: &[&[u8]],
+    out: &mut [u8],
+) -> Result<(), HashError>
+where
+    A: DigestAlgorithm + IntoHashAlgo + Default,
+    A::DigestOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let mut op = hace.init(A::default())?;
+    for part in parts {
+        op.update(part)?;
+    }
+    let digest = op.finalize()?;
+    out.copy_from_slice(&digest.as_ref()[..out.len()]);
+    Ok(())
+}
+
+/// Hash `parts[0] || parts[1] || ...` with the hash whose output is
+/// `out.len()` bytes long
+pub(crate) fn digest_parts(
+    hace: &mut HaceController,
+    parts: &[&[u8]],
+    out: &mut [u8],
+) -> Result<(), Pkcs1Error> {
+    match out.len() {
+        32 => digest_with::<Sha256>(hace, parts, out)?,
+        48 => digest_with::<Sha384>(hace, parts, out)?,
+        64 => digest_with::<Sha512>(hace, parts, out)?,
+        _ => return Err(Pkcs1Error::UnsupportedDigest),
+    }
+    Ok(())
+}
+
+/// XOR `MGF1(seed)` into `out`, using the hash with a `hash_len` byte output
+pub fn mgf1_xor(
+    hace: &mut HaceController,
+    hash_len: usize,
+    seed: &[u8],
+    out: &mut [u8],
+) -> Result<(), Pkcs1Error> {
+    let mut block = [0u8; MAX_HASH_LEN];
+    let mut result = Ok(());
+    for (counter, chunk) in (0u32..).zip(out.chunks_mut(hash_len)) {
+        result = digest_parts(
+            hace,
+            &[seed, &counter.to_be_bytes()],
+            &mut block[..hash_len],
+        );
+        if result.is_err() {
+            break;
+        }
+        for (o, m) in chunk.iter_mut().zip(block.iter()) {
+            *o ^= m;
+        }
+    }
+    wipe(&mut block);
+    result
+}
+
+/// Hash used by RSAES-OAEP for the label and MGF1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OaepHash {
+    Sha256,
+    Sha384,
+}
+
+impl OaepHash {
+    #[must_use]
+    pub fn digest_len(self) -> usize {
+        match self {
+            OaepHash::Sha256 => 32,
+            OaepHash::Sha384 => 48,
+        }
+    }
+}
+
+/// `1` if `a == b`, `0` otherwise, without branching on the values
+fn eq_mask(a: u8, b: u8) -> u8 {
+    let diff = a ^ b;
+    // The top bit of `diff | -diff` is set exactly when `diff` is non-zero
+    1 ^ ((diff | diff.wrapping_neg()) >> 7)
+}
+
+/// Mask covering the bits of the first byte beyond `em_bits`
+fn top_byte_mask(em_len: usize, em_bits: usize) -> u8 {
+    0xff >> (8 * em_len - em_bits)
+}
+
+/// `H = Hash(0x00 * 8 || mHash || salt)`
+fn pss_hash(
+    hace: &mut HaceController,
+    m_hash: &[u8],
+    salt: &[u8],
+    out: &mut [u8],
+) -> Result<(), Pkcs1Error> {
+    digest_parts(hace, &[&[0u8; 8], m_hash, salt], out)
+}
+
+/// EMSA-PSS-ENCODE (RFC 8017 section 9.1.1) of the digest `m_hash`.
+///
+/// `em` must be `ceil(em_bits / 8)` bytes, with `em_bits` one less than the
+/// modulus size. MGF1 uses the same hash as the digest.
+pub fn emsa_pss_encode(
+    hace: &mut HaceController,
+    m_hash: &[u8],
+    salt: &[u8],
+    em_bits: usize,
+    em: &mut [u8],
+) -> Result<(), Pkcs1Error> {
+    let h_len = m_hash.len();
+    let em_len = em.len();
+    if em_len != em_bits.div_ceil(8) {
+        return Err(Pkcs1Error::EncodingTooShort);
+    }
+    if em_len < h_len + salt.len() + 2 {
+        return Err(Pkcs1Error::EncodingTooShort);
+    }
+
+    let db_len = em_len - h_len - 1;
+    let (db, tail) = em.split_at_mut(db_len);
+    let (h, trailer) = tail.split_at_mut(h_len);
+
+    pss_hash(hace, m_hash, salt, h)?;
+
+    // DB = PS || 0x01 || salt
+    db.fill(0);
+    db[db_len - salt.len() - 1] = 0x01;
+    db[db_len - salt.len()..].copy_from_slice(salt);
+    mgf1_xor(hace, h_len, h, db)?;
+    db[0] &= top_byte_mask(em_len, em_bits);
+
+    trailer[0] = 0xbc;
+    Ok(())
+}
+
+/// EMSA-PSS-VERIFY (RFC 8017 section 9.1.2) of `em` against `m_hash`.
+///
+/// With `salt_len` set, only that salt length is accepted; with `None`
+/// the salt length is recovered from the encoding.
+pub fn emsa_pss_verify(
+    hace: &mut HaceController,
+    m_hash: &[u8],
+    em: &[u8],
+    em_bits: usize,
+    salt_len: Option<usize>,
+) -> Result<(), Pkcs1Error> {
+    let h_len = m_hash.len();
+    let em_len = em.len();
+    if h_len > MAX_HASH_LEN {
+        return Err(Pkcs1Error::UnsupportedDigest);
+    }
+    if em_len > MAX_EM_LEN
+        || em_len != em_bits.div_ceil(8)
+        || em_len < h_len + salt_len.unwrap_or(0) + 2
+    {
+        return Err(Pkcs1Error::Inconsistent);
+    }
+    if em[em_len - 1] != 0xbc {
+        return Err(Pkcs1Error::Inconsistent);
+    }
+
+    let db_len = em_len - h_len - 1;
+    let (masked_db, tail) = em.split_at(db_len);
+    let h = &tail[..h_len];
+    let top_mask = top_byte_mask(em_len, em_bits);
+    if masked_db[0] & !top_mask != 0 {
+        return Err(Pkcs1Error::Inconsistent);
+    }
+
+    let mut db_buf = [0u8; MAX_EM_LEN];
+    let db = &mut db_buf[..db_len];
+    db.copy_from_slice(masked_db);
+    let result = mgf1_xor(hace, h_len, h, db).and_then(|()| {
+        db[0] &= top_mask;
+
+        // DB = PS || 0x01 || salt, with PS all zero
+        let sep = db
+            .iter()
+            .position(|&b| b != 0)
+            .ok_or(Pkcs1Error::Inconsistent)?;
+        if db[sep] != 0x01 {
+            return Err(Pkcs1Error::Inconsistent);
+        }
+        let salt = &db[sep + 1..];
+        if salt_len.is_some_and(|len| len != salt.len()) {
+            return Err(Pkcs1Error::Inconsistent);
+        }
+
+        let mut expected = [0u8; MAX_HASH_LEN];
+        pss_hash(hace, m_hash, salt, &mut expected[..h_len])?;
+        if ct_eq(&expected[..h_len], h) {
+            Ok(())
+        } else {
+            Err(Pkcs1Error::Inconsistent)
+        }
+    });
+    wipe(db);
+    result
+}
+
+/// EME-OAEP encoding (RFC 8017 section 7.1.1) of `msg` into `em`, which is
+/// as long as the modulus. `seed` must be `hash.digest_len()` random bytes.
+pub fn eme_oaep_encode(
+    hace: &mut HaceController,
+    hash: OaepHash,
+    label: &[u8],
+    msg: &[u8],
+    seed: &[u8],
+    em: &mut [u8],
+) -> Result<(), Pkcs1Error> {
+    let h_len = hash.digest_len();
+    let k = em.len();
+    if seed.len() != h_len {
+        return Err(Pkcs1Error::InsufficientRandom);
+    }
+    if k < 2 * h_len + 2 || msg.len() > k - 2 * h_len - 2 {
+        return Err(Pkcs1Error::EncodingTooShort);
+    }
+
+    // EM = 0x00 || maskedSeed || maskedDB
+    let (y, rest) = em.split_at_mut(1);
+    let (masked_seed, db) = rest.split_at_mut(h_len);
+    y[0] = 0;
+
+    // DB = lHash || PS || 0x01 || M
+    let db_len = db.len();
+    digest_parts(hace, &[label], &mut db[..h_len])?;
+    db[h_len..db_len - msg.len() - 1].fill(0);
+    db[db_len - msg.len() - 1] = 0x01;
+    db[db_len - msg.len()..].copy_from_slice(msg);
+
+    mgf1_xor(hace, h_len, seed, db)?;
+    masked_seed.copy_from_slice(seed);
+    mgf1_xor(hace, h_len, db, masked_seed)
+}
+
+/// EME-OAEP decoding (RFC 8017 section 7.1.2) of `em` into `out`, returning
+/// the message length.
+///
+/// Every malformed encoding fails with the same `Inconsistent` error, and
+/// the checks run over the whole encoding without early exits.
+pub fn eme_oaep_decode(
+    hace: &mut HaceController,
+    hash: OaepHash,
+    label: &[u8],
+    em: &[u8],
+    out: &mut [u8],
+) -> Result<usize, Pkcs1Error> {
+    let h_len = hash.digest_len();
+    let k = em.len();
+    if k > MAX_EM_LEN || k < 2 * h_len + 2 {
+        return Err(Pkcs1Error::Inconsistent);
+    }
+
+    let mut buf = [0u8; MAX_EM_LEN];
+    let buf = &mut buf[..k];
+    buf.copy_from_slice(em);
+    let result = oaep_unmask(hace, h_len, label, buf).and_then(|start| {
+        let msg = &buf[start..];
+        let dest = out.get_mut(..msg.len()).ok_or(Pkcs1Error::BufferTooSmall)?;
+        dest.copy_from_slice(msg);
+        Ok(msg.len())
+    });
+    wipe(buf);
+    result
+}
+
+/// Unmask an OAEP encoding in place and return the offset of the message
+fn oaep_unmask(
+    hace: &mut HaceController,
+    h_len: usize,
+    label: &[u8],
+    em: &mut [u8],
+) -> Result<usize, Pkcs1Error> {
+    let mut l_hash = [0u8; MAX_HASH_LEN];
+    digest_parts(hace, &[label], &mut l_hash[..h_len])?;
+
+    let (y, rest) = em.split_at_mut(1);
+    let (seed, db) = rest.split_at_mut(h_len);
+    mgf1_xor(hace, h_len, db, seed)?;
+    mgf1_xor(hace, h_len, seed, db)?;
+
+    // DB = lHash' || PS || 0x01 || M; find the 0x01 without branching on
+    // the padding bytes
+    let mut bad = 1 ^ eq_mask(y[0], 0);
+    bad |= u8::from(!ct_eq(&db[..h_len], &l_hash[..h_len]));
+    let mut found = 0u8;
+    let mut sep = 0usize;
+    for (i, &b) in db.iter().enumerate().skip(h_len) {
+        let looking = 1 ^ found;
+        let one = eq_mask(b, 0x01);
+        let zero = eq_mask(b, 0x00);
+        sep |= usize::from(looking & one).wrapping_neg() & i;
+        bad |= looking & (1 ^ one) & (1 ^ zero);
+        found |= one;
+    }
+    bad |= 1 ^ found;
+
+    if bad == 0 {
+        Ok(1 + h_len + sep + 1)
+    } else {
+        Err(Pkcs1Error::Inconsistent)
+    }
+}
+
+/// EME-PKCS1-v1_5 encoding (RFC 8017 section 7.2.1) of `msg` into `em`,
+/// which is as long as the modulus.
+///
+/// The padding string is taken from the non-zero bytes of `random`, in
+/// order; zero bytes are skipped. Supplying `em.len() - msg.len() - 3`
+/// random bytes is usually enough, a few more make running out unlikely.
+pub fn eme_pkcs1_v15_encode(msg: &[u8], random: &[u8], em: &mut [u8]) -> Result<(), Pkcs1Error> {
+    let k = em.len();
+    if k < 11 || msg.len() > k - 11 {
+        return Err(Pkcs1Error::EncodingTooShort);
+    }
+
+    // EM = 0x00 || 0x02 || PS || 0x00 || M
+    let ps_len = k - msg.len() - 3;
+    let mut random = random.iter().copied().filter(|&b| b != 0);
+    for b in &mut em[2..2 + ps_len] {
+        *b = random.next().ok_or(Pkcs1Error::InsufficientRandom)?;
+    }
+    em[0] = 0x00;
+    em[1] = 0x02;
+    em[2 + ps_len] = 0x00;
+    em[3 + ps_len..].copy_from_slice(msg);
+    Ok(())
+}
+
+/// EME-PKCS1-v1_5 decoding (RFC 8017 section 7.2.2) of `em` into `out`,
+/// returning the message length.
+///
+/// Like [`eme_oaep_decode`], malformed encodings all fail with the same
+/// error after a scan of the whole encoding.
+pub fn eme_pkcs1_v15_decode(em: &[u8], out: &mut [u8]) -> Result<usize, Pkcs1Error> {
+    let k = em.len();
+    if k < 11 {
+        return Err(Pkcs1Error::Inconsistent);
+    }
+
+    let mut bad = (1 ^ eq_mask(em[0], 0x00)) | (1 ^ eq_mask(em[1], 0x02));
+    let mut found = 0u8;
+    let mut sep = 0usize;
+    for (i, &b) in em.iter().enumerate().skip(2) {
+        let zero = eq_mask(b, 0x00);
+        sep |= usize::from((1 ^ found) & zero).wrapping_neg() & i;
+        found |= zero;
+    }
+    bad |= 1 ^ found;
+    // PS must be at least eight bytes
+    bad |= u8::from(sep < 10);
+
+    if bad != 0 {
+        return Err(Pkcs1Error::Inconsistent);
+    }
+    let msg = &em[sep + 1..];
+    out.get_mut(..msg.len())
+        .ok_or(Pkcs1Error::BufferTooSmall)?
+        .copy_from_slice(msg);
+    Ok(msg.len())
+}
@@ -1,16 +1,25 @@
 // Licensed under the Apache-2.0 license
 
+use crate::bignum::{self, Scratch, MAX_LIMBS};
+use crate::common::{bit_len, ct_eq, wipe};
+use crate::hace_controller::HaceController;
+use crate::pkcs1::{
+    eme_oaep_decode, eme_oaep_encode, eme_pkcs1_v15_decode, eme_pkcs1_v15_encode, emsa_pss_encode,
+    emsa_pss_verify, OaepHash, Pkcs1Error, PSS_MAX_SALT_LEN,
+};
+use crate::secure_engine::EngineLock;
 use ast1060_pac::Secure;
-use core::ptr::{read_volatile, write_bytes, write_volatile, NonNull};
+use core::ptr::{read_volatile, write_volatile, NonNull};
 use embedded_hal::delay::DelayNs;
 use proposed_traits::common::{
     Endian, ErrorKind as CommonErrorKind, ErrorType as CommonErrorType, FromBytes,
     SerdeError as CommonSerdeError, ToBytes,
 };
 use proposed_traits::rsa::{
-    Error, ErrorKind, ErrorType as RsaErrorType, PaddingMode, RsaKeyGen, RsaKeys, RsaMessage,
-    RsaSign, RsaSignature, RsaSize, RsaVerify,
+    Error, ErrorKind, ErrorType as RsaErrorType, PaddingMode, RsaKeys, RsaMessage, RsaSign,
+    RsaSignature, RsaVerify,
 };
+use rand_core::CryptoRngCore;
 
 const RSA_SRAM_BASE: usize = 0x7900_0000; // SBC base address
 
@@ -22,28 +31,124 @@ const SRAM_SIZE: usize = 0x1800; // SRAM size for RSA operations
 
 const RSA_MAX_LEN: usize = 0x400;
 
+/// Largest modulus and exponent the engine takes, in bits
+pub const RSA_MAX_BITS: u32 = 4096;
+
 #[derive(Debug)]
 pub enum RsaDriverError {
     InvalidLength,
     HardwareError,
+    /// The recovered encoding is not well-formed padding for the digest
+    InvalidPadding,
+    /// The padding is well-formed but carries a different digest
     VerificationFailed,
+    /// The ciphertext does not decrypt to a valid encoding
+    DecryptionFailed,
+    /// A `mod_exp` operand is out of range for the modulus
+    InvalidOperand,
+    /// Another RSA or ECC operation is using the engine
+    Busy,
+    /// No random source for blinding was set with
+    /// [`AspeedRsa::set_blinding_rng`], so private-key operations are refused
+    NoBlindingRng,
+    /// PSS was asked of `RsaSign` or `RsaVerify`, which cannot take the hash
+    /// engine; use [`AspeedRsa::sign_pss`] or [`AspeedRsa::verify_pss`]
+    NoHashEngine,
+}
+
+impl From<Pkcs1Error> for RsaDriverError {
+    fn from(err: Pkcs1Error) -> Self {
+        match err {
+            Pkcs1Error::UnsupportedDigest
+            | Pkcs1Error::EncodingTooShort
+            | Pkcs1Error::BufferTooSmall
+            | Pkcs1Error::InsufficientRandom => RsaDriverError::InvalidLength,
+            Pkcs1Error::Inconsistent => RsaDriverError::VerificationFailed,
+            Pkcs1Error::Hash(_) => RsaDriverError::HardwareError,
+        }
+    }
 }
 
 impl Error for RsaDriverError {
     fn kind(&self) -> ErrorKind {
         match self {
-            RsaDriverError::InvalidLength => ErrorKind::InvalidLength,
-            RsaDriverError::HardwareError => ErrorKind::SignError,
-            RsaDriverError::VerificationFailed => ErrorKind::VerifyError,
+            RsaDriverError::InvalidLength | RsaDriverError::InvalidOperand => {
+                ErrorKind::InvalidLength
+            }
+            RsaDriverError::HardwareError
+            | RsaDriverError::Busy
+            | RsaDriverError::NoBlindingRng
+            | RsaDriverError::NoHashEngine => ErrorKind::SignError,
+            RsaDriverError::InvalidPadding
+            | RsaDriverError::VerificationFailed
+            | RsaDriverError::DecryptionFailed => ErrorKind::VerifyError,
         }
     }
 }
 
+/// RSA private key.
+///
+/// `e` is needed for blinding and for checking CRT results. Keys built
+/// with [`RsaPrivateKey::with_crt`] use the CRT parameters instead of `d`
+/// for private-key operations.
 pub struct RsaPrivateKey<'a> {
     pub m: &'a [u8],
     pub d: &'a [u8],
     pub m_bits: u32,
     pub d_bits: u32,
+    e: &'a [u8],
+    crt: Option<RsaCrtParams<'a>>,
+}
+
+impl<'a> RsaPrivateKey<'a> {
+    #[must_use]
+    pub fn new(m: &'a [u8], e: &'a [u8], d: &'a [u8], m_bits: u32, d_bits: u32) -> Self {
+        Self {
+            m,
+            d,
+            m_bits,
+            d_bits,
+            e,
+            crt: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_crt(
+        m: &'a [u8],
+        e: &'a [u8],
+        d: &'a [u8],
+        m_bits: u32,
+        d_bits: u32,
+        crt: RsaCrtParams<'a>,
+    ) -> Self {
+        Self {
+            crt: Some(crt),
+            ..Self::new(m, e, d, m_bits, d_bits)
+        }
+    }
+
+    #[must_use]
+    pub fn e(&self) -> &'a [u8] {
+        self.e
+    }
+
+    #[must_use]
+    pub fn crt(&self) -> Option<&RsaCrtParams<'a>> {
+        self.crt.as_ref()
+    }
+}
+
+/// CRT form of an RSA private key (RFC 8017 section 3.2), big-endian
+pub struct RsaCrtParams<'a> {
+    pub p: &'a [u8],
+    pub q: &'a [u8],
+    /// `d mod (p - 1)`
+    pub dp: &'a [u8],
+    /// `d mod (q - 1)`
+    pub dq: &'a [u8],
+    /// `q^-1 mod p`
+    pub qinv: &'a [u8],
 }
 
 pub struct RsaPublicKey<'a> {
@@ -53,6 +158,12 @@ pub struct RsaPublicKey<'a> {
     pub e_bits: u32,
 }
 
+/// RSAES-OAEP parameters; MGF1 uses the same hash as the label
+pub struct OaepParams<'a> {
+    pub hash: OaepHash,
+    pub label: &'a [u8],
+}
+
 pub struct RsaSignatureData {
     pub data: [u8; 512],
     pub len: usize,
@@ -158,6 +269,9 @@ pub struct AspeedRsa<'a, D: DelayNs> {
     pub secure: &'a Secure,
     sram_base: NonNull<u8>,
     delay: D,
+    pss_salt_len: Option<usize>,
+    pss_verify_salt_len: Option<usize>,
+    rng: Option<&'a mut dyn CryptoRngCore>,
 }
 
 impl<'a, D: DelayNs> AspeedRsa<'a, D> {
@@ -166,9 +280,38 @@ impl<'a, D: DelayNs> AspeedRsa<'a, D> {
             secure,
             sram_base: unsafe { NonNull::new_unchecked(RSA_SRAM_BASE as *mut u8) },
             delay,
+            pss_salt_len: None,
+            pss_verify_salt_len: None,
+            rng: None,
         }
     }
 
+    /// Random source for blinding private-key operations, which fail with
+    /// [`RsaDriverError::NoBlindingRng`] until one is set. Every signature
+    /// and decryption multiplies its input by `r^e` for a fresh random `r`
+    /// and divides the result by `r`, so the engine never exponentiates a
+    /// value an attacker chose or knows. PSS salts are drawn from it too.
+    pub fn set_blinding_rng(&mut self, rng: &'a mut dyn CryptoRngCore) {
+        self.rng = Some(rng);
+    }
+
+    /// Salt length of the following PSS signatures, or `None` (the default)
+    /// for the digest length. Every signature draws a fresh salt from the
+    /// random source; a length of 0 makes PSS signing deterministic.
+    pub fn set_pss_salt_len(&mut self, salt_len: Option<usize>) -> Result<(), RsaDriverError> {
+        if salt_len.is_some_and(|len| len > PSS_MAX_SALT_LEN) {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        self.pss_salt_len = salt_len;
+        Ok(())
+    }
+
+    /// Salt length PSS verification insists on, or `None` (the default) to
+    /// accept any salt length recovered from the signature.
+    pub fn set_pss_verify_salt_len(&mut self, salt_len: Option<usize>) {
+        self.pss_verify_salt_len = salt_len;
+    }
+
     pub fn pkcs1_v1_5_pad_inplace(digest: &[u8], out: &mut [u8]) -> Result<usize, PaddingError> {
         const DER_SHA256: &[u8] = &[
             0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
@@ -222,6 +365,30 @@ impl<'a, D: DelayNs> AspeedRsa<'a, D> {
         Ok(idx)
     }
 
+    /// Check a recovered EMSA-PKCS1-v1_5 block against the digest.
+    ///
+    /// The full expected encoding is rebuilt for the digest's hash and all
+    /// `em.len()` bytes are compared in constant time, so the padding, the
+    /// DigestInfo prefix and the digest are all checked.
+    fn pkcs1_v1_5_verify(digest: &[u8], em: &[u8]) -> Result<(), RsaDriverError> {
+        let mut expected = [0u8; 512];
+        let expected = &mut expected[..em.len()];
+        Self::pkcs1_v1_5_pad_inplace(digest, expected)
+            .map_err(|_e| RsaDriverError::InvalidLength)?;
+
+        let split = em.len() - digest.len();
+        let padding_ok = ct_eq(&expected[..split], &em[..split]);
+        let digest_ok = ct_eq(&expected[split..], &em[split..]);
+
+        if !padding_ok {
+            Err(RsaDriverError::InvalidPadding)
+        } else if !digest_ok {
+            Err(RsaDriverError::VerificationFailed)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn aspeed_rsa_trigger(
         &mut self,
         input: &[u8],
@@ -234,14 +401,34 @@ impl<'a, D: DelayNs> AspeedRsa<'a, D> {
         if input.len() > 512 {
             return Err(RsaDriverError::InvalidLength);
         }
+        let _lock = EngineLock::acquire().ok_or(RsaDriverError::Busy)?;
 
-        unsafe {
-            for i in 0..SRAM_SIZE {
-                write_volatile(self.sram_base.as_ptr().add(i), 0);
-            }
+        self.wipe_sram();
+        let result = self.run_engine(input, output, m, e_or_d, m_bits, ed_bits);
+        // The exponent may be a private key, so wipe on every exit path
+        self.wipe_sram();
+        result
+    }
 
+    fn wipe_sram(&self) {
+        for i in 0..SRAM_SIZE {
+            unsafe { write_volatile(self.sram_base.as_ptr().add(i), 0) };
+        }
+    }
+
+    /// Load the operands, run the engine and read back the result, leaving
+    /// the operands in SRAM for the caller to wipe
+    fn run_engine(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        m: &[u8],
+        e_or_d: &[u8],
+        m_bits: u32,
+        ed_bits: u32,
+    ) -> Result<usize, RsaDriverError> {
+        unsafe {
             for (i, byte) in e_or_d.iter().rev().enumerate() {
-                // print byte
                 write_volatile(self.sram_base.as_ptr().add(SRAM_DST_EXPONENT + i), *byte);
             }
 
@@ -285,19 +472,586 @@ impl<'a, D: DelayNs> AspeedRsa<'a, D> {
                 }
                 leading_zero = false;
                 if i < output.len() {
-                    output[i] = byte as u8;
+                    output[i] = byte;
                     i += 1;
                 }
                 out_len += 1;
             }
 
-            write_bytes(self.sram_base.as_ptr(), 0, SRAM_SIZE);
-
             Ok(out_len)
         }
     }
 }
 
+impl<D: DelayNs> AspeedRsa<'_, D> {
+    /// Run the engine and write the result big-endian into all of `out`,
+    /// restoring the leading zeros the engine drops
+    fn exp_mod_padded(
+        &mut self,
+        input: &[u8],
+        m: &[u8],
+        e_or_d: &[u8],
+        m_bits: u32,
+        ed_bits: u32,
+        out: &mut [u8],
+    ) -> Result<(), RsaDriverError> {
+        let mut output = [0u8; 512];
+        let result = self
+            .aspeed_rsa_trigger(input, &mut output, m, e_or_d, m_bits, ed_bits)
+            .and_then(|len| {
+                let pad = out
+                    .len()
+                    .checked_sub(len)
+                    .ok_or(RsaDriverError::HardwareError)?;
+                out[..pad].fill(0);
+                out[pad..].copy_from_slice(&output[..len]);
+                Ok(())
+            });
+        wipe(&mut output);
+        result
+    }
+
+    /// Compute `base ^ exponent mod modulus` on the engine.
+    ///
+    /// All operands are unsigned big-endian integers and may carry leading
+    /// zero bytes. The result is written big-endian into `out`, which must
+    /// be exactly as long as `modulus`, so it keeps a fixed width whatever
+    /// its value.
+    ///
+    /// # Errors
+    /// - `RsaDriverError::InvalidLength` if `modulus` or `exponent` is wider
+    ///   than [`RSA_MAX_BITS`], or `out` is not as long as `modulus`
+    /// - `RsaDriverError::InvalidOperand` if `modulus` is below 2 or `base`
+    ///   is not below `modulus`
+    /// - `RsaDriverError::HardwareError` if the engine does not complete
+    pub fn mod_exp(
+        &mut self,
+        base: &[u8],
+        exponent: &[u8],
+        modulus: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), RsaDriverError> {
+        let m_bits = bit_len(modulus);
+        let e_bits = bit_len(exponent);
+        if m_bits > RSA_MAX_BITS || e_bits > RSA_MAX_BITS || out.len() != modulus.len() {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let m = strip_leading_zeros(modulus);
+        let b = strip_leading_zeros(base);
+        // Magnitudes without leading zeros compare by length first
+        if m_bits < 2 || (b.len(), b) >= (m.len(), m) {
+            return Err(RsaDriverError::InvalidOperand);
+        }
+
+        // x ^ 0 = 1, which the engine is not asked to compute
+        if e_bits == 0 {
+            out.fill(0);
+            out[out.len() - 1] = 1;
+            return Ok(());
+        }
+
+        let width = out.len();
+        self.exp_mod_padded(
+            b,
+            m,
+            strip_leading_zeros(exponent),
+            m_bits,
+            e_bits,
+            &mut out[width - m.len()..],
+        )?;
+        out[..width - m.len()].fill(0);
+        Ok(())
+    }
+
+    /// RSAES-OAEP encryption (RFC 8017 section 7.1.1) of `msg` to
+    /// `public_key`.
+    ///
+    /// `seed` must be `params.hash.digest_len()` fresh random bytes. MGF1
+    /// and the label hash run on `hace`. The ciphertext, as long as the
+    /// modulus, is written to the start of `out` and its length returned.
+    pub fn encrypt_oaep(
+        &mut self,
+        hace: &mut HaceController,
+        public_key: &RsaPublicKey<'_>,
+        params: &OaepParams<'_>,
+        seed: &[u8],
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, RsaDriverError> {
+        let m_len = ((public_key.m_bits + 7) / 8) as usize;
+        let e_len = ((public_key.e_bits + 7) / 8) as usize;
+        if m_len > 512 || out.len() < m_len {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        let mut em = [0u8; 512];
+        let result = eme_oaep_encode(hace, params.hash, params.label, msg, seed, &mut em[..m_len])
+            .map_err(RsaDriverError::from)
+            .and_then(|()| {
+                self.exp_mod_padded(
+                    &em[..m_len],
+                    &public_key.m[..m_len],
+                    &public_key.e[..e_len],
+                    public_key.m_bits,
+                    public_key.e_bits,
+                    &mut out[..m_len],
+                )
+            });
+        wipe(&mut em);
+        result.map(|()| m_len)
+    }
+
+    /// RSAES-OAEP decryption (RFC 8017 section 7.1.2) of `ciphertext` with
+    /// `private_key`, writing the message to the start of `out` and
+    /// returning its length. MGF1 and the label hash run on `hace`.
+    ///
+    /// Any malformed ciphertext fails with `RsaDriverError::DecryptionFailed`.
+    pub fn decrypt_oaep(
+        &mut self,
+        hace: &mut HaceController,
+        private_key: &RsaPrivateKey<'_>,
+        params: &OaepParams<'_>,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, RsaDriverError> {
+        let mut em = [0u8; 512];
+        let result = self
+            .decrypt_raw(private_key, ciphertext, &mut em)
+            .and_then(|m_len| {
+                eme_oaep_decode(hace, params.hash, params.label, &em[..m_len], out)
+                    .map_err(decryption_error)
+            });
+        wipe(&mut em);
+        result
+    }
+
+    /// RSAES-PKCS1-v1_5 encryption (RFC 8017 section 7.2.1) of `msg` to
+    /// `public_key`.
+    ///
+    /// The padding string is drawn from the non-zero bytes of `random`,
+    /// which should hold a little more than `modulus length - msg.len() - 3`
+    /// fresh random bytes. The ciphertext is written to the start of `out`
+    /// and its length returned.
+    pub fn encrypt_pkcs1_v1_5(
+        &mut self,
+        public_key: &RsaPublicKey<'_>,
+        random: &[u8],
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, RsaDriverError> {
+        let m_len = ((public_key.m_bits + 7) / 8) as usize;
+        let e_len = ((public_key.e_bits + 7) / 8) as usize;
+        if m_len > 512 || out.len() < m_len {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        let mut em = [0u8; 512];
+        let result = eme_pkcs1_v15_encode(msg, random, &mut em[..m_len])
+            .map_err(RsaDriverError::from)
+            .and_then(|()| {
+                self.exp_mod_padded(
+                    &em[..m_len],
+                    &public_key.m[..m_len],
+                    &public_key.e[..e_len],
+                    public_key.m_bits,
+                    public_key.e_bits,
+                    &mut out[..m_len],
+                )
+            });
+        wipe(&mut em);
+        result.map(|()| m_len)
+    }
+
+    /// RSAES-PKCS1-v1_5 decryption (RFC 8017 section 7.2.2) of `ciphertext`
+    /// with `private_key`, writing the message to the start of `out` and
+    /// returning its length.
+    ///
+    /// Any malformed ciphertext fails with `RsaDriverError::DecryptionFailed`.
+    /// Callers unwrapping keys should still treat that error exactly like a
+    /// wrong key, to avoid becoming a padding oracle.
+    pub fn decrypt_pkcs1_v1_5(
+        &mut self,
+        private_key: &RsaPrivateKey<'_>,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, RsaDriverError> {
+        let mut em = [0u8; 512];
+        let result = self
+            .decrypt_raw(private_key, ciphertext, &mut em)
+            .and_then(|m_len| eme_pkcs1_v15_decode(&em[..m_len], out).map_err(decryption_error));
+        wipe(&mut em);
+        result
+    }
+
+    /// RSASSA-PSS signature (RFC 8017 section 8.1.1) of the digest
+    /// `message`, left-padded with zeros to the modulus length.
+    ///
+    /// MGF1 and the `M'` hash use the digest's hash on `hace`. The salt is
+    /// drawn from the random source for each signature, as long as set with
+    /// [`AspeedRsa::set_pss_salt_len`]. The private-key operation is the
+    /// same as for [`RsaSign::sign`].
+    pub fn sign_pss(
+        &mut self,
+        hace: &mut HaceController,
+        private_key: &RsaPrivateKey<'_>,
+        message: &RsaDigest,
+    ) -> Result<RsaSignatureData, RsaDriverError> {
+        // emBits = modBits - 1, so the encoding is one byte shorter than the
+        // modulus when modBits is 1 mod 8
+        let em_bits = (private_key.m_bits as usize)
+            .checked_sub(1)
+            .ok_or(RsaDriverError::InvalidLength)?;
+        let em_len = em_bits.div_ceil(8);
+        if em_len > 512 {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        let salt_len = self.pss_salt_len.unwrap_or(message.len);
+        if salt_len > PSS_MAX_SALT_LEN {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let rng = self.rng.as_mut().ok_or(RsaDriverError::NoBlindingRng)?;
+        let mut salt = [0u8; PSS_MAX_SALT_LEN];
+        if rng.try_fill_bytes(&mut salt[..salt_len]).is_err() {
+            return Err(RsaDriverError::HardwareError);
+        }
+
+        let mut em = [0u8; 512];
+        let result = emsa_pss_encode(
+            hace,
+            &message.data[..message.len],
+            &salt[..salt_len],
+            em_bits,
+            &mut em[..em_len],
+        );
+        wipe(&mut salt);
+        result?;
+        self.sign_encoded(private_key, &em[..em_len])
+    }
+
+    /// RSASSA-PSS verification (RFC 8017 section 8.1.2) of `signature`
+    /// over the digest `message`.
+    ///
+    /// MGF1 and the `M'` hash use the digest's hash on `hace`. The salt
+    /// length must be the one set with
+    /// [`AspeedRsa::set_pss_verify_salt_len`], if any. Fails with
+    /// `RsaDriverError::VerificationFailed` for any signature that does not
+    /// verify.
+    pub fn verify_pss(
+        &mut self,
+        hace: &mut HaceController,
+        public_key: &RsaPublicKey<'_>,
+        message: &RsaDigest,
+        signature: &RsaSignatureData,
+    ) -> Result<RsaSignatureData, RsaDriverError> {
+        let em_bits = (public_key.m_bits as usize)
+            .checked_sub(1)
+            .ok_or(RsaDriverError::InvalidLength)?;
+        let em_len = em_bits.div_ceil(8);
+        let mut output = [0u8; 512];
+        let len = self.public_op(public_key, signature, &mut output)?;
+        if len > em_len {
+            return Err(RsaDriverError::VerificationFailed);
+        }
+        // Restore the leading zeros dropped by the engine
+        let mut em = [0u8; 512];
+        em[em_len - len..em_len].copy_from_slice(&output[..len]);
+        emsa_pss_verify(
+            hace,
+            &message.data[..message.len],
+            &em[..em_len],
+            em_bits,
+            self.pss_verify_salt_len,
+        )
+        .map_err(|_| RsaDriverError::VerificationFailed)?;
+
+        Ok(RsaSignatureData {
+            data: signature.data,
+            len,
+        })
+    }
+
+    /// Private-key operation on an encoded message, as a signature
+    /// left-padded with zeros to the modulus length
+    fn sign_encoded(
+        &mut self,
+        private_key: &RsaPrivateKey<'_>,
+        em: &[u8],
+    ) -> Result<RsaSignatureData, RsaDriverError> {
+        let m_len = ((private_key.m_bits + 7) / 8) as usize;
+        if m_len > 512 {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let mut output = [0u8; 512];
+        self.private_op(private_key, em, &mut output[..m_len])?;
+        Ok(RsaSignatureData {
+            data: output,
+            len: m_len,
+        })
+    }
+
+    /// `signature ^ e mod n` into `output`, returning its length without
+    /// the leading zeros the engine drops.
+    ///
+    /// RFC 8017 sections 8.1.2 and 8.2.2: the signature must be a
+    /// modulus-length integer below the modulus; big-endian byte strings of
+    /// equal length compare like the integers.
+    fn public_op(
+        &mut self,
+        public_key: &RsaPublicKey<'_>,
+        signature: &RsaSignatureData,
+        output: &mut [u8; 512],
+    ) -> Result<usize, RsaDriverError> {
+        let input_len = signature.len;
+        let e_len = ((public_key.e_bits + 7) / 8) as usize;
+        let m_len = ((public_key.m_bits + 7) / 8) as usize;
+        if input_len != m_len || m_len > signature.data.len() {
+            return Err(RsaDriverError::VerificationFailed);
+        }
+        let input = &signature.data[..input_len];
+        let m = &public_key.m[..m_len];
+        let e = &public_key.e[..e_len];
+        if input >= m {
+            return Err(RsaDriverError::VerificationFailed);
+        }
+
+        self.aspeed_rsa_trigger(input, output, m, e, public_key.m_bits, public_key.e_bits)
+    }
+
+    /// RSADP: `ciphertext ^ d mod n` into the start of `em`, returning the
+    /// modulus length
+    fn decrypt_raw(
+        &mut self,
+        private_key: &RsaPrivateKey<'_>,
+        ciphertext: &[u8],
+        em: &mut [u8; 512],
+    ) -> Result<usize, RsaDriverError> {
+        let m_len = ((private_key.m_bits + 7) / 8) as usize;
+        if m_len > 512 {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let m = &private_key.m[..m_len];
+        // The ciphertext must be a modulus-length integer below the modulus;
+        // big-endian byte strings of equal length compare like the integers
+        if ciphertext.len() != m_len || ciphertext >= m {
+            return Err(RsaDriverError::DecryptionFailed);
+        }
+
+        self.private_op(private_key, ciphertext, &mut em[..m_len])?;
+        Ok(m_len)
+    }
+
+    /// `input ^ d mod n` into all of `out`, which is as long as the modulus.
+    ///
+    /// Always blinded, and computed from the CRT parameters when the key has
+    /// them.
+    fn private_op(
+        &mut self,
+        key: &RsaPrivateKey<'_>,
+        input: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), RsaDriverError> {
+        let m_len = ((key.m_bits + 7) / 8) as usize;
+        let limbs = m_len.div_ceil(4);
+        let m = key.m.get(..m_len).ok_or(RsaDriverError::InvalidLength)?;
+        if m_len > 512 || out.len() != m_len {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        let mut n = Scratch::<MAX_LIMBS>::default();
+        let mut x = Scratch::<MAX_LIMBS>::default();
+        let mut y = Scratch::<MAX_LIMBS>::default();
+        let mut unblind = Scratch::<MAX_LIMBS>::default();
+        let n = &mut n.0[..limbs];
+        let (x, y) = (&mut x.0[..limbs], &mut y.0[..limbs]);
+        let unblind = &mut unblind.0[..limbs];
+        bignum::from_be_bytes(m, n);
+        if !bignum::from_be_bytes(input, x) || bignum::cmp(x, n).is_ge() {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        // Take the random source out of `self` while the engine runs
+        let rng = self.rng.take().ok_or(RsaDriverError::NoBlindingRng)?;
+        let result = self.blind(key, n, x, unblind, &mut *rng);
+        self.rng = Some(rng);
+        result?;
+
+        match &key.crt {
+            Some(crt) => self.exp_crt(key, crt, x, y)?,
+            None => {
+                let d_len = ((key.d_bits + 7) / 8) as usize;
+                let d = key.d.get(..d_len).ok_or(RsaDriverError::InvalidLength)?;
+                self.mod_exp_limbs(x, d, m, y)?;
+            }
+        }
+
+        bignum::mul_mod(y, unblind, n, x);
+        y.copy_from_slice(x);
+        bignum::to_be_bytes(y, out);
+        Ok(())
+    }
+
+    /// Multiply `x` by `r^e mod n` for a random `r`, and set `unblind` to
+    /// `r^-1 mod n`
+    fn blind(
+        &mut self,
+        key: &RsaPrivateKey<'_>,
+        n: &[u32],
+        x: &mut [u32],
+        unblind: &mut [u32],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<(), RsaDriverError> {
+        if bit_len(key.e) == 0 {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let m_len = ((key.m_bits + 7) / 8) as usize;
+        let m = &key.m[..m_len];
+        let top_mask = 0xff >> (m_len * 8 - key.m_bits as usize);
+
+        let mut r = Scratch::<MAX_LIMBS>::default();
+        let mut r_e = Scratch::<MAX_LIMBS>::default();
+        let (r, r_e) = (&mut r.0[..n.len()], &mut r_e.0[..n.len()]);
+        let mut r_bytes = [0u8; 512];
+        let r_bytes = &mut r_bytes[..m_len];
+
+        // Draw r uniformly from [1, n) until it is invertible, which fails
+        // only if r shares a prime factor with n
+        let drawn = loop {
+            if rng.try_fill_bytes(r_bytes).is_err() {
+                break false;
+            }
+            r_bytes[0] &= top_mask;
+            bignum::from_be_bytes(r_bytes, r);
+            if !bignum::is_zero(r) && bignum::cmp(r, n).is_lt() && bignum::inv_mod(r, n, unblind) {
+                break true;
+            }
+        };
+        wipe(r_bytes);
+        if !drawn {
+            return Err(RsaDriverError::HardwareError);
+        }
+
+        self.mod_exp_limbs(r, key.e, m, r_e)?;
+        bignum::mul_mod(x, r_e, n, r);
+        x.copy_from_slice(r);
+        Ok(())
+    }
+
+    /// `y = x ^ d mod n` from the CRT parameters: two half-size
+    /// exponentiations and Garner's recombination. The result is checked
+    /// with the public exponent before it is released, so a faulted
+    /// half cannot leak a factor of `n`.
+    fn exp_crt(
+        &mut self,
+        key: &RsaPrivateKey<'_>,
+        crt: &RsaCrtParams<'_>,
+        x: &[u32],
+        y: &mut [u32],
+    ) -> Result<(), RsaDriverError> {
+        let p_limbs = crt.p.len().div_ceil(4);
+        let q_limbs = crt.q.len().div_ceil(4);
+        if bit_len(key.e) == 0 || p_limbs > MAX_LIMBS || q_limbs > MAX_LIMBS {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        let mut p = Scratch::<MAX_LIMBS>::default();
+        let mut q = Scratch::<MAX_LIMBS>::default();
+        let mut qinv = Scratch::<MAX_LIMBS>::default();
+        let mut m1 = Scratch::<MAX_LIMBS>::default();
+        let mut m2 = Scratch::<MAX_LIMBS>::default();
+        let mut h = Scratch::<MAX_LIMBS>::default();
+        let mut t = Scratch::<{ 2 * MAX_LIMBS }>::default();
+        let (p, qinv) = (&mut p.0[..p_limbs], &mut qinv.0[..p_limbs]);
+        let (m1, h) = (&mut m1.0[..p_limbs], &mut h.0[..p_limbs]);
+        let (q, m2) = (&mut q.0[..q_limbs], &mut m2.0[..q_limbs]);
+        if !bignum::from_be_bytes(crt.p, p)
+            || !bignum::from_be_bytes(crt.q, q)
+            || !bignum::from_be_bytes(crt.qinv, qinv)
+        {
+            return Err(RsaDriverError::InvalidLength);
+        }
+
+        // m1 = (x mod p) ^ dP mod p
+        bignum::rem(x, p, h);
+        self.mod_exp_limbs(h, crt.dp, crt.p, m1)?;
+        // m2 = (x mod q) ^ dQ mod q
+        let c = &mut t.0[..q_limbs];
+        bignum::rem(x, q, c);
+        self.mod_exp_limbs(c, crt.dq, crt.q, m2)?;
+
+        // h = qInv * (m1 - m2) mod p
+        bignum::rem(m2, p, h);
+        if bignum::sub(m1, h) != 0 {
+            bignum::add(m1, p);
+        }
+        bignum::mul_mod(m1, qinv, p, h);
+
+        // y = m2 + h * q, which is below n for a consistent key
+        let hq = &mut t.0[..p_limbs + q_limbs];
+        bignum::mul(h, q, hq);
+        bignum::add(hq, m2);
+        let fits = hq.len() <= y.len() || bignum::is_zero(&hq[y.len()..]);
+        if !fits {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        y.fill(0);
+        let width = y.len().min(hq.len());
+        y[..width].copy_from_slice(&hq[..width]);
+
+        // y ^ e must give back x
+        let m_len = ((key.m_bits + 7) / 8) as usize;
+        let check = &mut t.0[..x.len()];
+        self.mod_exp_limbs(y, key.e, &key.m[..m_len], check)?;
+        if bignum::cmp(check, x).is_ne() {
+            y.fill(0);
+            return Err(RsaDriverError::HardwareError);
+        }
+        Ok(())
+    }
+
+    /// [`AspeedRsa::mod_exp`] on limb operands, with `modulus` big-endian
+    fn mod_exp_limbs(
+        &mut self,
+        base: &[u32],
+        exponent: &[u8],
+        modulus: &[u8],
+        out: &mut [u32],
+    ) -> Result<(), RsaDriverError> {
+        let width = modulus.len();
+        if width > 512 {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let mut base_bytes = [0u8; 512];
+        let mut out_bytes = [0u8; 512];
+        bignum::to_be_bytes(base, &mut base_bytes[..width]);
+        let result = self.mod_exp(
+            &base_bytes[..width],
+            exponent,
+            modulus,
+            &mut out_bytes[..width],
+        );
+        if result.is_ok() {
+            bignum::from_be_bytes(&out_bytes[..width], out);
+        }
+        wipe(&mut base_bytes);
+        wipe(&mut out_bytes);
+        result
+    }
+}
+
+pub(crate) fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Report malformed encodings found while decrypting as a decryption error
+fn decryption_error(err: Pkcs1Error) -> RsaDriverError {
+    match err {
+        Pkcs1Error::Inconsistent => RsaDriverError::DecryptionFailed,
+        other => other.into(),
+    }
+}
+
 impl<D: DelayNs> RsaErrorType for AspeedRsa<'_, D> {
     type Error = RsaDriverError;
 }
@@ -315,28 +1069,22 @@ impl<D: DelayNs> RsaSignature for AspeedRsa<'_, D> {
     type Signature = RsaSignatureData;
 }
 
-impl<D: DelayNs> RsaKeyGen for AspeedRsa<'_, D> {
-    fn generate_keys(_bits: RsaSize) -> Result<(Self::PrivateKey, Self::PublicKey), Self::Error> {
-        // Not supported by hardware
-        Err(RsaDriverError::HardwareError)
-    }
-}
-
 impl<D: DelayNs> RsaSign for AspeedRsa<'_, D> {
     /// Performs RSA signature generation using PKCS#1 v1.5 padding and a private key.
     ///
-    /// This function pads the input message digest using PKCS#1 v1.5, triggers the RSA engine
-    /// with the private key components (modulus and private exponent), and writes the resulting
-    /// signature into the output buffer.
+    /// This function pads the input message digest, runs the private-key operation on the
+    /// engine and writes the resulting signature into the output buffer, left-padded with
+    /// zeros to the modulus length.
     ///
-    /// If the RSA engine returns a signature shorter than the modulus length (e.g. leading zeros
-    /// are omitted), this function will right-align the signature and pad the high bytes with zeros
-    /// to ensure a consistent `m_len`-byte big-endian representation.
+    /// The private-key operation uses the CRT parameters when the key has them, and blinds its
+    /// input with the random source set with [`AspeedRsa::set_blinding_rng`].
     ///
     /// # Parameters
-    /// - `private_key`: RSA private key containing modulus `m`, exponent `d`, and bit lengths
+    /// - `private_key`: RSA private key containing modulus `m`, exponents `e` and `d`, optional
+    ///   CRT parameters, and bit lengths
     /// - `message`: Pre-hashed message (digest) to be signed
-    /// - `_padding_mode`: Currently ignored; only PKCS#1 v1.5 is supported
+    /// - `padding_mode`: PKCS#1 v1.5. PSS hashes on the HACE, which this trait cannot be
+    ///   given; sign with [`AspeedRsa::sign_pss`] instead
     ///
     /// # Returns
     /// A `RsaSignatureData` struct containing the fixed-length signature output
@@ -344,74 +1092,52 @@ impl<D: DelayNs> RsaSign for AspeedRsa<'_, D> {
     ///
     /// # Errors
     /// Returns `RsaDriverError::InvalidLength` if the message or padding is malformed,
-    /// or if the hardware RSA engine fails.
+    /// `RsaDriverError::HardwareError` if the hardware RSA engine fails or a CRT result
+    /// does not check out, `RsaDriverError::NoBlindingRng` if no random source is set, or
+    /// `RsaDriverError::NoHashEngine` for PSS.
     fn sign(
         &mut self,
         private_key: &Self::PrivateKey,
         message: Self::Message,
-        _padding_mode: PaddingMode,
+        padding_mode: PaddingMode,
     ) -> Result<Self::Signature, Self::Error> {
-        let mut output = [0u8; 512];
-
+        if matches!(padding_mode, PaddingMode::Pss) {
+            return Err(RsaDriverError::NoHashEngine);
+        }
         let m_len = ((private_key.m_bits + 7) / 8) as usize;
-        let d_len = ((private_key.d_bits + 7) / 8) as usize;
-        let input_len = message.len;
-
-        let input = &message.data[..input_len];
-        let m = &private_key.m[..m_len];
-        let d = &private_key.d[..d_len];
-
-        let mut padded_input = [0u8; 512];
-        let padded_len = Self::pkcs1_v1_5_pad_inplace(input, &mut padded_input[..m_len])
-            .map_err(|_e| RsaDriverError::InvalidLength)?;
-        let len = self.aspeed_rsa_trigger(
-            &padded_input[..padded_len],
-            &mut output,
-            m,
-            d,
-            private_key.m_bits,
-            private_key.d_bits,
-        )?;
-
-        if len < m_len {
-            // Hardware output is shorter than modulus length.
-            // RSA signatures are represented as big-endian integers,
-            // so if the hardware omits leading zeros (e.g., starts with 0x00),
-            // we must right-align the result and pad the high bytes with 0.
-            //
-            // [    padding    |      actual data     ]
-            // [0 .. m_len-len | m_len-len .. m_len   ]
-            output.copy_within(0..len, m_len - len);
-            output[..m_len - len].fill(0);
+        if m_len > 512 {
+            return Err(RsaDriverError::InvalidLength);
         }
 
-        Ok(RsaSignatureData {
-            data: output,
-            len: m_len,
-        })
+        let mut padded_input = [0u8; 512];
+        let padded_len =
+            Self::pkcs1_v1_5_pad_inplace(&message.data[..message.len], &mut padded_input[..m_len])
+                .map_err(|_e| RsaDriverError::InvalidLength)?;
+        self.sign_encoded(private_key, &padded_input[..padded_len])
     }
 }
 
 impl<D: DelayNs> RsaVerify for AspeedRsa<'_, D> {
-    /// Verifies an RSA signature using the provided public key and digest.
+    /// Verifies an RSA PKCS#1 v1.5 signature using the provided public key and digest.
     ///
     /// This function performs RSA public-key decryption (i.e., modular exponentiation)
     /// on the input signature using the public modulus `m` and exponent `e`, then
-    /// compares the result against the expected digest (`message`).
-    ///
-    /// The verification is successful if the tail end of the decrypted output
-    /// matches the provided digest byte-for-byte. This assumes that the signature
-    /// follows the PKCS#1 v1.5 padding convention.
+    /// rebuilds the full `00 01 FF..FF 00 || DigestInfo || digest` encoding for the
+    /// digest's hash and compares it with the decrypted output in constant time.
     ///
     /// # Arguments
     /// - `public_key`: RSA public key, including modulus and exponent
     /// - `message`: Digest value expected to be embedded in the signature
-    /// - `_padding_mode`: Padding mode placeholder (currently unused)
+    /// - `padding_mode`: PKCS#1 v1.5. PSS hashes on the HACE, which this trait cannot be
+    ///   given; verify with [`AspeedRsa::verify_pss`] instead
     /// - `signature`: The RSA signature to be verified
     ///
     /// # Returns
     /// - `Ok(signature)` if verification succeeds
-    /// - `Err(RsaDriverError::VerificationFailed)` if digest mismatch
+    /// - `Err(RsaDriverError::InvalidPadding)` if the padding or DigestInfo is malformed
+    /// - `Err(RsaDriverError::VerificationFailed)` if digest mismatch, or if the signature is
+    ///   not as long as the modulus or not below it
+    /// - `Err(RsaDriverError::NoHashEngine)` for PSS
     ///
     /// # Notes
     /// - The implementation uses a fixed-size internal buffer (512 bytes) for output.
@@ -419,35 +1145,26 @@ impl<D: DelayNs> RsaVerify for AspeedRsa<'_, D> {
         &mut self,
         public_key: &Self::PublicKey,
         message: Self::Message,
-        _padding_mode: PaddingMode,
+        padding_mode: PaddingMode,
         signature: &Self::Signature,
     ) -> Result<Self::Signature, Self::Error> {
-        let mut output = [0u8; 512];
-
-        let input_len = signature.len;
-        let e_len = ((public_key.e_bits + 7) / 8) as usize;
+        if matches!(padding_mode, PaddingMode::Pss) {
+            return Err(RsaDriverError::NoHashEngine);
+        }
         let m_len = ((public_key.m_bits + 7) / 8) as usize;
-
-        let input = &signature.data[..input_len];
-        let m = &public_key.m[..m_len];
-        let e = &public_key.e[..e_len];
-
-        let len = self.aspeed_rsa_trigger(
-            input,
-            &mut output,
-            m,
-            e,
-            public_key.m_bits,
-            public_key.e_bits,
-        )?;
-
-        if output[len.saturating_sub(message.len)..len] == message.data[..message.len] {
-            Ok(RsaSignatureData {
-                data: signature.data,
-                len,
-            })
-        } else {
-            Err(RsaDriverError::VerificationFailed)
+        let mut output = [0u8; 512];
+        let len = self.public_op(public_key, signature, &mut output)?;
+        if len > m_len {
+            return Err(RsaDriverError::InvalidPadding);
         }
+        // Restore the leading 0x00 dropped by the engine
+        let mut em = [0u8; 512];
+        em[m_len - len..m_len].copy_from_slice(&output[..len]);
+        Self::pkcs1_v1_5_verify(&message.data[..message.len], &em[..m_len])?;
+
+        Ok(RsaSignatureData {
+            data: signature.data,
+            len,
+        })
     }
 }
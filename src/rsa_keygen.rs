@@ -0,0 +1,382 @@
+// Licensed under the Apache-2.0 license
+
+//! RSA key pair generation on the device, so that identity keys never leave
+//! it. Primes are found with trial division and Miller-Rabin tests run on
+//! the RSA engine's modular exponentiation; the remaining arithmetic is done
+//! in software on the limbs of [`crate::bignum`].
+//!
+//! The public exponent is always 65537. `RsaKeyGen` is not implemented, as
+//! its `generate_keys` can be given neither the engine, a random source nor
+//! storage for the keys.
+
+use crate::bignum::{self, Scratch, MAX_LIMBS};
+use crate::common::{bit_len, wipe};
+use crate::rsa::{
+    strip_leading_zeros, AspeedRsa, RsaCrtParams, RsaDriverError, RsaPrivateKey, RsaPublicKey,
+};
+use embedded_hal::delay::DelayNs;
+use rand_core::CryptoRngCore;
+
+/// Public exponent of generated keys
+const E: u32 = 65537;
+const E_BYTES: [u8; 3] = [0x01, 0x00, 0x01];
+
+/// Largest prime, in bytes, of a 4096-bit key
+const MAX_PRIME_LEN: usize = 256;
+
+/// Odd primes below 256, to reject most composite candidates before the
+/// first Miller-Rabin round
+const SMALL_PRIMES: [u32; 53] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251,
+];
+
+/// RSA key pair that owns its key material, as produced by
+/// [`AspeedRsa::generate_key_pair`]. The private values are wiped when it
+/// is dropped.
+pub struct RsaKeyPair {
+    m_len: usize,
+    m: [u8; 512],
+    d: [u8; 512],
+    p: [u8; MAX_PRIME_LEN],
+    q: [u8; MAX_PRIME_LEN],
+    dp: [u8; MAX_PRIME_LEN],
+    dq: [u8; MAX_PRIME_LEN],
+    qinv: [u8; MAX_PRIME_LEN],
+}
+
+impl Default for RsaKeyPair {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for RsaKeyPair {
+    fn drop(&mut self) {
+        for secret in [
+            &mut self.d[..],
+            &mut self.p[..],
+            &mut self.q[..],
+            &mut self.dp[..],
+            &mut self.dq[..],
+            &mut self.qinv[..],
+        ] {
+            wipe(secret);
+        }
+    }
+}
+
+impl RsaKeyPair {
+    /// Empty key pair, to be filled by [`AspeedRsa::generate_key_pair`]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            m_len: 0,
+            m: [0; 512],
+            d: [0; 512],
+            p: [0; MAX_PRIME_LEN],
+            q: [0; MAX_PRIME_LEN],
+            dp: [0; MAX_PRIME_LEN],
+            dq: [0; MAX_PRIME_LEN],
+            qinv: [0; MAX_PRIME_LEN],
+        }
+    }
+
+    /// Modulus size in bits, or 0 before a key has been generated
+    #[must_use]
+    pub fn m_bits(&self) -> u32 {
+        bit_len(&self.m[..self.m_len])
+    }
+
+    #[must_use]
+    pub fn public_key(&self) -> RsaPublicKey<'_> {
+        RsaPublicKey {
+            m: &self.m[..self.m_len],
+            e: &E_BYTES,
+            m_bits: self.m_bits(),
+            e_bits: bit_len(&E_BYTES),
+        }
+    }
+
+    /// The private key, with its CRT parameters
+    #[must_use]
+    pub fn private_key(&self) -> RsaPrivateKey<'_> {
+        let prime_len = self.m_len / 2;
+        let d = strip_leading_zeros(&self.d[..self.m_len]);
+        RsaPrivateKey::with_crt(
+            &self.m[..self.m_len],
+            &E_BYTES,
+            d,
+            self.m_bits(),
+            bit_len(d),
+            RsaCrtParams {
+                p: &self.p[..prime_len],
+                q: &self.q[..prime_len],
+                dp: &self.dp[..prime_len],
+                dq: &self.dq[..prime_len],
+                qinv: &self.qinv[..prime_len],
+            },
+        )
+    }
+}
+
+impl<D: DelayNs> AspeedRsa<'_, D> {
+    /// Generate a `bits`-bit key pair (2048, 3072 or 4096) into `key`,
+    /// drawing every random value from `rng`.
+    ///
+    /// The primes are of equal size with their top two bits set, so the
+    /// modulus has exactly `bits` bits, and differ in their top 100 bits.
+    /// `d` is the inverse of `e` modulo `(p - 1)(q - 1)` and is required to
+    /// exceed `2^(bits / 2)`. Generation takes a few hundred engine
+    /// operations on average, and the time it takes depends on the random
+    /// values drawn.
+    pub fn generate_key_pair(
+        &mut self,
+        bits: u32,
+        rng: &mut dyn CryptoRngCore,
+        key: &mut RsaKeyPair,
+    ) -> Result<(), RsaDriverError> {
+        if !matches!(bits, 2048 | 3072 | 4096) {
+            return Err(RsaDriverError::InvalidLength);
+        }
+        let prime_bits = bits as usize / 2;
+        let prime_len = prime_bits / 8;
+        let limbs = prime_len / 4;
+        // Rounds for an error probability of at most 2^-100, from
+        // FIPS 186-4 table C.3
+        let rounds = if bits >= 3072 { 4 } else { 5 };
+
+        let mut p = Scratch::<{ MAX_LIMBS / 2 }>::default();
+        let mut q = Scratch::<{ MAX_LIMBS / 2 }>::default();
+        let mut p1 = Scratch::<{ MAX_LIMBS / 2 }>::default();
+        let mut q1 = Scratch::<{ MAX_LIMBS / 2 }>::default();
+        let mut n = Scratch::<MAX_LIMBS>::default();
+        let mut phi = Scratch::<MAX_LIMBS>::default();
+        let mut d = Scratch::<MAX_LIMBS>::default();
+        let (p, q) = (&mut p.0[..limbs], &mut q.0[..limbs]);
+        let (p1, q1) = (&mut p1.0[..limbs], &mut q1.0[..limbs]);
+        let (n, phi, d) = (
+            &mut n.0[..2 * limbs],
+            &mut phi.0[..2 * limbs],
+            &mut d.0[..2 * limbs],
+        );
+
+        loop {
+            self.generate_prime(rng, rounds, p)?;
+            // p and q must differ somewhere in their top 100 bits
+            loop {
+                self.generate_prime(rng, rounds, q)?;
+                p1.copy_from_slice(p);
+                if bignum::sub(p1, q) != 0 {
+                    p1.copy_from_slice(q);
+                    bignum::sub(p1, p);
+                }
+                if bignum::bit_len(p1) > prime_bits - 100 {
+                    break;
+                }
+            }
+            // p > q, so that qInv = q^-1 mod p is computed on a reduced q,
+            // which the engine requires of its base
+            if bignum::cmp(p, q).is_lt() {
+                p.swap_with_slice(q);
+            }
+
+            p1.copy_from_slice(p);
+            q1.copy_from_slice(q);
+            bignum::sub(p1, &[1]);
+            bignum::sub(q1, &[1]);
+            bignum::mul(p1, q1, phi);
+            if !inverse_of_e(phi, d) {
+                continue;
+            }
+            // A small d would be open to Wiener-style attacks
+            if bignum::bit_len(d) > prime_bits {
+                break;
+            }
+        }
+
+        bignum::mul(p, q, n);
+        let m_len = 2 * prime_len;
+        bignum::to_be_bytes(n, &mut key.m[..m_len]);
+        bignum::to_be_bytes(d, &mut key.d[..m_len]);
+        bignum::to_be_bytes(p, &mut key.p[..prime_len]);
+        bignum::to_be_bytes(q, &mut key.q[..prime_len]);
+
+        // dP and dQ reuse the limbs of n and phi
+        let (dp, dq) = (&mut n[..limbs], &mut phi[..limbs]);
+        // e does not divide p - 1 or q - 1, which generate_prime ensures
+        if !inverse_of_e(p1, dp) || !inverse_of_e(q1, dq) {
+            return Err(RsaDriverError::HardwareError);
+        }
+        bignum::to_be_bytes(dp, &mut key.dp[..prime_len]);
+        bignum::to_be_bytes(dq, &mut key.dq[..prime_len]);
+
+        // qInv = q^(p - 2) mod p, as p is prime, on the engine rather than
+        // with a variable-time inverse that would leak the primes
+        bignum::sub(p1, &[1]);
+        let mut p2 = [0u8; MAX_PRIME_LEN];
+        let p2 = &mut p2[..prime_len];
+        bignum::to_be_bytes(p1, p2);
+        let result = self.mod_exp(
+            &key.q[..prime_len],
+            p2,
+            &key.p[..prime_len],
+            &mut key.qinv[..prime_len],
+        );
+        wipe(p2);
+        result?;
+        key.m_len = m_len;
+        Ok(())
+    }
+
+    /// Draw random odd candidates with the top two bits set into `w` until
+    /// one passes trial division and `rounds` Miller-Rabin tests, and for
+    /// which `w - 1` is coprime to `e`
+    fn generate_prime(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        rounds: usize,
+        w: &mut [u32],
+    ) -> Result<(), RsaDriverError> {
+        let len = w.len() * 4;
+        let mut bytes = [0u8; MAX_PRIME_LEN];
+        let bytes = &mut bytes[..len];
+
+        let result = loop {
+            if rng.try_fill_bytes(bytes).is_err() {
+                break Err(RsaDriverError::HardwareError);
+            }
+            bytes[0] |= 0xc0;
+            bytes[len - 1] |= 1;
+            bignum::from_be_bytes(bytes, w);
+
+            // w mod e = 1 would leave e without an inverse mod w - 1
+            if bignum::rem_small(w, E) == 1
+                || SMALL_PRIMES.iter().any(|&s| bignum::rem_small(w, s) == 0)
+            {
+                continue;
+            }
+            match self.miller_rabin(rng, rounds, w) {
+                Ok(true) => break Ok(()),
+                Ok(false) => {}
+                Err(err) => break Err(err),
+            }
+        };
+        wipe(bytes);
+        result
+    }
+
+    /// Miller-Rabin test of the odd `w` with `rounds` random bases
+    /// (FIPS 186-4 appendix C.3.1). Returns `false` if `w` is composite.
+    fn miller_rabin(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        rounds: usize,
+        w: &[u32],
+    ) -> Result<bool, RsaDriverError> {
+        let len = w.len() * 4;
+        let mut limbs = Scratch::<{ MAX_LIMBS / 2 }>::default();
+        let mut w_bytes = [0u8; MAX_PRIME_LEN];
+        let mut w1_bytes = [0u8; MAX_PRIME_LEN];
+        let mut m_bytes = [0u8; MAX_PRIME_LEN];
+        let mut b = [0u8; MAX_PRIME_LEN];
+        let mut z = [0u8; MAX_PRIME_LEN];
+        let (w_bytes, w1_bytes) = (&mut w_bytes[..len], &mut w1_bytes[..len]);
+        let (m_bytes, b, z) = (&mut m_bytes[..len], &mut b[..len], &mut z[..len]);
+
+        // w - 1 = 2^a * m with m odd
+        let m = &mut limbs.0[..w.len()];
+        m.copy_from_slice(w);
+        bignum::sub(m, &[1]);
+        bignum::to_be_bytes(w, w_bytes);
+        bignum::to_be_bytes(m, w1_bytes);
+        let mut a = 0;
+        while m[0] & 1 == 0 {
+            bignum::shr1(m, 0);
+            a += 1;
+        }
+        bignum::to_be_bytes(m, m_bytes);
+
+        let is_one = |x: &[u8]| x[..len - 1].iter().all(|&v| v == 0) && x[len - 1] == 1;
+        let mut probably_prime = true;
+        'rounds: for _ in 0..rounds {
+            // Base b with 1 < b < w - 1; big-endian strings of equal length
+            // compare like the integers
+            loop {
+                if rng.try_fill_bytes(b).is_err() {
+                    wipe_all([w_bytes, w1_bytes, m_bytes, b, z]);
+                    return Err(RsaDriverError::HardwareError);
+                }
+                if !is_one(b) && b.iter().any(|&v| v != 0) && b < w1_bytes {
+                    break;
+                }
+            }
+
+            if let Err(err) = self.mod_exp(b, m_bytes, w_bytes, z) {
+                wipe_all([w_bytes, w1_bytes, m_bytes, b, z]);
+                return Err(err);
+            }
+            if is_one(z) || z == w1_bytes {
+                continue;
+            }
+            for _ in 1..a {
+                b.copy_from_slice(z);
+                if let Err(err) = self.mod_exp(b, &[2], w_bytes, z) {
+                    wipe_all([w_bytes, w1_bytes, m_bytes, b, z]);
+                    return Err(err);
+                }
+                if z == w1_bytes {
+                    continue 'rounds;
+                }
+                if is_one(z) {
+                    break;
+                }
+            }
+            probably_prime = false;
+            break;
+        }
+        wipe_all([w_bytes, w1_bytes, m_bytes, b, z]);
+        Ok(probably_prime)
+    }
+}
+
+fn wipe_all<const N: usize>(bufs: [&mut [u8]; N]) {
+    for buf in bufs {
+        wipe(buf);
+    }
+}
+
+/// `out = e^-1 mod m`, with `out` as long as `m`. Returns `false` if `e`
+/// divides `m`.
+///
+/// With `k = -m^-1 mod e`, `1 + k * m` is a multiple of `e` and the
+/// quotient is the inverse; this works for the even moduli RSA needs,
+/// which `bignum::inv_mod` does not take.
+fn inverse_of_e(m: &[u32], out: &mut [u32]) -> bool {
+    let r = bignum::rem_small(m, E);
+    if r == 0 {
+        return false;
+    }
+    // r^-1 = r^(e-2) mod e, as e is prime
+    let mut inv = 1u64;
+    let mut base = u64::from(r);
+    let mut exp = E - 2;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            inv = inv * base % u64::from(E);
+        }
+        base = base * base % u64::from(E);
+        exp >>= 1;
+    }
+    let k = u64::from(E) - inv;
+
+    let mut t = Scratch::<{ MAX_LIMBS + 1 }>::default();
+    let t = &mut t.0[..=m.len()];
+    // k < e fits one limb
+    bignum::mul(m, &[u32::try_from(k).unwrap_or(0)], t);
+    bignum::add(t, &[1]);
+    let rem = bignum::div_small(t, E);
+    out.copy_from_slice(&t[..m.len()]);
+    rem == 0
+}
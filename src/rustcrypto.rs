@@ -0,0 +1,256 @@
+// Licensed under the Apache-2.0 license
+
+//! Adapters exposing the HACE hash engine through the RustCrypto `digest`
+//! traits, so crates written against `digest::Digest` and `digest::Mac` can
+//! hash on the engine.
+//!
+//! Each adapter owns a context slot from the session pool for its whole
+//! lifetime and drives the engine through its own [`HaceController`].
+//! [`HaceDigest::try_new`] and `Mac::new_from_slice` report an exhausted
+//! context pool as an error; the rest of the RustCrypto traits are
+//! infallible, so engine failures there panic.
+
+use crate::hace_controller::{ContextCleanup, HaceController, HashAlgo, HashSession};
+use crate::hash::{self, IntoHashAlgo};
+use ast1060_pac::Hace;
+use core::marker::PhantomData;
+use digest::consts::{U128, U20, U28, U32, U48, U64};
+use digest::crypto_common::{BlockSizeUser, KeySizeUser};
+use digest::generic_array::ArrayLength;
+use digest::{
+    FixedOutput, FixedOutputReset, HashMarker, InvalidLength, Key, KeyInit, MacMarker, Output,
+    OutputSizeUser, Reset, Update,
+};
+
+/// Output and block sizes of a hash, as `typenum` lengths
+pub trait HaceAlgorithm: IntoHashAlgo {
+    type OutputSize: ArrayLength<u8> + 'static;
+    type BlockSize: ArrayLength<u8> + 'static;
+}
+
+impl HaceAlgorithm for hash::Sha1 {
+    type OutputSize = U20;
+    type BlockSize = U64;
+}
+
+impl HaceAlgorithm for hash::Sha224 {
+    type OutputSize = U28;
+    type BlockSize = U64;
+}
+
+impl HaceAlgorithm for hash::Sha256 {
+    type OutputSize = U32;
+    type BlockSize = U64;
+}
+
+impl HaceAlgorithm for hash::Sha384 {
+    type OutputSize = U48;
+    type BlockSize = U128;
+}
+
+impl HaceAlgorithm for hash::Sha512 {
+    type OutputSize = U64;
+    type BlockSize = U128;
+}
+
+impl HaceAlgorithm for hash::Sha512_224 {
+    type OutputSize = U28;
+    type BlockSize = U128;
+}
+
+impl HaceAlgorithm for hash::Sha512_256 {
+    type OutputSize = U32;
+    type BlockSize = U128;
+}
+
+pub type Sha1 = HaceDigest<hash::Sha1>;
+pub type Sha224 = HaceDigest<hash::Sha224>;
+pub type Sha256 = HaceDigest<hash::Sha256>;
+pub type Sha384 = HaceDigest<hash::Sha384>;
+pub type Sha512 = HaceDigest<hash::Sha512>;
+pub type Sha512_224 = HaceDigest<hash::Sha512_224>;
+pub type Sha512_256 = HaceDigest<hash::Sha512_256>;
+
+pub type HmacSha1 = HaceHmac<hash::Sha1>;
+pub type HmacSha224 = HaceHmac<hash::Sha224>;
+pub type HmacSha256 = HaceHmac<hash::Sha256>;
+pub type HmacSha384 = HaceHmac<hash::Sha384>;
+pub type HmacSha512 = HaceHmac<hash::Sha512>;
+pub type HmacSha512_224 = HaceHmac<hash::Sha512_224>;
+pub type HmacSha512_256 = HaceHmac<hash::Sha512_256>;
+
+/// Bind a fresh context slot to a controller on the HACE instance, or
+/// `None` if every slot is in use
+fn open_session(algo: HashAlgo) -> Option<(HaceController<'static>, HashSession)> {
+    let session = HashSession::acquire()?;
+    let mut ctrl = HaceController::new(unsafe { &*Hace::ptr() });
+    ctrl.bind_session(&session);
+    ctrl.algo = algo;
+    Some((ctrl, session))
+}
+
+/// Hash computed on the HACE engine, usable as a `digest::Digest`.
+///
+/// # Panics
+/// Creating one through `Default` or `Digest::new` panics if every hash
+/// context is in use, which [`HaceDigest::try_new`] reports instead;
+/// updating or finalizing panics if the engine reports an error.
+pub struct HaceDigest<A: HaceAlgorithm> {
+    ctrl: HaceController<'static>,
+    _session: HashSession,
+    _algo: PhantomData<A>,
+}
+
+impl<A: HaceAlgorithm> HaceDigest<A> {
+    /// New hash, or `None` if every hash context is in use
+    #[must_use]
+    pub fn try_new() -> Option<Self> {
+        let (mut ctrl, session) = open_session(A::to_hash_algo())?;
+        ctrl.start_digest();
+        Some(Self {
+            ctrl,
+            _session: session,
+            _algo: PhantomData,
+        })
+    }
+
+    fn finish(&mut self, out: &mut [u8]) {
+        self.ctrl.finish_digest().expect("HACE hash failed");
+        out.copy_from_slice(&self.ctrl.ctx_mut().digest[..out.len()]);
+        self.ctrl.cleanup_context();
+    }
+}
+
+impl<A: HaceAlgorithm> Default for HaceDigest<A> {
+    fn default() -> Self {
+        Self::try_new().expect("no free hash context")
+    }
+}
+
+impl<A: HaceAlgorithm> Drop for HaceDigest<A> {
+    fn drop(&mut self) {
+        self.ctrl.cleanup_context();
+        self.ctrl.unbind_session();
+    }
+}
+
+impl<A: HaceAlgorithm> HashMarker for HaceDigest<A> {}
+
+impl<A: HaceAlgorithm> OutputSizeUser for HaceDigest<A> {
+    type OutputSize = A::OutputSize;
+}
+
+impl<A: HaceAlgorithm> BlockSizeUser for HaceDigest<A> {
+    type BlockSize = A::BlockSize;
+}
+
+impl<A: HaceAlgorithm> Update for HaceDigest<A> {
+    fn update(&mut self, data: &[u8]) {
+        self.ctrl.update_sg(&[data]).expect("HACE hash failed");
+    }
+}
+
+impl<A: HaceAlgorithm> FixedOutput for HaceDigest<A> {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        self.finish(out);
+    }
+}
+
+impl<A: HaceAlgorithm> Reset for HaceDigest<A> {
+    fn reset(&mut self) {
+        self.ctrl.cleanup_context();
+        self.ctrl.start_digest();
+    }
+}
+
+impl<A: HaceAlgorithm> FixedOutputReset for HaceDigest<A> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        self.finish(out);
+        self.ctrl.start_digest();
+    }
+}
+
+/// HMAC computed on the HACE engine, usable as a `digest::Mac`.
+///
+/// Keys of any length are accepted through `new_from_slice`. The padded keys
+/// stay in the context slot so the MAC can be reset, and are wiped on drop.
+///
+/// `new_from_slice` fails with `InvalidLength` if every hash context is in
+/// use or the engine cannot set up the key.
+///
+/// # Panics
+/// `KeyInit::new` panics where `new_from_slice` would fail; any other
+/// operation panics if the engine reports an error.
+pub struct HaceHmac<A: HaceAlgorithm> {
+    ctrl: HaceController<'static>,
+    _session: HashSession,
+    _algo: PhantomData<A>,
+}
+
+impl<A: HaceAlgorithm> HaceHmac<A> {
+    fn finish(&mut self, out: &mut [u8]) {
+        self.ctrl.finish_hmac(out).expect("HACE HMAC failed");
+    }
+}
+
+impl<A: HaceAlgorithm> Drop for HaceHmac<A> {
+    fn drop(&mut self) {
+        self.ctrl.wipe_hmac_context();
+        self.ctrl.unbind_session();
+    }
+}
+
+impl<A: HaceAlgorithm> MacMarker for HaceHmac<A> {}
+
+impl<A: HaceAlgorithm> OutputSizeUser for HaceHmac<A> {
+    type OutputSize = A::OutputSize;
+}
+
+impl<A: HaceAlgorithm> KeySizeUser for HaceHmac<A> {
+    type KeySize = A::BlockSize;
+}
+
+impl<A: HaceAlgorithm> KeyInit for HaceHmac<A> {
+    fn new(key: &Key<Self>) -> Self {
+        Self::new_from_slice(key).expect("HACE HMAC setup failed")
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        let (ctrl, session) = open_session(A::to_hash_algo()).ok_or(InvalidLength)?;
+        let mut mac = Self {
+            ctrl,
+            _session: session,
+            _algo: PhantomData,
+        };
+        // On failure, dropping `mac` wipes what was loaded of the key
+        mac.ctrl.load_hmac_key(key).map_err(|_| InvalidLength)?;
+        mac.ctrl.start_hmac();
+        Ok(mac)
+    }
+}
+
+impl<A: HaceAlgorithm> Update for HaceHmac<A> {
+    fn update(&mut self, data: &[u8]) {
+        self.ctrl.update_sg(&[data]).expect("HACE HMAC failed");
+    }
+}
+
+impl<A: HaceAlgorithm> FixedOutput for HaceHmac<A> {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        self.finish(out);
+    }
+}
+
+impl<A: HaceAlgorithm> Reset for HaceHmac<A> {
+    fn reset(&mut self) {
+        self.ctrl.cleanup_context();
+        self.ctrl.start_hmac();
+    }
+}
+
+impl<A: HaceAlgorithm> FixedOutputReset for HaceHmac<A> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        self.finish(out);
+        self.ctrl.start_hmac();
+    }
+}
@@ -0,0 +1,196 @@
+// Licensed under the Apache-2.0 license
+
+//! SEC 1 encodings of ECDSA public keys and signatures: compressed and
+//! uncompressed points (section 2.3.3), and signatures as the DER
+//! `ECDSA-Sig-Value` used in certificates or as fixed-width `r || s` as in
+//! COSE and SPDM.
+//!
+//! Decoding validates what it returns: points must lie on the curve and
+//! signature scalars in `[1, n - 1]`. The curve is chosen by the scalar
+//! type, as for [`crate::ecdsa`].
+
+use crate::der::{parse_single, DerError, Reader, TAG_INTEGER, TAG_SEQUENCE};
+use crate::ecc::{params_for_len, EcGroup};
+use crate::ecdsa::{PublicKey, Signature};
+
+/// Leading byte of an uncompressed point
+const POINT_UNCOMPRESSED: u8 = 0x04;
+/// Leading byte of a compressed point with an even y
+const POINT_EVEN_Y: u8 = 0x02;
+/// Leading byte of a compressed point with an odd y
+const POINT_ODD_Y: u8 = 0x03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sec1Error {
+    /// The scalar size matches neither P-256 nor P-384
+    UnsupportedCurve,
+    /// The encoding has the wrong length or leading byte
+    InvalidEncoding,
+    /// The point is not on the curve
+    NotOnCurve,
+    /// `r` or `s` is not in `[1, n - 1]`
+    OutOfRange,
+    /// The output buffer cannot hold the encoding
+    BufferTooSmall,
+    /// The DER signature is malformed
+    Der(DerError),
+}
+
+impl From<DerError> for Sec1Error {
+    fn from(err: DerError) -> Self {
+        Sec1Error::Der(err)
+    }
+}
+
+fn group_for(len: usize) -> Result<EcGroup, Sec1Error> {
+    params_for_len(len)
+        .map(EcGroup::new)
+        .ok_or(Sec1Error::UnsupportedCurve)
+}
+
+impl<S> PublicKey<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]> + Default,
+{
+    /// Decode a compressed (`02 || x` or `03 || x`) or uncompressed
+    /// (`04 || x || y`) point and check that it is on the curve. The
+    /// single-byte encoding of the point at infinity is refused.
+    pub fn from_sec1(bytes: &[u8]) -> Result<Self, Sec1Error> {
+        let mut qx = S::default();
+        let mut qy = S::default();
+        let len = qx.as_ref().len();
+        let group = group_for(len)?;
+
+        match bytes.split_first() {
+            Some((&POINT_UNCOMPRESSED, point)) if point.len() == 2 * len => {
+                qx.as_mut().copy_from_slice(&point[..len]);
+                qy.as_mut().copy_from_slice(&point[len..]);
+                if !group.is_on_curve(qx.as_ref(), qy.as_ref()) {
+                    return Err(Sec1Error::NotOnCurve);
+                }
+            }
+            Some((&tag @ (POINT_EVEN_Y | POINT_ODD_Y), x)) if x.len() == len => {
+                qx.as_mut().copy_from_slice(x);
+                if !group.decompress(x, tag == POINT_ODD_Y, qy.as_mut()) {
+                    return Err(Sec1Error::NotOnCurve);
+                }
+            }
+            _ => return Err(Sec1Error::InvalidEncoding),
+        }
+        Ok(PublicKey { qx, qy })
+    }
+
+    /// Write `04 || x || y` to `out`, returning its length
+    pub fn to_sec1_uncompressed(&self, out: &mut [u8]) -> Result<usize, Sec1Error> {
+        let len = self.qx.as_ref().len();
+        let out = out.get_mut(..=2 * len).ok_or(Sec1Error::BufferTooSmall)?;
+        out[0] = POINT_UNCOMPRESSED;
+        out[1..=len].copy_from_slice(self.qx.as_ref());
+        out[len + 1..].copy_from_slice(self.qy.as_ref());
+        Ok(out.len())
+    }
+
+    /// Write `02 || x` or `03 || x` to `out` by the parity of y, returning
+    /// its length
+    pub fn to_sec1_compressed(&self, out: &mut [u8]) -> Result<usize, Sec1Error> {
+        let len = self.qx.as_ref().len();
+        let out = out.get_mut(..=len).ok_or(Sec1Error::BufferTooSmall)?;
+        let y_odd = self.qy.as_ref().last().is_some_and(|b| b & 1 == 1);
+        out[0] = if y_odd { POINT_ODD_Y } else { POINT_EVEN_Y };
+        out[1..].copy_from_slice(self.qx.as_ref());
+        Ok(out.len())
+    }
+}
+
+/// Copy a big-endian integer into a scalar, left-padding with zeros
+fn scalar_from_be<S: AsMut<[u8]> + Default>(bytes: &[u8]) -> Result<S, Sec1Error> {
+    let mut scalar = S::default();
+    let buf = scalar.as_mut();
+    let start = buf
+        .len()
+        .checked_sub(bytes.len())
+        .ok_or(Sec1Error::OutOfRange)?;
+    buf[start..].copy_from_slice(bytes);
+    Ok(scalar)
+}
+
+/// Write a non-negative DER INTEGER holding the big-endian `value` to the
+/// front of `out`, returning its length. Values are at most 48 bytes, so
+/// the length always fits the short form.
+fn write_unsigned(value: &[u8], out: &mut [u8]) -> Result<usize, Sec1Error> {
+    let first = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    let magnitude = &value[first..];
+    // Zero is a single 00 byte; a set top bit needs a 00 to stay positive
+    let pad = magnitude.first().map_or(true, |&b| b & 0x80 != 0);
+    let content_len = magnitude.len() + usize::from(pad);
+    let out = out
+        .get_mut(..2 + content_len)
+        .ok_or(Sec1Error::BufferTooSmall)?;
+    out[0] = TAG_INTEGER;
+    out[1] = u8::try_from(content_len).map_err(|_| Sec1Error::InvalidEncoding)?;
+    out[2] = 0;
+    out[2 + usize::from(pad)..].copy_from_slice(magnitude);
+    Ok(out.len())
+}
+
+impl<S> Signature<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]> + Default,
+{
+    /// Check that `r` and `s` are both in `[1, n - 1]`
+    pub fn check_range(&self) -> Result<(), Sec1Error> {
+        let group = group_for(self.r.as_ref().len())?;
+        if group.is_valid_scalar(self.r.as_ref()) && group.is_valid_scalar(self.s.as_ref()) {
+            Ok(())
+        } else {
+            Err(Sec1Error::OutOfRange)
+        }
+    }
+
+    /// Decode a fixed-width big-endian `r || s` of exactly twice the
+    /// scalar size
+    pub fn from_fixed(bytes: &[u8]) -> Result<Self, Sec1Error> {
+        let mut r = S::default();
+        let mut s = S::default();
+        let len = r.as_ref().len();
+        if bytes.len() != 2 * len {
+            return Err(Sec1Error::InvalidEncoding);
+        }
+        r.as_mut().copy_from_slice(&bytes[..len]);
+        s.as_mut().copy_from_slice(&bytes[len..]);
+        let signature = Signature { r, s };
+        signature.check_range()?;
+        Ok(signature)
+    }
+
+    /// Decode a DER `ECDSA-Sig-Value ::= SEQUENCE { r INTEGER, s INTEGER }`
+    pub fn from_der(der: &[u8]) -> Result<Self, Sec1Error> {
+        let mut seq = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+        let r = seq.read_unsigned()?;
+        let s = seq.read_unsigned()?;
+        seq.finish()?;
+
+        let signature = Signature {
+            r: scalar_from_be(r)?,
+            s: scalar_from_be(s)?,
+        };
+        signature.check_range()?;
+        Ok(signature)
+    }
+
+    /// Write the DER `ECDSA-Sig-Value` to `out`, returning its length
+    pub fn to_der(&self, out: &mut [u8]) -> Result<usize, Sec1Error> {
+        // Two INTEGERs of at most 51 bytes each keep the SEQUENCE length in
+        // the short form
+        let body = out.get_mut(2..).ok_or(Sec1Error::BufferTooSmall)?;
+        let r_len = write_unsigned(self.r.as_ref(), body)?;
+        let s_len = write_unsigned(self.s.as_ref(), &mut body[r_len..])?;
+        let body_len = r_len + s_len;
+        out[0] = TAG_SEQUENCE;
+        out[1] = u8::try_from(body_len)
+            .ok()
+            .filter(|&len| len < 0x80)
+            .ok_or(Sec1Error::InvalidEncoding)?;
+        Ok(2 + body_len)
+    }
+}
@@ -0,0 +1,167 @@
+// Licensed under the Apache-2.0 license
+
+//! Arbitration of the secure-boot engines, which share one SRAM window and
+//! one set of `Secure` control registers: RSA operands sit at the bottom of
+//! the window and the ECC operands and instruction word above them.
+//!
+//! [`SecureEngine`] owns the `Secure` peripheral and the engine clock. It
+//! hands out one [`RsaSession`] or [`EccSession`] at a time, which the
+//! borrow checker enforces, and resets the engines and zeroes the SRAM when
+//! a session opens and when it ends. Drivers created directly with
+//! [`AspeedRsa::new`] or [`AspeedEcdsa::new`] bypass the sessions, so every
+//! engine operation also takes a runtime lock and fails as busy instead of
+//! running over another one in flight.
+
+use crate::ecdsa::{AspeedEcdsa, SEC_SRAM_CTRL, SEC_SRAM_CTRL_LOCKED, SEC_SRAM_CTRL_WRITE};
+use crate::rsa::AspeedRsa;
+use crate::syscon::{ClockId, Error as SysConError, SysCon};
+use ast1060_pac::Secure;
+use core::ops::{Deref, DerefMut};
+use core::ptr::write_volatile;
+use core::sync::atomic::{AtomicBool, Ordering};
+use embedded_hal::delay::DelayNs;
+
+const SECURE_SRAM_BASE: usize = 0x7900_0000;
+/// The RSA operands and the ECC operands and instruction word
+const SECURE_SRAM_SIZE: usize = 0x2400;
+
+/// Set while an RSA or ECC operation is running on the engine
+static ENGINE_BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Exclusive use of the engine for one operation, released on drop
+pub(crate) struct EngineLock(());
+
+impl EngineLock {
+    /// Take the engine, or `None` if another operation holds it
+    pub(crate) fn acquire() -> Option<Self> {
+        ENGINE_BUSY
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| Self(()))
+    }
+}
+
+impl Drop for EngineLock {
+    fn drop(&mut self) {
+        ENGINE_BUSY.store(false, Ordering::Release);
+    }
+}
+
+/// Write a `Secure` register that the PAC has no accessor for
+fn sec_wr(offset: usize, val: u32) {
+    unsafe {
+        write_volatile(Secure::ptr().cast::<u32>().cast_mut().add(offset / 4), val);
+    }
+}
+
+/// Put both engines back in reset and zero the shared SRAM
+fn scrub(secure: &Secure) {
+    unsafe {
+        secure.secure0b4().write(|w| w.bits(0));
+    }
+    sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_WRITE);
+    for offset in (0..SECURE_SRAM_SIZE).step_by(4) {
+        unsafe { write_volatile((SECURE_SRAM_BASE + offset) as *mut u32, 0) };
+    }
+    sec_wr(SEC_SRAM_CTRL, SEC_SRAM_CTRL_LOCKED);
+}
+
+/// Owner of the secure-boot RSA and ECC engines
+pub struct SecureEngine<D: DelayNs + Clone> {
+    secure: Secure,
+    delay: D,
+}
+
+impl<D: DelayNs + Clone> SecureEngine<D> {
+    /// Take the `Secure` peripheral and ungate the engine clock. A clock
+    /// that is already running is left as it is.
+    pub fn new<SD: DelayNs>(
+        secure: Secure,
+        syscon: &mut SysCon<SD>,
+        delay: D,
+    ) -> Result<Self, SysConError> {
+        match syscon.enable_clock(ClockId::ClkRSACLK as u8) {
+            Ok(()) | Err(SysConError::ClockAlreadyEnabled) => {}
+            Err(err) => return Err(err),
+        }
+        scrub(&secure);
+        Ok(Self { secure, delay })
+    }
+
+    /// Open an RSA session. The engine stays borrowed until it is dropped.
+    pub fn rsa(&mut self) -> RsaSession<'_, D> {
+        scrub(&self.secure);
+        RsaSession {
+            rsa: AspeedRsa::new(&self.secure, self.delay.clone()),
+        }
+    }
+
+    /// Open an ECC session. The engine stays borrowed until it is dropped.
+    pub fn ecc(&mut self) -> EccSession<'_, D> {
+        scrub(&self.secure);
+        EccSession {
+            secure: &self.secure,
+            ecdsa: AspeedEcdsa::new(&self.secure, self.delay.clone()),
+        }
+    }
+
+    /// Zero the SRAM, gate the engine clock and give the peripheral back
+    pub fn release<SD: DelayNs>(self, syscon: &mut SysCon<SD>) -> Result<Secure, SysConError> {
+        scrub(&self.secure);
+        match syscon.disable_clock(ClockId::ClkRSACLK as u8) {
+            Ok(()) | Err(SysConError::ClockAlreadyDisabled) => Ok(self.secure),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Exclusive use of the RSA engine; the SRAM is zeroed on drop
+pub struct RsaSession<'s, D: DelayNs> {
+    rsa: AspeedRsa<'s, D>,
+}
+
+impl<'s, D: DelayNs> Deref for RsaSession<'s, D> {
+    type Target = AspeedRsa<'s, D>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rsa
+    }
+}
+
+impl<D: DelayNs> DerefMut for RsaSession<'_, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rsa
+    }
+}
+
+impl<D: DelayNs> Drop for RsaSession<'_, D> {
+    fn drop(&mut self) {
+        scrub(self.rsa.secure);
+    }
+}
+
+/// Exclusive use of the ECC engine; the SRAM is zeroed on drop
+pub struct EccSession<'s, D: DelayNs> {
+    secure: &'s Secure,
+    ecdsa: AspeedEcdsa<'s, D>,
+}
+
+impl<'s, D: DelayNs> Deref for EccSession<'s, D> {
+    type Target = AspeedEcdsa<'s, D>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ecdsa
+    }
+}
+
+impl<D: DelayNs> DerefMut for EccSession<'_, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.ecdsa
+    }
+}
+
+impl<D: DelayNs> Drop for EccSession<'_, D> {
+    fn drop(&mut self) {
+        scrub(self.secure);
+    }
+}
@@ -0,0 +1,174 @@
+// Licensed under the Apache-2.0 license
+
+//! Measure SPI flash contents with the HACE hash engine.
+//!
+//! The flash is read through the controller's memory-mapped decode window,
+//! so the chip select has to be in normal (direct) read mode. By default the
+//! engine fetches the data from the window itself; when it cannot master the
+//! window, [`hash_flash_region_buffered`] copies the region through a small
+//! bounce buffer in non-cacheable RAM instead.
+
+use super::{SpiBusWithCs, SpiError};
+use crate::hace_controller::{HaceController, HaceError, HACE_DMA_ALIGN};
+use crate::hash::{HashError, IntoHashAlgo};
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+use proposed_traits::digest::{DigestAlgorithm, DigestInit, DigestOp};
+
+/// Largest amount of flash handed to the engine in one pass, keeping each
+/// pass well inside the controller's timeout budget
+pub const FLASH_HASH_CHUNK: usize = 256 * 1024;
+
+/// Size of the bounce buffer used by [`hash_flash_region_buffered`]
+pub const FLASH_BOUNCE_SIZE: usize = 4096;
+
+const DMA_ALIGN: usize = HACE_DMA_ALIGN as usize;
+
+#[derive(Debug)]
+pub enum FlashHashError {
+    /// The chip select has no decode window
+    Spi(SpiError),
+    /// `offset + len` does not fit inside the decode window
+    OutOfRange,
+    /// Failure hashing the data
+    Hash(HashError),
+}
+
+impl From<SpiError> for FlashHashError {
+    fn from(err: SpiError) -> Self {
+        FlashHashError::Spi(err)
+    }
+}
+
+impl From<HashError> for FlashHashError {
+    fn from(err: HashError) -> Self {
+        FlashHashError::Hash(err)
+    }
+}
+
+#[repr(C, align(64))]
+struct BounceBuffer(UnsafeCell<[u8; FLASH_BOUNCE_SIZE]>);
+
+unsafe impl Sync for BounceBuffer {}
+
+#[link_section = ".ram_nc"]
+static FLASH_BOUNCE_BUFFER: BounceBuffer = BounceBuffer(UnsafeCell::new([0; FLASH_BOUNCE_SIZE]));
+
+/// Set while a buffered measurement owns the bounce buffer
+static FLASH_BOUNCE_BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Address of `offset` in the decode window of `cs`, after checking that
+/// `len` bytes from there are mapped
+fn window_addr<S: SpiBusWithCs>(
+    spi: &mut S,
+    cs: usize,
+    offset: u32,
+    len: u32,
+) -> Result<usize, FlashHashError> {
+    let window = spi.get_decode_addr(cs)?;
+    let end = offset.checked_add(len).ok_or(FlashHashError::OutOfRange)?;
+    if end > window.len {
+        return Err(FlashHashError::OutOfRange);
+    }
+    window
+        .start
+        .checked_add(offset)
+        .map(|addr| addr as usize)
+        .ok_or(FlashHashError::OutOfRange)
+}
+
+/// Hash `len` bytes of the flash behind `cs`, starting at `offset`.
+///
+/// The engine reads the flash straight from the decode window in passes of
+/// at most [`FLASH_HASH_CHUNK`] bytes. Bytes before the first
+/// `HACE_DMA_ALIGN` boundary are copied by the CPU so every pass starts on
+/// an address the engine can fetch from.
+pub fn hash_flash_region<A, S>(
+    hace: &mut HaceController,
+    spi: &mut S,
+    cs: usize,
+    offset: u32,
+    len: u32,
+) -> Result<A::DigestOutput, FlashHashError>
+where
+    A: DigestAlgorithm + IntoHashAlgo + Default,
+    A::DigestOutput: Default + AsMut<[u8]>,
+    S: SpiBusWithCs,
+{
+    let addr = window_addr(spi, cs, offset, len)?;
+    let len = len as usize;
+    let head_len = ((DMA_ALIGN - addr % DMA_ALIGN) % DMA_ALIGN).min(len);
+
+    let mut op = hace.init(A::default())?;
+
+    let mut head = [0u8; DMA_ALIGN];
+    unsafe {
+        core::ptr::copy_nonoverlapping(addr as *const u8, head.as_mut_ptr(), head_len);
+    }
+    op.update(&head[..head_len])?;
+
+    let body =
+        unsafe { core::slice::from_raw_parts((addr + head_len) as *const u8, len - head_len) };
+    for chunk in body.chunks(FLASH_HASH_CHUNK) {
+        op.update(chunk)?;
+    }
+
+    Ok(op.finalize()?)
+}
+
+/// Hash `len` bytes of the flash behind `cs` like [`hash_flash_region`],
+/// copying the data through a [`FLASH_BOUNCE_SIZE`] byte buffer in RAM.
+///
+/// For windows the engine cannot read directly. Only one buffered
+/// measurement can run at a time; a concurrent call fails with
+/// `HaceError::Busy`.
+pub fn hash_flash_region_buffered<A, S>(
+    hace: &mut HaceController,
+    spi: &mut S,
+    cs: usize,
+    offset: u32,
+    len: u32,
+) -> Result<A::DigestOutput, FlashHashError>
+where
+    A: DigestAlgorithm + IntoHashAlgo + Default,
+    A::DigestOutput: Default + AsMut<[u8]>,
+    S: SpiBusWithCs,
+{
+    let addr = window_addr(spi, cs, offset, len)?;
+
+    if FLASH_BOUNCE_BUSY
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        return Err(HashError::from(HaceError::Busy).into());
+    }
+
+    let result = hash_through_bounce::<A>(hace, addr, len as usize);
+    FLASH_BOUNCE_BUSY.store(false, Ordering::Release);
+    result
+}
+
+fn hash_through_bounce<A>(
+    hace: &mut HaceController,
+    addr: usize,
+    len: usize,
+) -> Result<A::DigestOutput, FlashHashError>
+where
+    A: DigestAlgorithm + IntoHashAlgo + Default,
+    A::DigestOutput: Default + AsMut<[u8]>,
+{
+    let bounce = unsafe { &mut *FLASH_BOUNCE_BUFFER.0.get() };
+    let mut op = hace.init(A::default())?;
+
+    let mut done = 0;
+    while done < len {
+        let n = (len - done).min(FLASH_BOUNCE_SIZE);
+        unsafe {
+            core::ptr::copy_nonoverlapping((addr + done) as *const u8, bounce.as_mut_ptr(), n);
+        }
+        op.update(&bounce[..n])?;
+        done += n;
+    }
+
+    Ok(op.finalize()?)
+}
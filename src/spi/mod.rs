@@ -11,6 +11,7 @@ use embedded_hal::spi::SpiBus;
 use embedded_io::Write;
 
 pub mod device;
+pub mod flashhash;
 pub mod fmccontroller;
 pub mod norflash;
 pub mod norflashblockdevice;
@@ -57,6 +58,8 @@ pub trait SpiBusWithCs: SpiBus<u8, Error = SpiError> + ErrorType<Error = SpiErro
 
     fn get_device_info(&mut self, cs: usize) -> (u32, u32);
     fn get_master_id(&mut self) -> u32;
+    /// AHB decode window of the flash behind `cs`
+    fn get_decode_addr(&mut self, cs: usize) -> Result<SpiDecodeAddress, SpiError>;
 }
 
 // Constants (unchanged)
@@ -3,9 +3,9 @@
 use super::{
     aspeed_get_spi_freq_div, get_addr_buswidth, get_hclock_rate, get_mid_point_of_longest_one,
     spi_cal_dummy_cycle, spi_calibration_enable, spi_io_mode, spi_io_mode_user, spi_read_data,
-    spi_write_data, CtrlType, SpiBusWithCs, SpiConfig, SpiData, SpiError, Write, ASPEED_MAX_CS,
-    ASPEED_SPI_NORMAL_READ, ASPEED_SPI_NORMAL_WRITE, ASPEED_SPI_SZ_256M, ASPEED_SPI_SZ_2M,
-    ASPEED_SPI_USER, ASPEED_SPI_USER_INACTIVE, SPI_CALIB_LEN, SPI_CTRL_FREQ_MASK,
+    spi_write_data, CtrlType, SpiBusWithCs, SpiConfig, SpiData, SpiDecodeAddress, SpiError, Write,
+    ASPEED_MAX_CS, ASPEED_SPI_NORMAL_READ, ASPEED_SPI_NORMAL_WRITE, ASPEED_SPI_SZ_256M,
+    ASPEED_SPI_SZ_2M, ASPEED_SPI_USER, ASPEED_SPI_USER_INACTIVE, SPI_CALIB_LEN, SPI_CTRL_FREQ_MASK,
     SPI_DMA_CALC_CKSUM, SPI_DMA_CALIB_MODE, SPI_DMA_DISCARD_REQ_MAGIC, SPI_DMA_ENABLE,
     SPI_DMA_FLASH_MAP_BASE, SPI_DMA_GET_REQ_MAGIC, SPI_DMA_GRANT, SPI_DMA_RAM_MAP_BASE,
     SPI_DMA_REQUEST, SPI_DMA_STATUS, SPI_DMA_TIMEOUT,
@@ -902,4 +902,15 @@ impl<'a> SpiBusWithCs for SpiController<'a> {
     fn get_master_id(&mut self) -> u32 {
         self.spi_config.master_idx
     }
+
+    fn get_decode_addr(&mut self, cs: usize) -> Result<SpiDecodeAddress, SpiError> {
+        if cs > self.spi_config.max_cs {
+            return Err(SpiError::CsSelectFailed(cs));
+        }
+        self.spi_data
+            .decode_addr
+            .get(cs)
+            .copied()
+            .ok_or(SpiError::CsSelectFailed(cs))
+    }
 }
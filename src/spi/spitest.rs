@@ -1,15 +1,18 @@
 // Licensed under the Apache-2.0 license
 
 use super::device::ChipSelectDevice;
+use super::flashhash::{hash_flash_region, hash_flash_region_buffered};
 use super::fmccontroller::FmcController;
 use super::norflash::{
     Jesd216Mode, SpiNorData, SpiNorDevice, SPI_NOR_CMD_QREAD, SPI_NOR_CMD_READ_FAST_4B,
 };
 use super::{
-    norflash, CommandMode, CtrlType, SpiConfig, SpiData, SpiDecodeAddress,
+    norflash, CommandMode, CtrlType, SpiBusWithCs, SpiConfig, SpiData, SpiDecodeAddress,
     SPI_NOR_DATA_DIRECT_READ, SPI_NOR_DATA_DIRECT_WRITE,
 };
 use crate::common::{DmaBuffer, DummyDelay};
+use crate::hace_controller::HaceController;
+use crate::hash::Sha384;
 use crate::spi::norflashblockdevice;
 use crate::spi::norflashblockdevice::{BlockAddrUsize, NorFlashBlockDevice};
 use crate::spi::spicontroller::SpiController;
@@ -22,6 +25,7 @@ use embedded_hal::delay::DelayNs;
 use embedded_hal::spi::SpiDevice;
 use embedded_io::Write;
 use proposed_traits::block_device::{BlockDevice, BlockRange};
+use proposed_traits::digest::{DigestInit, DigestOp};
 
 pub const FMC_CTRL_BASE: usize = 0x7e62_0000;
 pub const FMC_MMAP_BASE: usize = 0x8000_0000;
@@ -385,9 +389,53 @@ pub fn test_fmc(uart: &mut UartController<'_>) {
         TEST_DATA_SIZE,
         true,
     );
+    test_flash_hash(uart, &mut controller, 1, DeviceId::FmcCs1Idx);
     test_log!(uart, "################# FMC test done ! ###############");
 }
 
+/// Hash a flash region through the decode window, directly and through the
+/// bounce buffer, and compare with hashing a RAM copy of the same bytes.
+pub fn test_flash_hash<S: SpiBusWithCs>(
+    uart: &mut UartController<'_>,
+    bus: &mut S,
+    cs: usize,
+    dev_id: DeviceId,
+) {
+    test_log!(uart, "############# Flash hash test ############");
+    let peripherals = unsafe { Peripherals::steal() };
+    let mut hace = HaceController::new(&peripherals.hace);
+    let (_, mmap_base, _) = device_info(dev_id);
+
+    // An unaligned offset exercises the CPU-copied head
+    for offset in [0x0u32, 0x3] {
+        let len = SPI_NC_BUFFER_SIZE - 5;
+        let mut copy = [0u8; SPI_NC_BUFFER_SIZE];
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                (mmap_base + offset as usize) as *const u8,
+                copy.as_mut_ptr(),
+                len,
+            );
+        }
+        let expected = {
+            let mut op = hace.init(Sha384).unwrap();
+            op.update(&copy[..len]).unwrap();
+            op.finalize().unwrap()
+        };
+
+        let len = u32::try_from(len).unwrap();
+        let direct = hash_flash_region::<Sha384, _>(&mut hace, bus, cs, offset, len);
+        let buffered = hash_flash_region_buffered::<Sha384, _>(&mut hace, bus, cs, offset, len);
+        let passed = matches!(&direct, Ok(d) if d.0 == expected.0)
+            && matches!(&buffered, Ok(d) if d.0 == expected.0);
+        if passed {
+            test_log!(uart, "offset 0x{:x}: flash hash passed", offset);
+        } else {
+            test_log!(uart, "offset 0x{:x}: flash hash failed", offset);
+        }
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 pub fn test_spi(uart: &mut UartController<'_>) {
     let spi0 = unsafe { &*ast1060_pac::Spi::ptr() };
@@ -0,0 +1,126 @@
+// Licensed under the Apache-2.0 license
+
+use crate::crypto::{AesController, AesMode, CryptoError, Direction, AES_GCM_TAG_SIZE};
+use crate::tests::functional::crypto_test_vec::{GCM_AES_TV, SP800_38A_AES_TV};
+use crate::tests::functional::report_output;
+use crate::uart::UartController;
+use embedded_io::Write;
+
+fn key_bits(key: &[u8]) -> usize {
+    key.len() * 8
+}
+
+/// Run the NIST SP 800-38A and GCM known-answer tests in both directions
+pub fn run_aes_tests(uart: &mut UartController, aes: &mut AesController) {
+    writeln!(uart, "\r\nRunning AES tests...").unwrap();
+
+    let mut output = [0u8; 64];
+    for vec in SP800_38A_AES_TV {
+        let len = vec.plaintext.len();
+        let bits = key_bits(vec.key);
+        writeln!(uart, "\r\nAES-{bits}-{:?}:", vec.mode).unwrap();
+
+        aes.encrypt(vec.key, vec.mode, vec.iv, vec.plaintext, &mut output)
+            .unwrap();
+        report_output(uart, "encrypt", &output[..len], vec.ciphertext);
+
+        aes.decrypt(vec.key, vec.mode, vec.iv, vec.ciphertext, &mut output)
+            .unwrap();
+        report_output(uart, "decrypt", &output[..len], vec.plaintext);
+    }
+
+    for (i, vec) in GCM_AES_TV.iter().enumerate() {
+        let len = vec.plaintext.len();
+        let bits = key_bits(vec.key);
+        writeln!(uart, "\r\nAES-{bits}-GCM vector {i}:").unwrap();
+
+        let mut tag = [0u8; AES_GCM_TAG_SIZE];
+        aes.gcm_encrypt(
+            vec.key,
+            vec.iv,
+            vec.aad,
+            vec.plaintext,
+            &mut output,
+            &mut tag,
+        )
+        .unwrap();
+        report_output(uart, "encrypt", &output[..len], vec.ciphertext);
+        report_output(uart, "tag", &tag, vec.tag);
+
+        let decrypted = aes.gcm_decrypt(
+            vec.key,
+            vec.iv,
+            vec.aad,
+            vec.ciphertext,
+            &mut output,
+            vec.tag,
+        );
+        if decrypted.is_ok() {
+            report_output(uart, "decrypt", &output[..len], vec.plaintext);
+        } else {
+            writeln!(uart, "\r\ndecrypt: tag rejected, Test failed!").unwrap();
+        }
+    }
+}
+
+/// Feed the same data through the streaming API in uneven pieces and check
+/// that forged GCM tags are rejected.
+pub fn run_aes_streaming_tests(uart: &mut UartController, aes: &mut AesController) {
+    writeln!(uart, "\r\nRunning AES streaming tests...").unwrap();
+
+    let cbc = &SP800_38A_AES_TV[3];
+    let mut output = [0u8; 64];
+    {
+        let mut op = aes
+            .init(cbc.key, AesMode::Cbc, Direction::Encrypt, cbc.iv)
+            .unwrap();
+        op.update(&cbc.plaintext[..16], &mut output[..16]).unwrap();
+        op.update(&cbc.plaintext[16..48], &mut output[16..48])
+            .unwrap();
+        op.update(&cbc.plaintext[48..], &mut output[48..]).unwrap();
+    }
+    report_output(uart, "CBC in pieces", &output, cbc.ciphertext);
+
+    let ctr = &SP800_38A_AES_TV[6];
+    {
+        let mut op = aes
+            .init(ctr.key, AesMode::Ctr, Direction::Decrypt, ctr.iv)
+            .unwrap();
+        for (src, dst) in [(0, 7), (7, 40), (40, 41), (41, 64)] {
+            op.update(&ctr.ciphertext[src..dst], &mut output[src..dst])
+                .unwrap();
+        }
+    }
+    report_output(uart, "CTR in pieces", &output, ctr.plaintext);
+
+    let gcm = &GCM_AES_TV[2];
+    let len = gcm.plaintext.len();
+    let tag = {
+        let mut op = aes.init_gcm(gcm.key, Direction::Encrypt, gcm.iv).unwrap();
+        op.update_aad(&gcm.aad[..3]).unwrap();
+        op.update_aad(&gcm.aad[3..]).unwrap();
+        op.update(&gcm.plaintext[..21], &mut output[..21]).unwrap();
+        op.update(&gcm.plaintext[21..], &mut output[21..len])
+            .unwrap();
+        op.finish()
+    };
+    report_output(uart, "GCM in pieces", &output[..len], gcm.ciphertext);
+    report_output(uart, "GCM tag in pieces", &tag, gcm.tag);
+
+    let mut forged = [0u8; AES_GCM_TAG_SIZE];
+    forged.copy_from_slice(gcm.tag);
+    forged[0] ^= 1;
+    let result = aes.gcm_decrypt(
+        gcm.key,
+        gcm.iv,
+        gcm.aad,
+        gcm.ciphertext,
+        &mut output,
+        &forged,
+    );
+    if result == Err(CryptoError::AuthenticationFailed) && output[..len].iter().all(|&b| b == 0) {
+        writeln!(uart, "\r\nGCM forged tag: Test passed!").unwrap();
+    } else {
+        writeln!(uart, "\r\nGCM forged tag: Test failed!").unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+// Licensed under the Apache-2.0 license
+
+use crate::crypto::AesMode;
+use hex_literal::hex;
+
+pub struct AesTestVec {
+    pub mode: AesMode,
+    pub key: &'static [u8],
+    pub iv: &'static [u8],
+    pub plaintext: &'static [u8],
+    pub ciphertext: &'static [u8],
+}
+
+pub struct GcmTestVec {
+    pub key: &'static [u8],
+    pub iv: &'static [u8],
+    pub aad: &'static [u8],
+    pub plaintext: &'static [u8],
+    pub ciphertext: &'static [u8],
+    pub tag: &'static [u8],
+}
+
+const SP800_38A_PLAINTEXT: [u8; 64] = hex!(
+    "6bc1bee22e409f96e93d7e117393172a"
+    "ae2d8a571e03ac9c9eb76fac45af8e51"
+    "30c81c46a35ce411e5fbc1191a0a52ef"
+    "f69f2445df4f9b17ad2b417be66c3710"
+);
+
+const SP800_38A_KEY128: [u8; 16] = hex!("2b7e151628aed2a6abf7158809cf4f3c");
+const SP800_38A_KEY192: [u8; 24] = hex!("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b");
+const SP800_38A_KEY256: [u8; 32] =
+    hex!("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+
+const SP800_38A_CBC_IV: [u8; 16] = hex!("000102030405060708090a0b0c0d0e0f");
+const SP800_38A_CTR_IV: [u8; 16] = hex!("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+
+/// NIST SP 800-38A appendix F, sections F.1, F.2 and F.5
+pub static SP800_38A_AES_TV: &[AesTestVec] = &[
+    AesTestVec {
+        mode: AesMode::Ecb,
+        key: &SP800_38A_KEY128,
+        iv: &[],
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "3ad77bb40d7a3660a89ecaf32466ef97"
+            "f5d3d58503b9699de785895a96fdbaaf"
+            "43b1cd7f598ece23881b00e3ed030688"
+            "7b0c785e27e8ad3f8223207104725dd4"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Ecb,
+        key: &SP800_38A_KEY192,
+        iv: &[],
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "bd334f1d6e45f25ff712a214571fa5cc"
+            "974104846d0ad3ad7734ecb3ecee4eef"
+            "ef7afd2270e2e60adce0ba2face6444e"
+            "9a4b41ba738d6c72fb16691603c18e0e"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Ecb,
+        key: &SP800_38A_KEY256,
+        iv: &[],
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "f3eed1bdb5d2a03c064b5a7e3db181f8"
+            "591ccb10d410ed26dc5ba74a31362870"
+            "b6ed21b99ca6f4f9f153e7b1beafed1d"
+            "23304b7a39f9f3ff067d8d8f9e24ecc7"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Cbc,
+        key: &SP800_38A_KEY128,
+        iv: &SP800_38A_CBC_IV,
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "7649abac8119b246cee98e9b12e9197d"
+            "5086cb9b507219ee95db113a917678b2"
+            "73bed6b8e3c1743b7116e69e22229516"
+            "3ff1caa1681fac09120eca307586e1a7"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Cbc,
+        key: &SP800_38A_KEY192,
+        iv: &SP800_38A_CBC_IV,
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "4f021db243bc633d7178183a9fa071e8"
+            "b4d9ada9ad7dedf4e5e738763f69145a"
+            "571b242012fb7ae07fa9baac3df102e0"
+            "08b0e27988598881d920a9e64f5615cd"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Cbc,
+        key: &SP800_38A_KEY256,
+        iv: &SP800_38A_CBC_IV,
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "f58c4c04d6e5f1ba779eabfb5f7bfbd6"
+            "9cfc4e967edb808d679f777bc6702c7d"
+            "39f23369a9d9bacfa530e26304231461"
+            "b2eb05e2c39be9fcda6c19078c6a9d1b"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Ctr,
+        key: &SP800_38A_KEY128,
+        iv: &SP800_38A_CTR_IV,
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "874d6191b620e3261bef6864990db6ce"
+            "9806f66b7970fdff8617187bb9fffdff"
+            "5ae4df3edbd5d35e5b4f09020db03eab"
+            "1e031dda2fbe03d1792170a0f3009cee"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Ctr,
+        key: &SP800_38A_KEY192,
+        iv: &SP800_38A_CTR_IV,
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "1abc932417521ca24f2b0459fe7e6e0b"
+            "090339ec0aa6faefd5ccc2c6f4ce8e94"
+            "1e36b26bd1ebc670d1bd1d665620abf7"
+            "4f78a7f6d29809585a97daec58c6b050"
+        ),
+    },
+    AesTestVec {
+        mode: AesMode::Ctr,
+        key: &SP800_38A_KEY256,
+        iv: &SP800_38A_CTR_IV,
+        plaintext: &SP800_38A_PLAINTEXT,
+        ciphertext: &hex!(
+            "601ec313775789a5b7a7f504bbf3d228"
+            "f443e3ca4d62b59aca84e990cacaf5c5"
+            "2b0930daa23de94ce87017ba2d84988d"
+            "dfc9c58db67aada613c2dd08457941a6"
+        ),
+    },
+];
+
+const GCM_KEY: [u8; 16] = hex!("feffe9928665731c6d6a8f9467308308");
+
+const GCM_PLAINTEXT: [u8; 64] = hex!(
+    "d9313225f88406e5a55909c5aff5269a"
+    "86a7a9531534f7da2e4c303d8a318a72"
+    "1c3c0c95956809532fcf0e2449a6b525"
+    "b16aedf5aa0de657ba637b391aafd255"
+);
+
+const GCM_PLAINTEXT_60: [u8; 60] = hex!(
+    "d9313225f88406e5a55909c5aff5269a"
+    "86a7a9531534f7da2e4c303d8a318a72"
+    "1c3c0c95956809532fcf0e2449a6b525"
+    "b16aedf5aa0de657ba637b39"
+);
+
+const GCM_AAD: [u8; 20] = hex!("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+
+const GCM_CT128: [u8; 64] = hex!(
+    "42831ec2217774244b7221b784d0d49c"
+    "e3aa212f2c02a4e035c17e2329aca12e"
+    "21d514b25466931c7d8f6a5aac84aa05"
+    "1ba30b396a0aac973d58e091473f5985"
+);
+
+const GCM_CT128_60: [u8; 60] = hex!(
+    "42831ec2217774244b7221b784d0d49c"
+    "e3aa212f2c02a4e035c17e2329aca12e"
+    "21d514b25466931c7d8f6a5aac84aa05"
+    "1ba30b396a0aac973d58e091"
+);
+
+const GCM_CT256: [u8; 64] = hex!(
+    "522dc1f099567d07f47f37a32a84427d"
+    "643a8cdcbfe5c0c97598a2bd2555d1aa"
+    "8cb08e48590dbb3da7b08b1056828838"
+    "c5f61e6393ba7a0abcc9f662898015ad"
+);
+
+const GCM_CT256_60: [u8; 60] = hex!(
+    "522dc1f099567d07f47f37a32a84427d"
+    "643a8cdcbfe5c0c97598a2bd2555d1aa"
+    "8cb08e48590dbb3da7b08b1056828838"
+    "c5f61e6393ba7a0abcc9f662"
+);
+
+/// GCM specification test cases, as used by NIST SP 800-38D validation
+pub static GCM_AES_TV: &[GcmTestVec] = &[
+    // Test case 2
+    GcmTestVec {
+        key: &[0; 16],
+        iv: &[0; 12],
+        aad: &[],
+        plaintext: &[0; 16],
+        ciphertext: &hex!("0388dace60b6a392f328c2b971b2fe78"),
+        tag: &hex!("ab6e47d42cec13bdf53a67b21257bddf"),
+    },
+    // Test case 3
+    GcmTestVec {
+        key: &GCM_KEY,
+        iv: &hex!("cafebabefacedbaddecaf888"),
+        aad: &[],
+        plaintext: &GCM_PLAINTEXT,
+        ciphertext: &GCM_CT128,
+        tag: &hex!("4d5c2af327cd64a62cf35abd2ba6fab4"),
+    },
+    // Test case 4: additional data and a partial last block
+    GcmTestVec {
+        key: &GCM_KEY,
+        iv: &hex!("cafebabefacedbaddecaf888"),
+        aad: &GCM_AAD,
+        plaintext: &GCM_PLAINTEXT_60,
+        ciphertext: &GCM_CT128_60,
+        tag: &hex!("5bc94fbc3221a5db94fae95ae7121a47"),
+    },
+    // Test case 5: 64-bit IV
+    GcmTestVec {
+        key: &GCM_KEY,
+        iv: &hex!("cafebabefacedbad"),
+        aad: &GCM_AAD,
+        plaintext: &GCM_PLAINTEXT_60,
+        ciphertext: &hex!(
+            "61353b4c2806934a777ff51fa22a4755"
+            "699b2a714fcdc6f83766e5f97b6c7423"
+            "73806900e49f24b22b097544d4896b42"
+            "4989b5e1ebac0f07c23f4598"
+        ),
+        tag: &hex!("3612d2e79e3b0785561be14aaca2fccb"),
+    },
+    // Test case 9
+    GcmTestVec {
+        key: &hex!("feffe9928665731c6d6a8f9467308308feffe9928665731c"),
+        iv: &hex!("cafebabefacedbaddecaf888"),
+        aad: &[],
+        plaintext: &GCM_PLAINTEXT,
+        ciphertext: &hex!(
+            "3980ca0b3c00e841eb06fac4872a2757"
+            "859e1ceaa6efd984628593b40ca1e19c"
+            "7d773d00c144c525ac619d18c84a3f47"
+            "18e2448b2fe324d9ccda2710acade256"
+        ),
+        tag: &hex!("9924a7c8587336bfb118024db8674a14"),
+    },
+    // Test case 14
+    GcmTestVec {
+        key: &[0; 32],
+        iv: &[0; 12],
+        aad: &[],
+        plaintext: &[0; 16],
+        ciphertext: &hex!("cea7403d4d606b6e074ec5d3baf39d18"),
+        tag: &hex!("d0d1c8a799996bf0265b98b5d48ab919"),
+    },
+    // Test case 15
+    GcmTestVec {
+        key: &hex!("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308"),
+        iv: &hex!("cafebabefacedbaddecaf888"),
+        aad: &[],
+        plaintext: &GCM_PLAINTEXT,
+        ciphertext: &GCM_CT256,
+        tag: &hex!("b094dac5d93471bdec1a502270e3cc6c"),
+    },
+    // Test case 16
+    GcmTestVec {
+        key: &hex!("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308"),
+        iv: &hex!("cafebabefacedbaddecaf888"),
+        aad: &GCM_AAD,
+        plaintext: &GCM_PLAINTEXT_60,
+        ciphertext: &GCM_CT256_60,
+        tag: &hex!("76fc6ece0f4e1768cddf8853bb2d551b"),
+    },
+];
@@ -0,0 +1,134 @@
+// Licensed under the Apache-2.0 license
+
+use crate::ecdh::{validate_public_key, EcdhError};
+use crate::ecdsa::{AspeedEcdsa, PrivateKey, PublicKey, Scalar32, Scalar48};
+use crate::uart::UartController;
+use embedded_hal::delay::DelayNs;
+use embedded_io::Write;
+use hex_literal::hex;
+
+/// NIST CAVP ECC CDH primitive test vector
+pub struct EcdhTestVec<const N: usize> {
+    /// Peer public key
+    pub peer_x: [u8; N],
+    pub peer_y: [u8; N],
+    /// Our private key and its public key
+    pub d: [u8; N],
+    pub qx: [u8; N],
+    pub qy: [u8; N],
+    /// Expected shared secret
+    pub z: [u8; N],
+}
+
+pub const P256_ECDH_TV: EcdhTestVec<32> = EcdhTestVec {
+    peer_x: hex!("700c48f77f56584c5cc632ca65640db91b6bacce3a4df6b42ce7cc838833d287"),
+    peer_y: hex!("db71e509e3fd9b060ddb20ba5c51dcc5948d46fbf640dfe0441782cab85fa4ac"),
+    d: hex!("7d7dc5f71eb29ddaf80d6214632eeae03d9058af1fb6d22ed80badb62bc1a534"),
+    qx: hex!("ead218590119e8876b29146ff89ca61770c4edbbf97d38ce385ed281d8a6b230"),
+    qy: hex!("28af61281fd35e2fa7002523acc85a429cb06ee6648325389f59edfce1405141"),
+    z: hex!("46fc62106420ff012e54a434fbdd2d25ccc5852060561e68040dd7778997bd7b"),
+};
+
+pub const P384_ECDH_TV: EcdhTestVec<48> = EcdhTestVec {
+    peer_x: hex!("a7c76b970c3b5fe8b05d2838ae04ab47697b9eaf52e764592efda27fe7513272734466b400091adbf2d68c58e0c50066"),
+    peer_y: hex!("ac68f19f2e1cb879aed43a9969b91a0839c4c38a49749b661efedf243451915ed0905a32b060992b468c64766fc8437a"),
+    d: hex!("3cc3122a68f0d95027ad38c067916ba0eb8c38894d22e1b15618b6818a661774ad463b205da88cf699ab4d43c9cf98a1"),
+    qx: hex!("9803807f2f6d2fd966cdd0290bd410c0190352fbec7ff6247de1302df86f25d34fe4a97bef60cff548355c015dbb3e5f"),
+    qy: hex!("ba26ca69ec2f5b5d9dad20cc9da711383a9dbe34ea3fa5a2af75b46502629ad54dd8b7d73a8abb06a3a3be47d650cc99"),
+    z: hex!("5f9d29dc5e31a163060356213669c8ce132e22f57c9a04f40ba7fcead493b457e5621e766c40a2e3d4d6a04b25e533f1"),
+};
+
+fn check<S, D: DelayNs>(
+    uart: &mut UartController,
+    ecdsa: &mut AspeedEcdsa<'_, D>,
+    name: &str,
+    key: &PrivateKey<S>,
+    peer: &PublicKey<S>,
+    vec_q: (&[u8], &[u8]),
+    z: &[u8],
+) where
+    S: AsRef<[u8]> + AsMut<[u8]> + Default,
+{
+    let derived = ecdsa
+        .public_key(key)
+        .is_ok_and(|q| q.qx.as_ref() == vec_q.0 && q.qy.as_ref() == vec_q.1);
+    writeln!(
+        uart,
+        "\r{name} public key... {}",
+        if derived { "Pass" } else { "Failed" }
+    )
+    .unwrap();
+
+    let agreed = ecdsa
+        .shared_secret(key, peer)
+        .is_ok_and(|secret| secret.z.as_ref() == z);
+    writeln!(
+        uart,
+        "\r{name} shared secret... {}",
+        if agreed { "Pass" } else { "Failed" }
+    )
+    .unwrap();
+}
+
+/// Derive public keys and shared secrets from the CAVP vectors, and check
+/// that points off the curve are refused.
+pub fn run_ecdh_tests<D: DelayNs>(uart: &mut UartController, ecdsa: &mut AspeedEcdsa<'_, D>) {
+    writeln!(uart, "\r\nRunning ECDH test").unwrap();
+
+    let vec = &P256_ECDH_TV;
+    let key = PrivateKey { d: Scalar32(vec.d) };
+    let peer = PublicKey {
+        qx: Scalar32(vec.peer_x),
+        qy: Scalar32(vec.peer_y),
+    };
+    check(
+        uart,
+        ecdsa,
+        "P-256",
+        &key,
+        &peer,
+        (&vec.qx, &vec.qy),
+        &vec.z,
+    );
+
+    let vec = &P384_ECDH_TV;
+    let key = PrivateKey { d: Scalar48(vec.d) };
+    let mut peer = PublicKey {
+        qx: Scalar48(vec.peer_x),
+        qy: Scalar48(vec.peer_y),
+    };
+    check(
+        uart,
+        ecdsa,
+        "P-384",
+        &key,
+        &peer,
+        (&vec.qx, &vec.qy),
+        &vec.z,
+    );
+
+    // A point off the curve, and one with a coordinate of p or more
+    peer.qy.0[47] ^= 1;
+    let off_curve = matches!(
+        ecdsa.shared_secret(&key, &peer),
+        Err(EcdhError::InvalidPublicKey)
+    );
+    peer.qy.0 = [0xff; 48];
+    let out_of_range = validate_public_key(&peer) == Err(EcdhError::InvalidPublicKey);
+    let zero_key = matches!(
+        ecdsa.public_key(&PrivateKey {
+            d: Scalar48::default()
+        }),
+        Err(EcdhError::InvalidPrivateKey)
+    );
+    writeln!(
+        uart,
+        "\rInvalid keys rejected... {}",
+        if off_curve && out_of_range && zero_key {
+            "Pass"
+        } else {
+            "Failed"
+        }
+    )
+    .unwrap();
+}
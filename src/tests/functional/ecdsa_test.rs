@@ -1,19 +1,33 @@
 // Licensed under the Apache-2.0 license
 
-use crate::ecdsa::{PublicKey, Scalar48, Secp384r1Curve, Signature};
+use crate::der::DerError;
+#[cfg(feature = "test-rng")]
+use crate::ecdsa::AspeedEcdsaError;
+use crate::ecdsa::{
+    AspeedEcdsa, PrivateKey, PublicKey, Scalar32, Scalar48, Secp256r1Curve, Secp384r1Curve,
+    Signature,
+};
+use crate::sec1::Sec1Error;
+use crate::tests::functional::report;
+#[cfg(feature = "test-rng")]
+use crate::tests::functional::TestRng;
 use crate::uart::UartController;
+use embedded_hal::delay::DelayNs;
 use embedded_io::Write;
+use proposed_traits::common::{Endian, FromBytes, ToBytes};
 use proposed_traits::digest::DigestAlgorithm;
+#[cfg(feature = "test-rng")]
+use proposed_traits::ecdsa::EcdsaSign;
 use proposed_traits::ecdsa::{Curve, EcdsaVerify};
 
 use hex_literal::hex;
 
-pub struct EcdsaTestVec {
-    pub qx: [u8; 48],
-    pub qy: [u8; 48],
-    pub r: [u8; 48],
-    pub s: [u8; 48],
-    pub m: [u8; 48],
+pub struct EcdsaTestVec<const N: usize = 48> {
+    pub qx: [u8; N],
+    pub qy: [u8; N],
+    pub r: [u8; N],
+    pub s: [u8; N],
+    pub m: [u8; N],
     pub result: bool,
 }
 
@@ -84,6 +98,59 @@ pub const SECP384R1_TESTVEC: &[EcdsaTestVec] = &[
     },
 ];
 
+/// NIST CAVP 186-3 SigVer, [P-256,SHA-256]; `m` is the SHA-256 digest of
+/// the vector's message
+pub const SECP256R1_TESTVEC: &[EcdsaTestVec<32>] = &[
+    EcdsaTestVec {
+        qx: hex!("87F8F2B218F49845F6F10EEC3877136269F5C1A54736DBDF69F89940CAD41555"),
+        qy: hex!("E15F369036F49842FAC7A86C8A2B0557609776814448B8F5E84AA9F4395205E9"),
+        r: hex!("D19FF48B324915576416097D2544F7CBDF8768B1454AD20E0BAAC50E211F23B0"),
+        s: hex!("A3E81E59311CDFFF2D4784949F7A2CB50BA6C3A91FA54710568E61ACA3E847C6"),
+        m: hex!("A82C31412F537135D1C418BD7136FB5FDE9426E70C70E7C2FB11F02F30FDEAE2"),
+        result: false,
+    },
+    EcdsaTestVec {
+        qx: hex!("5CF02A00D205BDFEE2016F7421807FC38AE69E6B7CCD064EE689FC1A94A9F7D2"),
+        qy: hex!("EC530CE3CC5C9D1AF463F264D685AFE2B4DB4B5828D7E61B748930F3CE622A85"),
+        r: hex!("DC23D130C6117FB5751201455E99F36F59ABA1A6A21CF2D0E7481A97451D6693"),
+        s: hex!("D6CE7708C18DBF35D4F8AA7240922DC6823F2E7058CBC1484FCAD1599DB5018C"),
+        m: hex!("5984EAB8854D0A9AA5F0C70F96DEEB510E5F9FF8C51BEFCDC3C41BAC53577F22"),
+        result: false,
+    },
+    EcdsaTestVec {
+        qx: hex!("2DDFD145767883FFBB0AC003AB4A44346D08FA2570B3120DCCE94562422244CB"),
+        qy: hex!("5F70C7D11AC2B7A435CCFBBAE02C3DF1EA6B532CC0E9DB74F93FFFCA7C6F9A64"),
+        r: hex!("9913111CFF6F20C5BF453A99CD2C2019A4E749A49724A08774D14E4C113EDDA8"),
+        s: hex!("9467CD4CD21ECB56B0CAB0A9A453B43386845459127A952421F5C6382866C5CC"),
+        m: hex!("44B02AD3088076F997220A68FF0B27A58ECFA528B604427097CCE5CA956274C5"),
+        result: false,
+    },
+    EcdsaTestVec {
+        qx: hex!("E424DC61D4BB3CB7EF4344A7F8957A0C5134E16F7A67C074F82E6E12F49ABF3C"),
+        qy: hex!("970EED7AA2BC48651545949DE1DDDAF0127E5965AC85D1243D6F60E7DFAEE927"),
+        r: hex!("BF96B99AA49C705C910BE33142017C642FF540C76349B9DAB72F981FD9347F4F"),
+        s: hex!("17C55095819089C2E03B9CD415ABDF12444E323075D98F31920B9E0F57EC871C"),
+        m: hex!("D1B8EF21EB4182EE270638061063A3F3C16C114E33937F69FB232CC833965A94"),
+        result: true,
+    },
+    EcdsaTestVec {
+        qx: hex!("E0FC6A6F50E1C57475673EE54E3A57F9A49F3328E743BF52F335E3EEAA3D2864"),
+        qy: hex!("7F59D689C91E463607D9194D99FAF316E25432870816DDE63F5D4B373F12F22A"),
+        r: hex!("1D75830CD36F4C9AA181B2C4221E87F176B7F05B7C87824E82E396C88315C407"),
+        s: hex!("CB2ACB01DAC96EFC53A32D4A0D85D0C2E48955214783ECF50A4F0414A319C05A"),
+        m: hex!("B9336A8D1F3E8EDE001D19F41320BC7672D772A3D2CB0E435FFF3C27D6804A2C"),
+        result: true,
+    },
+    EcdsaTestVec {
+        qx: hex!("A849BEF575CAC3C6920FBCE675C3B787136209F855DE19FFE2E8D29B31A5AD86"),
+        qy: hex!("BF5FE4F7858F9B805BD8DCC05AD5E7FB889DE2F822F3D8B41694E6C55C16B471"),
+        r: hex!("25ACC3AA9D9E84C7ABF08F73FA4195ACC506491D6FC37CB9074528A7DB87B9D6"),
+        s: hex!("9B21D5B5259ED3F2EF07DFEC6CC90D3A37855D1CE122A85BA6A333F307D31537"),
+        m: hex!("640C13E290147A48C83E0EA75A0F92723CDA125EE21A747E34C8D1B36F16CF2D"),
+        result: false,
+    },
+];
+
 pub fn run_ecdsa_tests(
     uart: &mut UartController,
     verifier: &mut impl EcdsaVerify<Secp384r1Curve, PublicKey = PublicKey, Signature = Signature>,
@@ -113,3 +180,221 @@ pub fn run_ecdsa_tests(
         };
     }
 }
+
+pub fn run_ecdsa_p256_tests(
+    uart: &mut UartController,
+    verifier: &mut impl EcdsaVerify<
+        Secp256r1Curve,
+        PublicKey = PublicKey<Scalar32>,
+        Signature = Signature<Scalar32>,
+    >,
+) {
+    writeln!(uart, "\r\nRunning ECDSA P-256 test").unwrap();
+    for (i, vec) in SECP256R1_TESTVEC.iter().enumerate() {
+        let pubkey = PublicKey {
+            qx: Scalar32(vec.qx),
+            qy: Scalar32(vec.qy),
+        };
+        let sig = Signature {
+            r: Scalar32(vec.r),
+            s: Scalar32(vec.s),
+        };
+        let digest = Scalar32(vec.m);
+
+        let result = verifier.verify(&pubkey, digest, &sig);
+
+        writeln!(uart, "\r\nTest case {i}... ").unwrap();
+
+        let _ = match (result.is_ok(), vec.result) {
+            (true, true) => writeln!(uart, "\rresult expected (pass), Pass"),
+            (false, false) => writeln!(uart, "\rresult expected (fail), Pass"),
+            _ => writeln!(uart, "\rresult unexpected (got {result:?}), Failed"),
+        };
+    }
+}
+
+/// P-384 key of RFC 6979 appendix A.2.6
+#[cfg(feature = "test-rng")]
+const RFC6979_P384_D: [u8; 48] = hex!("6B9D3DAD2E1B8C1C05B19875B6659F4DE23C3B667BF297BA9AA47740787137D896D5724E4C70A825F872C9EA60D2EDF5");
+const RFC6979_P384_QX: [u8; 48] = hex!("EC3A4E415B4E19A4568618029F427FA5DA9A8BC4AE92E02E06AAE5286B300C64DEF8F0EA9055866064A254515480BC13");
+const RFC6979_P384_QY: [u8; 48] = hex!("8015D9B72D7D57244EA8EF9AC0C621896708A59367F9DFB9F54CA84B3F1C9DB1288B231C3AE0D4FE7344FD2533264720");
+
+/// SHA-384 signatures of "sample" and "test" with the RFC 6979 P-384 key;
+/// `result` is unused
+pub const RFC6979_P384_SIGS: &[EcdsaTestVec] = &[
+    EcdsaTestVec {
+        qx: RFC6979_P384_QX,
+        qy: RFC6979_P384_QY,
+        r:  hex!("94EDBB92A5ECB8AAD4736E56C691916B3F88140666CE9FA73D64C4EA95AD133C81A648152E44ACF96E36DD1E80FABE46"),
+        s:  hex!("99EF4AEB15F178CEA1FE40DB2603138F130E740A19624526203B6351D0A3A94FA329C145786E679E7B82C71A38628AC8"),
+        m:  hex!("9A9083505BC92276AEC4BE312696EF7BF3BF603F4BBD381196A029F340585312313BCA4A9B5B890EFEE42C77B1EE25FE"),
+        result: true,
+    },
+    EcdsaTestVec {
+        qx: RFC6979_P384_QX,
+        qy: RFC6979_P384_QY,
+        r:  hex!("8203B63D3C853E8D77227FB377BCF7B7B772E97892A80F36AB775D509D7A5FEB0542A7F0812998DA8F1DD3CA3CF023DB"),
+        s:  hex!("DDD0760448D42D8A43AF45AF836FCE4DE8BE06B485E9B61B827C2F13173923E06A739F040649A667BF3B828246BAA5A5"),
+        m:  hex!("768412320F7B0AA5812FCE428DC4706B3CAE50E02A64CAA16A782249BFE8EFC4B7EF1CCB126255D196047DFEDF17A0A9"),
+        result: true,
+    },
+];
+
+/// Sign the RFC 6979 P-384 samples, which must give the RFC's deterministic
+/// signatures and verify with the matching public key, and check that
+/// out-of-range private scalars are rejected.
+#[cfg(feature = "test-rng")]
+pub fn run_ecdsa_sign_tests<D: DelayNs>(uart: &mut UartController, ecdsa: &mut AspeedEcdsa<'_, D>) {
+    writeln!(uart, "\r\nRunning ECDSA P-384 signing test").unwrap();
+    let mut rng = TestRng(0x2545_f491_4f6c_dd1d);
+    let key = PrivateKey {
+        d: Scalar48(RFC6979_P384_D),
+    };
+    for (i, vec) in RFC6979_P384_SIGS.iter().enumerate() {
+        let pubkey = PublicKey {
+            qx: Scalar48(vec.qx),
+            qy: Scalar48(vec.qy),
+        };
+        let signed = EcdsaSign::<Secp384r1Curve>::sign(ecdsa, &key, Scalar48(vec.m), &mut rng);
+        let passed = signed.is_ok_and(|sig| {
+            sig.r.0 == vec.r
+                && sig.s.0 == vec.s
+                && EcdsaVerify::<Secp384r1Curve>::verify(ecdsa, &pubkey, Scalar48(vec.m), &sig)
+                    .is_ok()
+        });
+        writeln!(
+            uart,
+            "\rTest case {i}... {}",
+            if passed { "Pass" } else { "Failed" }
+        )
+        .unwrap();
+    }
+
+    // d must lie in [1, n - 1]
+    let order = Scalar48(hex!("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC7634D81F4372DDF581A0DB248B0A77AECEC196ACCC52973"));
+    for d in [Scalar48::default(), order] {
+        let key = PrivateKey { d };
+        let result = EcdsaSign::<Secp384r1Curve>::sign(ecdsa, &key, Scalar48::default(), &mut rng);
+        let rejected = matches!(result, Err(AspeedEcdsaError::BadInput));
+        writeln!(
+            uart,
+            "\rOut-of-range key rejected... {}",
+            if rejected { "Pass" } else { "Failed" }
+        )
+        .unwrap();
+    }
+}
+
+/// Round-trip keys and signatures through the SEC 1, DER and fixed-width
+/// encodings, verify with what was decoded, and check that malformed or
+/// out-of-range values are refused.
+pub fn run_ecdsa_encoding_tests(
+    uart: &mut UartController,
+    verifier: &mut impl EcdsaVerify<Secp384r1Curve, PublicKey = PublicKey, Signature = Signature>,
+) {
+    writeln!(uart, "\r\nRunning ECDSA encoding test").unwrap();
+    let vec = &RFC6979_P384_SIGS[0];
+    let mut buf = [0u8; 128];
+
+    // Uncompressed and compressed points decode to the same key
+    let key = PublicKey {
+        qx: Scalar48(vec.qx),
+        qy: Scalar48(vec.qy),
+    };
+    let len = key.to_sec1_uncompressed(&mut buf).unwrap();
+    let uncompressed = PublicKey::<Scalar48>::from_sec1(&buf[..len])
+        .is_ok_and(|q| len == 97 && q.qx.0 == vec.qx && q.qy.0 == vec.qy);
+    report(uart, "SEC 1 uncompressed point", uncompressed);
+
+    let len = key.to_sec1_compressed(&mut buf).unwrap();
+    let compressed = PublicKey::<Scalar48>::from_sec1(&buf[..len])
+        .is_ok_and(|q| len == 49 && q.qx.0 == vec.qx && q.qy.0 == vec.qy);
+    // The other parity names the negated point, which differs in y
+    buf[0] ^= 1;
+    let negated = PublicKey::<Scalar48>::from_sec1(&buf[..len])
+        .is_ok_and(|q| q.qx.0 == vec.qx && q.qy.0 != vec.qy);
+    let p256 = &SECP256R1_TESTVEC[0];
+    let p256_key = PublicKey {
+        qx: Scalar32(p256.qx),
+        qy: Scalar32(p256.qy),
+    };
+    let len = p256_key.to_sec1_compressed(&mut buf).unwrap();
+    let p256_round_trip = PublicKey::<Scalar32>::from_sec1(&buf[..len])
+        .is_ok_and(|q| q.qx.0 == p256.qx && q.qy.0 == p256.qy);
+    report(
+        uart,
+        "SEC 1 compressed point",
+        compressed && negated && p256_round_trip,
+    );
+
+    // r and s of the sample both have the top bit set, so each INTEGER
+    // carries a leading zero
+    let sig = Signature {
+        r: Scalar48(vec.r),
+        s: Scalar48(vec.s),
+    };
+    let len = sig.to_der(&mut buf).unwrap();
+    let header = len == 104 && buf[..5] == [0x30, 0x66, 0x02, 0x31, 0x00];
+    let der = Signature::<Scalar48>::from_der(&buf[..len]).is_ok_and(|decoded| {
+        decoded.r.0 == vec.r
+            && decoded.s.0 == vec.s
+            && verifier.verify(&key, Scalar48(vec.m), &decoded).is_ok()
+    });
+    report(uart, "DER signature", header && der);
+
+    let mut fixed = [0u8; 96];
+    fixed[..48].copy_from_slice(&vec.r);
+    fixed[48..].copy_from_slice(&vec.s);
+    let from_fixed =
+        Signature::<Scalar48>::from_fixed(&fixed).is_ok_and(|d| d.r.0 == vec.r && d.s.0 == vec.s);
+    report(uart, "Fixed-width signature", from_fixed);
+
+    // Little-endian raw bytes reverse each scalar
+    sig.to_bytes(&mut buf, Endian::Little).unwrap();
+    let reversed = buf[0] == vec.r[47] && buf[48] == vec.s[47];
+    let little = Signature::<Scalar48>::from_bytes(&buf[..96], Endian::Little)
+        .is_ok_and(|d| d.r.0 == vec.r && d.s.0 == vec.s);
+    report(uart, "Little-endian raw signature", reversed && little);
+
+    // s = 0 and r = n are out of range, for decoding and for verification
+    fixed[48..].fill(0);
+    let zero_s = Signature::<Scalar48>::from_fixed(&fixed).err() == Some(Sec1Error::OutOfRange);
+    let order = hex!("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC7634D81F4372DDF581A0DB248B0A77AECEC196ACCC52973");
+    fixed[..48].copy_from_slice(&order);
+    fixed[48..].copy_from_slice(&vec.s);
+    let r_is_n = Signature::<Scalar48>::from_fixed(&fixed).err() == Some(Sec1Error::OutOfRange);
+    let unchecked = Signature {
+        r: Scalar48(order),
+        s: Scalar48(vec.s),
+    };
+    let verify_refused = verifier.verify(&key, Scalar48(vec.m), &unchecked).is_err();
+    // Negative INTEGERs and trailing data are malformed DER
+    let negative =
+        Signature::<Scalar48>::from_der(&[0x30, 0x06, 0x02, 0x01, 0x81, 0x02, 0x01, 0x01]).err()
+            == Some(Sec1Error::Der(DerError::InvalidInteger));
+    let trailing =
+        Signature::<Scalar48>::from_der(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01, 0x00])
+            .err()
+            == Some(Sec1Error::Der(DerError::TrailingData));
+    report(
+        uart,
+        "Out-of-range signatures rejected",
+        zero_s && r_is_n && verify_refused && negative && trailing,
+    );
+
+    // A point off the curve, a bad leading byte and the point at infinity
+    let mut point = [0u8; 97];
+    key.to_sec1_uncompressed(&mut point).unwrap();
+    point[96] ^= 1;
+    let off_curve = PublicKey::<Scalar48>::from_sec1(&point).err() == Some(Sec1Error::NotOnCurve);
+    point[0] = 0x05;
+    let bad_tag =
+        PublicKey::<Scalar48>::from_sec1(&point).err() == Some(Sec1Error::InvalidEncoding);
+    let infinity =
+        PublicKey::<Scalar48>::from_sec1(&[0x00]).err() == Some(Sec1Error::InvalidEncoding);
+    report(
+        uart,
+        "Invalid points rejected",
+        off_curve && bad_tag && infinity,
+    );
+}
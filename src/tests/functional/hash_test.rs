@@ -1,23 +1,24 @@
 // Licensed under the Apache-2.0 license
 
-use crate::hace_controller::HaceController;
-use crate::hash::{IntoHashAlgo, Sha256, Sha384, Sha512};
+use crate::hace_controller::{
+    HaceController, HaceError, HashSession, HACE_DEFAULT_TIMEOUT_POLLS, HASH_CTX_POOL_SIZE,
+};
+use crate::hash::{
+    HashError, HashState, HashStateError, IntoHashAlgo, Sha1, Sha224, Sha256, Sha384, Sha512,
+    Sha512_224, Sha512_256, HASH_STATE_LEN,
+};
+use crate::syscon::SysCon;
+use crate::tests::functional::{print_hex_array, report, report_output};
 use crate::uart::UartController;
+use ast1060_pac::Hace;
 use core::any::TypeId;
+use core::future::Future;
+use core::pin::pin;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use embedded_hal::delay::DelayNs;
 use embedded_io::Write;
-use proposed_traits::digest::{DigestAlgorithm, DigestInit, DigestOp};
-
-fn print_hex_array(uart: &mut UartController, data: &[u8], bytes_per_line: usize) {
-    for (i, b) in data.iter().enumerate() {
-        if i % bytes_per_line == 0 {
-            writeln!(uart, "\r").unwrap();
-        } else {
-            write!(uart, " ").unwrap();
-        }
-        write!(uart, "{b:02x}").unwrap();
-    }
-    writeln!(uart).unwrap();
-}
+use proposed_traits::digest::{DigestAlgorithm, DigestInit, DigestOp, Error, ErrorKind};
 
 fn print_input(uart: &mut UartController, algo: &str, input: &[u8]) {
     match core::str::from_utf8(input) {
@@ -41,9 +42,13 @@ fn print_input(uart: &mut UartController, algo: &str, input: &[u8]) {
 pub fn run_hash_tests(uart: &mut UartController, hace: &mut HaceController) {
     let input = *b"hello_world";
 
+    run_hash::<Sha1>(uart, hace, &input);
+    run_hash::<Sha224>(uart, hace, &input);
     run_hash::<Sha256>(uart, hace, &input);
     run_hash::<Sha384>(uart, hace, &input);
     run_hash::<Sha512>(uart, hace, &input);
+    run_hash::<Sha512_224>(uart, hace, &input);
+    run_hash::<Sha512_256>(uart, hace, &input);
 }
 
 fn run_hash<A>(uart: &mut UartController, ctrl: &mut HaceController, input: &[u8])
@@ -59,7 +64,22 @@ where
     writeln!(uart, "\r\nOutput:").unwrap();
     print_hex_array(uart, output.as_ref(), 16);
 
-    let expected = if TypeId::of::<A>() == TypeId::of::<Sha256>() {
+    let expected = if TypeId::of::<A>() == TypeId::of::<Sha1>() {
+        Some(
+            &[
+                // Expected SHA-1 hash of "hello_world"
+                0xe4, 0xec, 0xd6, 0xfc, 0x11, 0x89, 0x85, 0x65, 0xaf, 0x24, 0x97, 0x7e, 0x99, 0x2c,
+                0xea, 0x0c, 0x9c, 0x7b, 0x70, 0x25,
+            ][..],
+        )
+    } else if TypeId::of::<A>() == TypeId::of::<Sha224>() {
+        Some(
+            &[
+                0x69, 0xc9, 0x39, 0x2f, 0x54, 0xe5, 0xa0, 0xe0, 0xff, 0xf8, 0x94, 0x5e, 0x9e, 0xd6,
+                0x47, 0x5e, 0xf8, 0x92, 0x36, 0x09, 0x2a, 0x52, 0xb2, 0x00, 0x57, 0x76, 0x91, 0x2c,
+            ][..],
+        )
+    } else if TypeId::of::<A>() == TypeId::of::<Sha256>() {
         Some(
             &[
                 // Expected SHA-256 hash of "hello_world"
@@ -87,6 +107,21 @@ where
                 0x5c, 0xb9, 0x6e, 0xe8, 0xc8, 0xc8, 0x0a, 0x85,
             ][..],
         )
+    } else if TypeId::of::<A>() == TypeId::of::<Sha512_224>() {
+        Some(
+            &[
+                0xcd, 0x54, 0x6e, 0x1a, 0xb3, 0xb0, 0xa2, 0xef, 0xa3, 0xa1, 0x48, 0x8a, 0x4f, 0x9e,
+                0xc7, 0x13, 0x68, 0xbf, 0xd9, 0x5c, 0x91, 0x80, 0x59, 0x17, 0x14, 0x11, 0x82, 0x7a,
+            ][..],
+        )
+    } else if TypeId::of::<A>() == TypeId::of::<Sha512_256>() {
+        Some(
+            &[
+                0x39, 0x94, 0x44, 0x01, 0x52, 0xc5, 0xd4, 0x9d, 0x10, 0xd5, 0x30, 0xe9, 0x1a, 0x33,
+                0xe1, 0x58, 0xb6, 0x97, 0x1d, 0xce, 0x08, 0x6f, 0x47, 0x4c, 0x51, 0xfe, 0xee, 0xa5,
+                0xfa, 0xc5, 0x55, 0x0f,
+            ][..],
+        )
     } else {
         None
     };
@@ -110,3 +145,315 @@ where
         .unwrap();
     }
 }
+
+/// Interleave multi-block updates of two digests on separate controllers,
+/// split at different offsets so each context carries a partial block
+/// across the other's updates, and check that neither corrupts the other.
+/// Then make sure the pool reports exhaustion.
+pub fn run_hash_session_tests(uart: &mut UartController, hace: &Hace) {
+    // Digests of bytes i % 251 for i in 0..1000
+    const SHA256_EXPECTED: [u8; 32] = [
+        0x4e, 0x4c, 0x29, 0x4b, 0x33, 0x1f, 0x7a, 0x20, 0x99, 0xa3, 0x79, 0xbe, 0xc3, 0x4b, 0x9f,
+        0x9f, 0xc0, 0x3d, 0xc4, 0x6a, 0xb4, 0x65, 0xd9, 0x98, 0xf4, 0xd6, 0x83, 0xda, 0x53, 0x48,
+        0x7e, 0x6d,
+    ];
+    const SHA384_EXPECTED: [u8; 48] = [
+        0x7a, 0x2f, 0x8c, 0x7f, 0x12, 0x34, 0x49, 0x64, 0xa1, 0x3c, 0xb9, 0x26, 0x04, 0x92, 0xb8,
+        0x45, 0xe5, 0x66, 0x15, 0xd6, 0x15, 0x2b, 0x9e, 0xb9, 0xe5, 0x4b, 0x58, 0x0f, 0xc8, 0x84,
+        0x05, 0xe6, 0x4f, 0x31, 0x81, 0x3b, 0xfd, 0xa1, 0x0d, 0xe2, 0xa6, 0x42, 0xfd, 0xf1, 0x67,
+        0x6c, 0x61, 0xb4,
+    ];
+    const SPLITS_A: [usize; 5] = [0, 70, 333, 600, 1000];
+    const SPLITS_B: [usize; 5] = [0, 5, 200, 777, 1000];
+
+    writeln!(uart, "\r\nRunning hash session tests...").unwrap();
+
+    let mut message = [0u8; 1000];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 251).unwrap();
+    }
+
+    let mut ctrl_a = HaceController::new(hace);
+    let mut ctrl_b = HaceController::new(hace);
+
+    let mut ctx_a = ctrl_a.init(Sha256).unwrap();
+    let mut ctx_b = ctrl_b.init(Sha384).unwrap();
+
+    for (a, b) in SPLITS_A.windows(2).zip(SPLITS_B.windows(2)) {
+        ctx_a.update(&message[a[0]..a[1]]).unwrap();
+        ctx_b.update(&message[b[0]..b[1]]).unwrap();
+    }
+
+    let out_b = ctx_b.finalize().unwrap();
+    let out_a = ctx_a.finalize().unwrap();
+
+    if out_a.as_ref() == &SHA256_EXPECTED[..] && out_b.as_ref() == &SHA384_EXPECTED[..] {
+        writeln!(uart, "\r\nInterleaved sessions: Test passed!").unwrap();
+    } else {
+        writeln!(uart, "\r\nInterleaved sessions: Test failed!").unwrap();
+        writeln!(uart, "Got SHA256:").unwrap();
+        print_hex_array(uart, out_a.as_ref(), 16);
+        writeln!(uart, "Got SHA384:").unwrap();
+        print_hex_array(uart, out_b.as_ref(), 16);
+    }
+
+    // Hold every slot in the pool, then ask for one more.
+    let held: [Option<HashSession>; HASH_CTX_POOL_SIZE] =
+        core::array::from_fn(|_| HashSession::acquire());
+    let all_acquired = held.iter().all(Option::is_some);
+    let exhausted = ctrl_a.init(Sha256).map(|_| ()).map_err(|e| e.kind());
+    drop(held);
+
+    match (all_acquired, exhausted) {
+        (true, Err(ErrorKind::MemoryAllocationFailure)) => {
+            writeln!(uart, "\r\nSession pool exhaustion: Test passed!").unwrap();
+        }
+        _ => {
+            writeln!(uart, "\r\nSession pool exhaustion: Test failed!").unwrap();
+        }
+    }
+}
+
+/// Hash non-contiguous segments through the scatter-gather path and compare
+/// against the same data fed as one contiguous buffer.
+pub fn run_hash_sg_tests(uart: &mut UartController, hace: &mut HaceController) {
+    writeln!(uart, "\r\nRunning scatter-gather hash tests...").unwrap();
+
+    let mut message = [0u8; 337];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 251).unwrap();
+    }
+    let (header, rest) = message.split_at(100);
+    let (body, trailer) = rest.split_at(37);
+
+    let mut ctx = hace.init(Sha384).unwrap();
+    ctx.update(&message).unwrap();
+    let expected = ctx.finalize().unwrap();
+
+    let mut ctx = hace.init(Sha384).unwrap();
+    ctx.update(b"").unwrap();
+    ctx.update_sg(&[header, b"", body, trailer]).unwrap();
+    let output = ctx.finalize().unwrap();
+
+    if output.as_ref() == expected.as_ref() {
+        writeln!(uart, "\r\nScatter-gather: Test passed!").unwrap();
+    } else {
+        writeln!(uart, "\r\nScatter-gather: Test failed!").unwrap();
+        writeln!(uart, "Expected:").unwrap();
+        print_hex_array(uart, expected.as_ref(), 16);
+        writeln!(uart, "Got:").unwrap();
+        print_hex_array(uart, output.as_ref(), 16);
+    }
+}
+
+/// Drive a large update through the poll-style and async APIs and compare
+/// against the blocking path.
+pub fn run_hash_nb_tests(uart: &mut UartController, hace: &mut HaceController) {
+    writeln!(uart, "\r\nRunning non-blocking hash tests...").unwrap();
+
+    let mut message = [0u8; 4096];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 253).unwrap();
+    }
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    ctx.update(&message).unwrap();
+    let expected = ctx.finalize().unwrap();
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    let inputs = [&message[..]];
+    let mut pending = ctx.start_update(&inputs).unwrap();
+    let mut polls = 0u32;
+    while !pending.try_finish() {
+        polls += 1;
+    }
+    drop(pending);
+    let output = ctx.finalize().unwrap();
+    writeln!(uart, "\r\nPolled {polls} times before completion").unwrap();
+    report_output(
+        uart,
+        "poll-style update",
+        output.as_ref(),
+        expected.as_ref(),
+    );
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    block_on(ctx.update_async(&message)).unwrap();
+    let output = ctx.finalize().unwrap();
+    report_output(uart, "async update", output.as_ref(), expected.as_ref());
+}
+
+/// Hash with the engine in interrupt mode, blocking and through the future,
+/// and check the results against polling mode. `irqs` counts the HACE
+/// interrupts taken by the caller's handler, which must be installed and
+/// unmasked, and must call [`HaceController::handle_interrupt`].
+pub fn run_hash_irq_tests(uart: &mut UartController, hace: &mut HaceController, irqs: &AtomicU32) {
+    writeln!(uart, "\r\nRunning interrupt-mode hash tests...").unwrap();
+
+    let mut message = [0u8; 4096];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 239).unwrap();
+    }
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    ctx.update(&message).unwrap();
+    let expected = ctx.finalize().unwrap();
+
+    hace.set_interrupt_mode(true);
+    let before = irqs.load(Ordering::Relaxed);
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    ctx.update(&message).unwrap();
+    let output = ctx.finalize().unwrap();
+    report_output(uart, "IRQ-mode update", output.as_ref(), expected.as_ref());
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    block_on(ctx.update_async(&message)).unwrap();
+    let output = ctx.finalize().unwrap();
+    report_output(
+        uart,
+        "IRQ-mode async update",
+        output.as_ref(),
+        expected.as_ref(),
+    );
+
+    let taken = irqs.load(Ordering::Relaxed).wrapping_sub(before);
+    hace.set_interrupt_mode(false);
+    writeln!(uart, "\r\n{taken} HACE interrupts taken").unwrap();
+    report(uart, "HACE interrupt raised", taken > 0);
+}
+
+/// Force a timeout, check that the engine refuses new work until it is
+/// reset, then recover through `SysCon` and hash again.
+pub fn run_hash_recovery_tests<D: DelayNs>(
+    uart: &mut UartController,
+    hace: &mut HaceController,
+    syscon: &mut SysCon<D>,
+) {
+    writeln!(uart, "\r\nRunning hash error recovery tests...").unwrap();
+
+    let mut message = [0u8; 4096];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 241).unwrap();
+    }
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    ctx.update(&message).unwrap();
+    let expected = ctx.finalize().unwrap();
+
+    // Nothing is in flight, so the completion flag is never raised and the
+    // configured limit must run out
+    hace.set_timeout(1000);
+    let timed_out = hace.wait_hash_done();
+    hace.set_timeout(HACE_DEFAULT_TIMEOUT_POLLS);
+
+    // An operation whose completion is never collected, as after a timeout,
+    // keeps the engine claimed until it is recovered. It runs in a session
+    // of its own, since the controller has none bound between operations.
+    let Some(session) = HashSession::acquire() else {
+        writeln!(uart, "\r\nTimeout and busy reporting: no free hash context").unwrap();
+        return;
+    };
+    hace.bind_session(&session);
+    hace.algo = Sha256::to_hash_algo();
+    hace.start_digest();
+    let inputs = [&message[..]];
+    let started = hace.start_update_sg(&inputs).map(core::mem::forget).is_ok();
+    hace.unbind_session();
+
+    let ctx = hace.init(Sha256).unwrap();
+    let refused = ctx.finalize();
+
+    let recovered = hace.recover(syscon);
+    drop(session);
+
+    let mut ctx = hace.init(Sha256).unwrap();
+    ctx.update(&message).unwrap();
+    let output = ctx.finalize().unwrap();
+
+    if matches!(timed_out, Err(HashError::Engine(HaceError::Timeout)))
+        && started
+        && matches!(refused, Err(HashError::Engine(HaceError::Busy)))
+        && recovered.is_ok()
+    {
+        writeln!(uart, "\r\nTimeout and busy reporting: Test passed!").unwrap();
+    } else {
+        writeln!(uart, "\r\nTimeout and busy reporting: Test failed!").unwrap();
+    }
+    report_output(
+        uart,
+        "hash after recovery",
+        output.as_ref(),
+        expected.as_ref(),
+    );
+}
+
+/// Export a hash part way through, restore it from the serialized blob and
+/// finish it, once per suffix, as when reusing a precomputed prefix.
+pub fn run_hash_state_tests(uart: &mut UartController, hace: &mut HaceController) {
+    writeln!(uart, "\r\nRunning hash state export/import tests...").unwrap();
+
+    let mut message = [0u8; 1000];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 251).unwrap();
+    }
+    // Not a multiple of the block size, so part of the prefix stays buffered
+    let (prefix, suffix) = message.split_at(333);
+
+    let mut blob = [0u8; HASH_STATE_LEN];
+    {
+        let mut ctx = hace.init(Sha384).unwrap();
+        ctx.update(prefix).unwrap();
+        blob.copy_from_slice(ctx.export_state().as_bytes());
+    }
+
+    for split in [0, 100, suffix.len()] {
+        let expected = {
+            let mut ctx = hace.init(Sha384).unwrap();
+            ctx.update(prefix).unwrap();
+            ctx.update(&suffix[..split]).unwrap();
+            ctx.finalize().unwrap()
+        };
+
+        let state = HashState::from_bytes(&blob).unwrap();
+        let mut ctx = hace.resume::<Sha384>(&state).unwrap();
+        ctx.update(&suffix[..split]).unwrap();
+        let output = ctx.finalize().unwrap();
+        writeln!(uart, "\r\nResumed with {split} more bytes:").unwrap();
+        report_output(uart, "restored hash", output.as_ref(), expected.as_ref());
+    }
+
+    let state = HashState::from_bytes(&blob).unwrap();
+    let wrong_algo = hace.resume::<Sha512>(&state).err();
+    blob[4] ^= 0xff;
+    let bad_version = HashState::from_bytes(&blob).err();
+    if matches!(
+        wrong_algo,
+        Some(HashError::State(HashStateError::AlgorithmMismatch))
+    ) && bad_version == Some(HashStateError::UnsupportedVersion)
+    {
+        writeln!(uart, "\r\nInvalid state rejected: Test passed!").unwrap();
+    } else {
+        writeln!(uart, "\r\nInvalid state rejected: Test failed!").unwrap();
+    }
+}
+
+static NOOP_VTABLE: RawWakerVTable = RawWakerVTable::new(noop_clone, noop, noop, noop);
+
+unsafe fn noop_clone(_: *const ()) -> RawWaker {
+    RawWaker::new(core::ptr::null(), &NOOP_VTABLE)
+}
+
+unsafe fn noop(_: *const ()) {}
+
+/// Minimal executor: poll `fut` until it completes
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &NOOP_VTABLE)) };
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
@@ -1,24 +1,19 @@
 // Licensed under the Apache-2.0 license
 
-use crate::hace_controller::HaceController;
-use crate::hmac::{IntoHashAlgo, Sha256, Sha384, Sha512};
+use crate::hace_controller::{HaceController, HashSession};
+use crate::hmac::{
+    HmacKey, IntoHashAlgo, Sha1, Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256,
+};
+use crate::tests::functional::hmac_test_vec::{
+    HmacTestVec, HMAC_SHA512_224_TV, HMAC_SHA512_256_TV, RFC2202_HMAC_SHA1_TV,
+    RFC4231_HMAC_SHA224_TV, RFC4231_HMAC_SHA256_TV, RFC4231_HMAC_SHA384_TV, RFC4231_HMAC_SHA512_TV,
+};
+use crate::tests::functional::{print_hex_array, report, report_output};
 use crate::uart::UartController;
 use core::any::TypeId;
 use embedded_io::Write;
 use proposed_traits::mac::{MacAlgorithm, MacInit, MacOp};
 
-fn print_hex_array(uart: &mut UartController, data: &[u8], bytes_per_line: usize) {
-    for (i, b) in data.iter().enumerate() {
-        if i % bytes_per_line == 0 {
-            writeln!(uart, "\r").unwrap();
-        } else {
-            write!(uart, " ").unwrap();
-        }
-        write!(uart, "{b:02x}").unwrap();
-    }
-    writeln!(uart).unwrap();
-}
-
 fn print_input(uart: &mut UartController, algo: &str, input: &[u8]) {
     match core::str::from_utf8(input) {
         Ok(ascii) => {
@@ -39,15 +34,52 @@ fn print_input(uart: &mut UartController, algo: &str, input: &[u8]) {
 }
 
 pub fn run_hmac_tests(uart: &mut UartController, hace: &mut HaceController) {
-    let key256 = [0xb; 32];
-    let key384 = [0xb; 48];
-    let key512 = [0xb; 64];
+    let key256 = HmacKey::new(&[0xb; 32]).unwrap();
+    let key384 = HmacKey::new(&[0xb; 48]).unwrap();
+    let key512 = HmacKey::new(&[0xb; 64]).unwrap();
     let message = *b"The quick brown fox jumps over the lazy dog";
 
     writeln!(uart, "\r\nRunning HMAC tests...").unwrap();
     run_hmac::<Sha256>(uart, hace, &key256, &message);
     run_hmac::<Sha384>(uart, hace, &key384, &message);
     run_hmac::<Sha512>(uart, hace, &key512, &message);
+
+    run_hmac_kat::<Sha1>(uart, hace, "RFC 2202", RFC2202_HMAC_SHA1_TV);
+    run_hmac_kat::<Sha224>(uart, hace, "RFC 4231", RFC4231_HMAC_SHA224_TV);
+    run_hmac_kat::<Sha256>(uart, hace, "RFC 4231", RFC4231_HMAC_SHA256_TV);
+    run_hmac_kat::<Sha384>(uart, hace, "RFC 4231", RFC4231_HMAC_SHA384_TV);
+    run_hmac_kat::<Sha512>(uart, hace, "RFC 4231", RFC4231_HMAC_SHA512_TV);
+    run_hmac_kat::<Sha512_224>(uart, hace, "RFC 4231 inputs", HMAC_SHA512_224_TV);
+    run_hmac_kat::<Sha512_256>(uart, hace, "RFC 4231 inputs", HMAC_SHA512_256_TV);
+}
+
+fn run_hmac_kat<A>(
+    uart: &mut UartController,
+    ctrl: &mut HaceController,
+    source: &str,
+    vectors: &[HmacTestVec],
+) where
+    A: MacAlgorithm<Key = HmacKey> + IntoHashAlgo + Default,
+    A::MacOutput: Default + AsRef<[u8]> + AsMut<[u8]>,
+{
+    let name = core::any::type_name::<A>();
+
+    for (i, vec) in vectors.iter().enumerate() {
+        let key = HmacKey::new(vec.key).unwrap();
+        let mut ctx = ctrl.init(A::default(), &key).unwrap();
+        ctx.update(vec.data).unwrap();
+        let output = ctx.finalize().unwrap();
+
+        if output.as_ref()[..vec.mac.len()] == *vec.mac {
+            writeln!(uart, "\r\n{name} {source} case {}: Test passed!", i + 1).unwrap();
+        } else {
+            writeln!(uart, "\r\n{name} {source} case {}: Test failed!", i + 1).unwrap();
+            writeln!(uart, "Expected:").unwrap();
+            print_hex_array(uart, vec.mac, 16);
+            writeln!(uart, "Got:").unwrap();
+            print_hex_array(uart, output.as_ref(), 16);
+        }
+    }
 }
 
 fn run_hmac<A>(uart: &mut UartController, ctrl: &mut HaceController, key: &A::Key, input: &[u8])
@@ -116,3 +148,70 @@ where
         .unwrap();
     }
 }
+
+/// Feed HMAC input through several `update` calls, including a message much
+/// larger than the context buffer.
+pub fn run_hmac_streaming_tests(uart: &mut UartController, hace: &mut HaceController) {
+    const FOX_HMAC_SHA256: [u8; 32] = [
+        0xde, 0x60, 0xb1, 0xd4, 0x83, 0xd2, 0x00, 0x11, 0xf1, 0xb4, 0x2f, 0x33, 0x70, 0x0c, 0xb4,
+        0x4f, 0xa3, 0x16, 0xc4, 0x43, 0xce, 0x43, 0x03, 0x78, 0xcb, 0x5d, 0x65, 0x42, 0x7f, 0x64,
+        0x34, 0x8d,
+    ];
+    const LARGE_HMAC_SHA256: [u8; 32] = [
+        0xd2, 0x6c, 0x94, 0x3c, 0xe6, 0xaa, 0x8f, 0xf3, 0x0f, 0x94, 0xe3, 0xaa, 0xd1, 0xbd, 0x55,
+        0x0e, 0x01, 0xf0, 0x99, 0xe4, 0x4e, 0xf5, 0xbd, 0xb0, 0xa5, 0xac, 0xa3, 0xed, 0x56, 0x77,
+        0xa7, 0x7b,
+    ];
+
+    let key = HmacKey::new(&[0xb; 32]).unwrap();
+
+    writeln!(uart, "\r\nRunning streaming HMAC tests...").unwrap();
+
+    let mut ctx = hace.init(Sha256, &key).unwrap();
+    ctx.update(b"The quick brown ").unwrap();
+    ctx.update(b"fox jumps over ").unwrap();
+    ctx.update(b"the lazy dog").unwrap();
+    let output = ctx.finalize().unwrap();
+    report_output(
+        uart,
+        "HMAC split updates",
+        output.as_ref(),
+        &FOX_HMAC_SHA256,
+    );
+
+    let mut message = [0u8; 1000];
+    for (i, b) in message.iter_mut().enumerate() {
+        *b = u8::try_from(i % 251).unwrap();
+    }
+    let mut ctx = hace.init_hmac::<Sha256>(key.as_ref()).unwrap();
+    for chunk in message.chunks(300) {
+        ctx.update(chunk).unwrap();
+    }
+    let output = ctx.finalize().unwrap();
+    report_output(
+        uart,
+        "HMAC large input",
+        output.as_ref(),
+        &LARGE_HMAC_SHA256,
+    );
+
+    // An abandoned MAC must leave no key material in its context slot,
+    // which is the first free one and so the next to be handed out
+    let ctx = hace.init_hmac::<Sha256>(key.as_ref()).unwrap();
+    let slot = ctx.session().slot();
+    drop(ctx);
+    let wiped = HashSession::acquire().is_some_and(|session| {
+        hace.bind_session(&session);
+        let ctx = hace.ctx_mut();
+        let clean = ctx
+            .key
+            .iter()
+            .chain(&ctx.ipad)
+            .chain(&ctx.opad)
+            .all(|&b| b == 0)
+            && ctx.key_len == 0;
+        hace.unbind_session();
+        session.slot() == slot && clean
+    });
+    report(uart, "HMAC key wiped from context", wiped);
+}
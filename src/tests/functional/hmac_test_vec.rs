@@ -0,0 +1,321 @@
+// Licensed under the Apache-2.0 license
+
+use hex_literal::hex;
+
+pub struct HmacTestVec {
+    pub key: &'static [u8],
+    pub data: &'static [u8],
+    /// Expected MAC, possibly truncated; only this many bytes are compared
+    pub mac: &'static [u8],
+}
+
+/// RFC 2202 section 3 test cases for HMAC-SHA-1
+pub static RFC2202_HMAC_SHA1_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!("b617318655057264e28bc0b6fb378c8ef146be00"),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!("effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!("125d7342b9ac11cd91a39af48aa17b4f63f175d3"),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!("4c9007f4026250c6bc8414f9bf50c86c2d7235da"),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("4c1a03424b55e07fe7f27be1d58bb9324a9a5a04"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 80],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!("aa4ae5e15272d00e95705637ce8a3b55ed402112"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 80],
+        data: b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data",
+        mac: &hex!("e8e99d0f45237d786d6bbaa7965c7808bbff1a91"),
+    },
+];
+
+/// RFC 4231 section 4 test cases for HMAC-SHA-224
+pub static RFC4231_HMAC_SHA224_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!("896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22"),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!("a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!("7fb3cb3588c6c1f6ffa9694d7d6ad2649365b0c1f65d69d1ec8333ea"),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!("6c11506874013cac6a2abc1bb382627cec6a90d86efc012de7afec5a"),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("0e2aea68a90c8d37c988bcdb9fca6fa8"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!("95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        mac: &hex!("3a854166ac5d9f023f54d517d0b39dbd946770db9c2b95c9f6f565d1"),
+    },
+];
+
+/// RFC 4231 section 4 test cases for HMAC-SHA-256
+pub static RFC4231_HMAC_SHA256_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!("773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe"),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!("82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b"),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("a3b6167473100ee06e0c796c2955552b"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        mac: &hex!("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2"),
+    },
+];
+
+/// RFC 4231 section 4 test cases for HMAC-SHA-384
+pub static RFC4231_HMAC_SHA384_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!(
+            "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59c"
+            "faea9ea9076ede7f4af152e8b2fa9cb6"
+        ),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!(
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e"
+            "8e2240ca5e69e2c78b3239ecfab21649"
+        ),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!(
+            "88062608d3e6ad8a0aa2ace014c8a86f0aa635d947ac9febe83ef4e55966144b"
+            "2a5ab39dc13814b94e3ab6e101a34f27"
+        ),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!(
+            "3e8a69b7783c25851933ab6290af6ca77a9981480850009cc5577c6e1f573b4e"
+            "6801dd23c4a7d679ccf8a386c674cffb"
+        ),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("3abf34c3503b2a23a46efc619baef897"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!(
+            "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c6"
+            "0c2ef6ab4030fe8296248df163f44952"
+        ),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        mac: &hex!(
+            "6617178e941f020d351e2f254e8fd32c602420feb0b8fb9adccebb82461e99c5"
+            "a678cc31e799176d3860e6110c46523e"
+        ),
+    },
+];
+
+/// RFC 4231 section 4 test cases for HMAC-SHA-512
+pub static RFC4231_HMAC_SHA512_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!(
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde"
+            "daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"
+        ),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!(
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554"
+            "9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        ),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!(
+            "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39"
+            "bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb"
+        ),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!(
+            "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3db"
+            "a91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd"
+        ),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("415fad6271580a531d4179bc891d87a6"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!(
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352"
+            "6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+        ),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        mac: &hex!(
+            "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944"
+            "b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58"
+        ),
+    },
+];
+
+/// HMAC-SHA-512/224 over the RFC 4231 section 4 inputs, which the RFC gives no
+/// results for; computed with OpenSSL and Python's `hmac`
+pub static HMAC_SHA512_224_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!("b244ba01307c0e7a8ccaad13b1067a4cf6b961fe0c6a20bda3d92039"),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!("4a530b31a79ebcce36916546317c45f247d83241dfb818fd37254bde"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!("db34ea525c2c216ee5a6ccb6608bea870bbef12fd9b96a5109e2b6fc"),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!("c2391863cda465c6828af06ac5d4b72d0b792109952da530e11a0d26"),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("1df8eae8baeedd4eddfb555ec0ba768f"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!("29bef8ce88b54d4226c3c7718ea9e32ace2429026f089e38cea9aeda"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        mac: &hex!("82a9619b47af0cea73a8b9741355ce902d807ad87ee9078522a246e1"),
+    },
+];
+
+/// HMAC-SHA-512/256 over the RFC 4231 section 4 inputs, which the RFC gives no
+/// results for; computed with OpenSSL and Python's `hmac`
+pub static HMAC_SHA512_256_TV: &[HmacTestVec] = &[
+    HmacTestVec {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        mac: &hex!("9f9126c3d9c3c330d760425ca8a217e31feae31bfe70196ff81642b868402eab"),
+    },
+    HmacTestVec {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        mac: &hex!("6df7b24630d5ccb2ee335407081a87188c221489768fa2020513b2d593359456"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        mac: &hex!("229006391d66c8ecddf43ba5cf8f83530ef221a4e9401840d1bead5137c8a2ea"),
+    },
+    HmacTestVec {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        mac: &hex!("36d60c8aa1d0be856e10804cf836e821e8733cbafeae87630589fd0b9b0a2f4c"),
+    },
+    HmacTestVec {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        mac: &hex!("337f526924766971bf72b82ad19c2c82"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        mac: &hex!("87123c45f7c537a404f8f47cdbedda1fc9bec60eeb971982ce7ef10e774e6539"),
+    },
+    HmacTestVec {
+        key: &[0xaa; 131],
+        data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        mac: &hex!("6ea83f8e7315072c0bdaa33b93a26fc1659974637a9db8a887d06c05a7f35a66"),
+    },
+];
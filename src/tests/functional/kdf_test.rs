@@ -0,0 +1,58 @@
+// Licensed under the Apache-2.0 license
+
+use crate::hace_controller::HaceController;
+use crate::hmac::{Sha256, Sha384};
+use crate::kdf::{hkdf, hkdf_expand, hkdf_extract, kdf_counter, kdf_counter_fixed};
+use crate::tests::functional::kdf_test_vec::{
+    KDFCTR_HMAC_SHA256_TV, KDFCTR_HMAC_SHA384_TV, KDF_COUNTER_LABEL_KO, RFC5869_HKDF_SHA256_TV,
+};
+use crate::tests::functional::report;
+use crate::uart::UartController;
+use embedded_io::Write;
+
+pub fn run_kdf_tests(uart: &mut UartController, hace: &mut HaceController) {
+    writeln!(uart, "\r\nRunning KDF tests...").unwrap();
+
+    let mut okm = [0u8; 82];
+    for (i, vec) in RFC5869_HKDF_SHA256_TV.iter().enumerate() {
+        let okm = &mut okm[..vec.okm.len()];
+        writeln!(uart, "\r\nRFC 5869 test case {}:", i + 1).unwrap();
+
+        let prk = hkdf_extract::<Sha256>(hace, vec.salt, vec.ikm).unwrap();
+        report(uart, "HKDF-Extract", prk.as_ref() == vec.prk);
+
+        hkdf_expand::<Sha256>(hace, vec.prk, vec.info, okm).unwrap();
+        report(uart, "HKDF-Expand", okm == vec.okm);
+
+        okm.fill(0);
+        hkdf::<Sha256>(hace, vec.salt, vec.ikm, vec.info, okm).unwrap();
+        report(uart, "HKDF", okm == vec.okm);
+    }
+
+    // SP 800-108 counter mode over opaque fixed data, then with the
+    // label and context encoding
+    let mut ko = [0u8; 64];
+    let passed = KDFCTR_HMAC_SHA256_TV.iter().all(|vec| {
+        let ko = &mut ko[..vec.ko.len()];
+        kdf_counter_fixed::<Sha256>(hace, vec.ki, vec.fixed, ko).is_ok() && ko == vec.ko
+    });
+    report(uart, "SP 800-108 counter KDF, HMAC-SHA-256", passed);
+
+    let passed = KDFCTR_HMAC_SHA384_TV.iter().all(|vec| {
+        let ko = &mut ko[..vec.ko.len()];
+        kdf_counter_fixed::<Sha384>(hace, vec.ki, vec.fixed, ko).is_ok() && ko == vec.ko
+    });
+    report(uart, "SP 800-108 counter KDF, HMAC-SHA-384", passed);
+
+    let mut derived = [0u8; 80];
+    let passed = kdf_counter::<Sha384>(
+        hace,
+        &[0x5a; 48],
+        b"attestation",
+        b"device-0001",
+        &mut derived,
+    )
+    .is_ok()
+        && derived == KDF_COUNTER_LABEL_KO;
+    report(uart, "SP 800-108 counter KDF, label and context", passed);
+}
@@ -0,0 +1,131 @@
+// Licensed under the Apache-2.0 license
+
+use hex_literal::hex;
+
+pub struct HkdfTestVec {
+    pub ikm: &'static [u8],
+    pub salt: &'static [u8],
+    pub info: &'static [u8],
+    pub prk: &'static [u8],
+    pub okm: &'static [u8],
+}
+
+const fn seq<const N: usize>(start: u8) -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut value = start;
+    let mut i = 0;
+    while i < N {
+        out[i] = value;
+        value = value.wrapping_add(1);
+        i += 1;
+    }
+    out
+}
+
+/// RFC 5869 appendix A, test cases 1 to 3 (SHA-256)
+pub static RFC5869_HKDF_SHA256_TV: &[HkdfTestVec] = &[
+    HkdfTestVec {
+        ikm: &[0x0b; 22],
+        salt: &hex!("000102030405060708090a0b0c"),
+        info: &hex!("f0f1f2f3f4f5f6f7f8f9"),
+        prk: &hex!("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"),
+        okm: &hex!(
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+            "34007208d5b887185865"
+        ),
+    },
+    HkdfTestVec {
+        ikm: &seq::<80>(0x00),
+        salt: &seq::<80>(0x60),
+        info: &seq::<80>(0xb0),
+        prk: &hex!("06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244"),
+        okm: &hex!(
+            "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c"
+            "59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71"
+            "cc30c58179ec3e87c14c01d5c1f3434f1d87"
+        ),
+    },
+    HkdfTestVec {
+        ikm: &[0x0b; 22],
+        salt: &[],
+        info: &[],
+        prk: &hex!("19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"),
+        okm: &hex!(
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d"
+            "9d201395faa4b61a96c8"
+        ),
+    },
+];
+
+/// SP 800-108 counter-mode vector in the layout of the CAVP `KDFCTR` files:
+/// HMAC PRF, 32-bit counter before the fixed input data. `L` is the length
+/// of `ko`.
+pub struct KdfCtrTestVec {
+    pub ki: &'static [u8],
+    pub fixed: &'static [u8],
+    pub ko: &'static [u8],
+}
+
+/// HMAC-SHA-256, one partial block and two and a half blocks. Computed with
+/// Python's `hmac` module and checked against pyca/cryptography's
+/// `KBKDFHMAC`, both independent of this crate.
+pub static KDFCTR_HMAC_SHA256_TV: &[KdfCtrTestVec] = &[
+    KdfCtrTestVec {
+        ki: &hex!("05a7dfeefffb2ac96141b03c9c59228e73ea883c9e87cc1db3cc72c59368ac2e"),
+        fixed: &hex!(
+            "63a26f7d0b67c5c9c7d5b371a92fb0700d8bdd0f14f54861c8209b49e991bb43"
+            "4d8db952424c6d9bdd3eeae04a88e39643993d302392aa1dcb6ae517"
+        ),
+        ko: &hex!("5bd75f99921b937f4322ed746a28c1d1"),
+    },
+    KdfCtrTestVec {
+        ki: &hex!("0e530a4bfe2107637925409333083c3d63a5da19f87d8bdf390af7cc4c3eb436"),
+        fixed: &hex!(
+            "263a54d7da2998ce85ba9ac90c197cc8b56c3ec70c802ade2abfc981556d24ea"
+            "b9d4e485d0c0082d34cb291a704777d08edb88cd80f056327f6e04a5"
+        ),
+        ko: &hex!(
+            "307e42e86a63fc15ec8d36ba4716e72d4ae2d5053583308d706f1b3e3346290d"
+            "24cefe1549f90fa8"
+        ),
+    },
+];
+
+/// HMAC-SHA-384, one partial block and one and a third blocks, generated
+/// as for [`KDFCTR_HMAC_SHA256_TV`]
+pub static KDFCTR_HMAC_SHA384_TV: &[KdfCtrTestVec] = &[
+    KdfCtrTestVec {
+        ki: &hex!(
+            "8ff8ed9a76265c62d98e2faf59b4c7dea387beccdb78c885d16b8416b1d39a8f"
+            "5603115cf280dc2197125aa1e7366eb5"
+        ),
+        fixed: &hex!(
+            "1ad8b63a86a81685754f0b482aa9cd6ce625c4125fc4c91957c812dcb32d589c"
+            "b0635115d0d34387b6fe1147c723e3730937d254f6ee81b48de2efec"
+        ),
+        ko: &hex!("cc4eadc06257eb563021063c8d94cb8a"),
+    },
+    KdfCtrTestVec {
+        ki: &hex!(
+            "64dde74636f9021f0e24d86cdccda560692a23ab79183d7244dc71432a409bc4"
+            "df2966cd238893dd34a847114f318796"
+        ),
+        fixed: &hex!(
+            "7e33cad64ff7cf312ed9b5a157fec5befe89954ae55df47b60c232fbc2d58fa0"
+            "89b00699024b757f8b34493890fcfeb18b08acc1ce2e921b07ad7fb6"
+        ),
+        ko: &hex!(
+            "3d92e37d68777516eb27ebd2600f5aa2726a6a895c3f64b1379269906e811d79"
+            "2079e28c1b164e39679746f2994405a1e7ff3075e8e8171a597a4288a04da731"
+        ),
+    },
+];
+
+/// [`kdf_counter`](crate::kdf::kdf_counter) with HMAC-SHA-384, key `5a`
+/// repeated 48 times, label `attestation`, context `device-0001` and
+/// 80 bytes of output, generated as for [`KDFCTR_HMAC_SHA256_TV`]
+pub const KDF_COUNTER_LABEL_KO: [u8; 80] = hex!(
+    "e57284b9e2729d3478cea4d93cd2fed695708c4fe356d2a73ed30afc17abdc70"
+    "9ae11e8f1b80609a60203103102f96680f77daa504bb6f30223c0423be1c2a15"
+    "e52d794d3b84c4a4234d04d2f2e61c3c"
+);
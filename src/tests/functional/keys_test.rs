@@ -0,0 +1,181 @@
+// Licensed under the Apache-2.0 license
+
+use crate::der::{pem_decode, DerError};
+use crate::keys::{
+    parse_pkcs8, parse_rsa_public_key, parse_spki, EcCurve, KeyError, PrivateKeyInfo, PublicKeyInfo,
+};
+use crate::tests::functional::ecdsa_test::SECP384R1_TESTVEC;
+use crate::tests::functional::report;
+use crate::tests::functional::rsa_test_vec::RSA_VERIFY_TV;
+use crate::uart::UartController;
+use embedded_io::Write;
+use hex_literal::hex;
+
+// Keys generated with OpenSSL: the RSA keys are those of `RSA_VERIFY_TV`,
+// the P-384 key that of the first `SECP384R1_TESTVEC` vector, and the P-256
+// key the one of RFC 6979 appendix A.2.5.
+
+static RSA_SPKI_PEM: &str = "\
+-----BEGIN PUBLIC KEY-----\n\
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAshtK4Wx2a/QOIceoClNL\n\
+8Da8JY3LIDGznjCLet3Oq3xM+YMRRVpRlhuXjGbdHAadEYx/s91ujF608RPuAGLw\n\
+NIHjQr51FrbwpoQNv48fYkebm/debZhi4fNpybmBWuThUAv5+nQDlJQmrUJ0emIn\n\
++WSpArOjBzQdaXZEXvj+JcKL3OvhezZMq6NB7vFBudtUQq6R52H7p0QBymLLYUk2\n\
+QlbYXWBLCFrjB6p0NqTp9mw5wUQE6rHfhCkU2Pfy7aMSkponNwkVZAlkdsaT0ywQ\n\
+Jc1a2RUO9ClLycdw2T2H74AK2FwfoB52xNo6bTt646tFpPGC+IVmtOquCcK0/zYV\n\
+3wIDAQAB\n\
+-----END PUBLIC KEY-----\n\
+";
+
+static RSA3072_PKCS1_DER: &[u8] = &hex!(
+    "3082018a0282018100d8ad08b52783f47941d39a32b37f318f786805634e7f62"
+    "fbe754816aeead1041ba892068b415ee368a2fc5be149b1f68c07deb8dc4c108"
+    "663ec747d8a6c48c5cfb849d21af7b2d0008c91afa50b1c15beae002b5a1f456"
+    "a2b5235d30149b26bf137cc457bfedf9c73174d209725bc39cf2365900b677b8"
+    "fbdd2d95a523da8836af5559f98c4580097e73cb65ecf7fd8491a9d6ab94fc67"
+    "a45c43f04b1f2fd4e812fbddeb09290fc20dbb210ce27a1baae2e8eb5169c30b"
+    "31ada8a7001588586d68330b8ec9211f45e65654152f9d083a536784771db878"
+    "005079d9d337f0c909a779c6073df870ec4b374fe55bcdecb8977348ed20038b"
+    "4e85f87e175c9b0e05a0a438848b621181221e2d9f498855ea2fff71a9192081"
+    "85025f6732dcd3e265dd6af448fa39c159f08ae86d983c49ac2f55b280c0380e"
+    "128699ec1db4e88128551c6fc8a89ad65cc552534d7c7f982c5517f70695918a"
+    "b91106cf3cad5a2ec8e6852230bcde5f1929b18b80552db2989a15e4757e2607"
+    "1efbd85d56dfb0d4810203010001"
+);
+
+pub static RSA_PKCS8_DER: &[u8] = &hex!(
+    "308204be020100300d06092a864886f70d0101010500048204a8308204a40201"
+    "000282010100b21b4ae16c766bf40e21c7a80a534bf036bc258dcb2031b39e30"
+    "8b7addceab7c4cf98311455a51961b978c66dd1c069d118c7fb3dd6e8c5eb4f1"
+    "13ee0062f03481e342be7516b6f0a6840dbf8f1f62479b9bf75e6d9862e1f369"
+    "c9b9815ae4e1500bf9fa7403949426ad42747a6227f964a902b3a307341d6976"
+    "445ef8fe25c28bdcebe17b364caba341eef141b9db5442ae91e761fba74401ca"
+    "62cb6149364256d85d604b085ae307aa7436a4e9f66c39c14404eab1df842914"
+    "d8f7f2eda312929a2737091564096476c693d32c1025cd5ad9150ef4294bc9c7"
+    "70d93d87ef800ad85c1fa01e76c4da3a6d3b7ae3ab45a4f182f88566b4eaae09"
+    "c2b4ff3615df02030100010282010004511c23f53fb4353e1ee37a9e4c8f6f12"
+    "2ba9b2afe375347d4c9c5bf43b70cad35d75443de5a36826369c5890219ce04f"
+    "ef3e103edcd1bdb6588fbb197adaa4079acb67c9779f077f0feefab4baaee476"
+    "502ec4edcf89f27561385a7403bb86a8bae8b1815574e1c97c6b57ecb8561a5d"
+    "58e2736f4821dc1f4dcf6a6c1935bd7d389a2aed446befb046ccd3f286d8f4a2"
+    "c1913c915a5d9994d091d442fec6b289d722e1b4573841b3bea73ca32c4fd40e"
+    "1bcfa7cd4d4356187f44c1c26ea37afee54a16df309f8233190edb4163093942"
+    "491756b5da43f544f140d9392eb8f0293f6c232d6f813f67e3a55fa19008cb76"
+    "8e7af7c3dcf51e878066e09c0e278102818100e9e3918ab7273284ca6243cc56"
+    "091df1b610b9f22d49cc44e0704bb6dbbe7f9c2b5b3c19a676e314de1de0864b"
+    "25653de2314ef5e20c094b839ac2fc1d798fb8159115b6bd7a22fe6f8dcd34f3"
+    "0cc7209d0299bbbba917b749787ab5d8fa16b9c64fa8dbc8513b51373c70f62d"
+    "d822b41c9881d1d61a225c061ef3e2d5bafc7902818100c2f1b72c73f0ae554c"
+    "b9be0a85abbcd2d6659dcf41547a3c32c4e44e0cb76c5f99d02c99db6e0f1449"
+    "4b7ccf7a905e1f589630ee49df776abdd35901688398e4592cddf96d7f1955e3"
+    "c46a4732e9dc06a0fee142db5f8c91c707670e2663ac8b777510d28f4faa38b7"
+    "d876a82d2716b04b4b26403ac9558a7919ea051e5cdf1702818100ca04134bbb"
+    "129221fc248fe6c8512b9a00e5c59615e3ee1977b9c6cbf4554027859351ffdd"
+    "b6464159f07863c638508ff8f519fc6af87676d0ff357749861bcaa66620592c"
+    "a377e2483c34888d7df5e887751d8ff770ffd9020e9bfec1a0914c78ab896ffc"
+    "3f9d7a72b933e08e8c66bd270ddae38188253152e01d4b49cc2e1102818100c2"
+    "796675b737dc8285e72a39cefdf38215960be76d51fc1b3fbe81cf870a18d80a"
+    "56cd97b37ac91a40c048c3a68e8d071255ca7c7d3fac1ed53e1925b980c6aece"
+    "735f01ec8505de543966989ea703f3acfa77f83d66c591cc0e8b756740ba29eb"
+    "c2e2445be96a73e89bce4b99b7cbff952d63fc6168e6a81c85889b109e2f8302"
+    "8180780b7f478240eb2401c35c8a83b9ba7e358bdf221f45184b7e4909e08b7c"
+    "1b33b852440cb5724fe970cda61506f7dda1feb02e7844e6494ebef75f01a77b"
+    "c1b4afe1c09528e52a9dc563ccbed630609ddcaed29a6656cd3c2fc3ff7b6f08"
+    "73df3a79752d5b6f13346aeee0aa69346cc18d99d2f15225fbcbb408910cea06"
+    "d0fc"
+);
+
+static P384_SPKI_DER: &[u8] = &hex!(
+    "3076301006072a8648ce3d020106052b81040022036200043bf701bc9e9d36b4"
+    "d5f1455343f09126f2564390f2b487365071243c61e6471fb9d2ab74657b82f9"
+    "086489d9ef0f5cb5d1a358eafbf952e68d533855ccbdaa6ff75b137a51014431"
+    "99325583552a6295ffe5382d00cfcda30344a9b5b68db855"
+);
+
+static P256_PKCS8_DER: &[u8] = &hex!(
+    "308187020100301306072a8648ce3d020106082a8648ce3d030107046d306b02"
+    "01010420c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b"
+    "120f6721a1440342000460fed4ba255a9d31c961eb74c6356d68c049b8923b61"
+    "fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e"
+    "9f5177a3c294d4462299"
+);
+
+static P256_SPKI_DER: &[u8] = &hex!(
+    "3059301306072a8648ce3d020106082a8648ce3d0301070342000460fed4ba25"
+    "5a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008"
+    "b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+);
+
+static SECP256K1_SPKI_DER: &[u8] = &hex!(
+    "3056301006072a8648ce3d020106052b8104000a03420004f01d6b9018ab421d"
+    "d410404cb869072065522bf85734008f105cf385a023a80f0eba29d0f0c5408e"
+    "d681984dc525982abefccd9f7ff01dd26da4999cf3f6a295"
+);
+
+static ED25519_SPKI_DER: &[u8] = &hex!(
+    "302a300506032b65700321003b6a27bcceb6a42d62a3a8d02a6f0d7365321577"
+    "1de243a63ac048a18b59da29"
+);
+
+/// Decode the key fixtures and check that they yield the known key values
+pub fn run_key_parse_tests(uart: &mut UartController) {
+    writeln!(uart, "\r\nRunning key parsing tests...").unwrap();
+
+    let rsa2048 = &RSA_VERIFY_TV[0].k;
+    let rsa3072 = &RSA_VERIFY_TV[4].k;
+
+    let mut der = [0u8; 512];
+    let passed = pem_decode(RSA_SPKI_PEM.as_bytes(), "PUBLIC KEY", &mut der).is_ok_and(|der| {
+        matches!(parse_spki(der), Ok(PublicKeyInfo::Rsa(key))
+            if key.m == rsa2048.m
+                && key.e == rsa2048.e
+                && key.m_bits == 2048
+                && key.e_bits == 17)
+    });
+    report(uart, "SPKI RSA-2048 (PEM)", passed);
+
+    let passed = matches!(parse_rsa_public_key(RSA3072_PKCS1_DER), Ok(key)
+        if key.m == rsa3072.m && key.m_bits == 3072);
+    report(uart, "PKCS#1 RSA-3072 public key", passed);
+
+    let passed = matches!(parse_pkcs8(RSA_PKCS8_DER), Ok(PrivateKeyInfo::Rsa(key))
+        if key.m == rsa2048.m
+            && key.d == rsa2048.d
+            && key.m_bits == 2048
+            && key.d_bits == 2043);
+    report(uart, "PKCS#8 RSA-2048 private key", passed);
+
+    let ecdsa = &SECP384R1_TESTVEC[0];
+    let passed = match parse_spki(P384_SPKI_DER) {
+        Ok(PublicKeyInfo::Ec(key)) => key
+            .to_p384()
+            .is_ok_and(|key| key.qx.0 == ecdsa.qx && key.qy.0 == ecdsa.qy),
+        _ => false,
+    };
+    report(uart, "SPKI P-384", passed);
+
+    let passed = matches!(parse_spki(P256_SPKI_DER), Ok(PublicKeyInfo::Ec(key))
+        if key.curve == EcCurve::P256
+            && key.x == hex!("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6")
+            && key.y == hex!("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299")
+            && key.to_p256().is_ok()
+            && key.to_p384().is_err());
+    report(uart, "SPKI P-256", passed);
+
+    let passed = matches!(parse_pkcs8(P256_PKCS8_DER), Ok(PrivateKeyInfo::Ec(key))
+        if key.curve == EcCurve::P256
+            && key.d == hex!("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"));
+    report(uart, "PKCS#8 P-256 private key", passed);
+
+    let passed = matches!(
+        parse_spki(ED25519_SPKI_DER),
+        Err(KeyError::UnsupportedAlgorithm)
+    ) && matches!(
+        parse_spki(SECP256K1_SPKI_DER),
+        Err(KeyError::UnsupportedCurve)
+    ) && matches!(
+        parse_spki(&P256_SPKI_DER[..P256_SPKI_DER.len() - 1]),
+        Err(KeyError::Der(DerError::Truncated))
+    );
+    report(uart, "Unsupported and malformed keys", passed);
+}
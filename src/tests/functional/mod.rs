@@ -1,8 +1,90 @@
 // Licensed under the Apache-2.0 license
 
+pub mod crypto_test;
+pub mod crypto_test_vec;
+pub mod ecdh_test;
 pub mod ecdsa_test;
 pub mod gpio_test;
 pub mod hash_test;
 pub mod hmac_test;
+pub mod hmac_test_vec;
+pub mod kdf_test;
+pub mod kdf_test_vec;
+pub mod keys_test;
 pub mod rsa_test;
 pub mod rsa_test_vec;
+#[cfg(feature = "rustcrypto")]
+pub mod rustcrypto_test;
+pub mod secure_engine_test;
+pub mod x509_test;
+pub mod x509_test_vec;
+
+use crate::uart::UartController;
+use embedded_io::Write;
+#[cfg(feature = "test-rng")]
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+/// Dump `data` as hex, `bytes_per_line` bytes to a line
+pub fn print_hex_array(uart: &mut UartController, data: &[u8], bytes_per_line: usize) {
+    for (i, b) in data.iter().enumerate() {
+        if i % bytes_per_line == 0 {
+            writeln!(uart, "\r").unwrap();
+        } else {
+            write!(uart, " ").unwrap();
+        }
+        write!(uart, "{b:02x}").unwrap();
+    }
+    writeln!(uart).unwrap();
+}
+
+/// Print the outcome of a single check
+pub fn report(uart: &mut UartController, name: &str, passed: bool) {
+    if passed {
+        writeln!(uart, "\r\n{name}: Test passed!").unwrap();
+    } else {
+        writeln!(uart, "\r\n{name}: Test failed!").unwrap();
+    }
+}
+
+/// Compare `output` with `expected` and print the outcome, dumping both on
+/// a mismatch
+pub fn report_output(uart: &mut UartController, name: &str, output: &[u8], expected: &[u8]) {
+    report(uart, name, output == expected);
+    if output != expected {
+        writeln!(uart, "Expected:").unwrap();
+        print_hex_array(uart, expected, 16);
+        writeln!(uart, "Got:").unwrap();
+        print_hex_array(uart, output, 16);
+    }
+}
+
+/// Deterministic xorshift generator, only to exercise the code that takes
+/// an RNG. It is not a cryptographic random source, so it only passes as a
+/// `CryptoRng` with the `test-rng` feature.
+pub struct TestRng(pub u64);
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        u32::try_from(self.next_u64() >> 32).unwrap()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "test-rng")]
+impl CryptoRng for TestRng {}
@@ -1,8 +1,27 @@
 // Licensed under the Apache-2.0 license
 
-use crate::rsa::{RsaDigest, RsaPrivateKey, RsaPublicKey, RsaSignatureData};
-use crate::tests::functional::rsa_test_vec::RSA_VERIFY_TV;
+use crate::hace_controller::HaceController;
+#[cfg(feature = "test-rng")]
+use crate::keys::{parse_pkcs8, PrivateKeyInfo};
+#[cfg(feature = "test-rng")]
+use crate::rsa::RsaCrtParams;
+use crate::rsa::{
+    AspeedRsa, OaepParams, RsaDigest, RsaDriverError, RsaPrivateKey, RsaPublicKey, RsaSignatureData,
+};
+#[cfg(feature = "test-rng")]
+use crate::rsa_keygen::RsaKeyPair;
+#[cfg(feature = "test-rng")]
+use crate::secure_engine::SecureEngine;
+#[cfg(feature = "test-rng")]
+use crate::tests::functional::keys_test::RSA_PKCS8_DER;
+use crate::tests::functional::report;
+use crate::tests::functional::rsa_test_vec::{
+    RsaTestVec, RSA_ENC_TV, RSA_PSS_EDGE_TV, RSA_PSS_TV, RSA_VERIFY_TV,
+};
+#[cfg(feature = "test-rng")]
+use crate::tests::functional::TestRng;
 use crate::uart::UartController;
+use embedded_hal::delay::DelayNs;
 use embedded_io::Write;
 use proposed_traits::rsa::{PaddingMode, RsaSign, RsaVerify};
 
@@ -13,29 +32,25 @@ where
     writeln!(uart, "\rRunning RSA Signing tests...").unwrap();
 
     for (i, vec) in RSA_VERIFY_TV.iter().enumerate() {
-        let pubkey = RsaPrivateKey {
-            m: vec.k.m,
-            d: vec.k.d,
-            // m_bits: vec.k.m_bits as u32,
-            m_bits: u32::try_from(vec.k.m_bits).unwrap_or_else(|_| {
-                writeln!(
-                    uart,
-                    "\rRSA vector[{}] m_bits {} exceeds u32 limit",
-                    i, vec.k.m_bits
-                )
-                .ok();
-                0
-            }),
-            d_bits: u32::try_from(vec.k.d_bits).unwrap_or_else(|_| {
-                writeln!(
-                    uart,
-                    "\rRSA vector[{}] d_bits {} exceeds u32 limit",
-                    i, vec.k.d_bits
-                )
-                .ok();
-                0
-            }),
-        };
+        let m_bits = u32::try_from(vec.k.m_bits).unwrap_or_else(|_| {
+            writeln!(
+                uart,
+                "\rRSA vector[{}] m_bits {} exceeds u32 limit",
+                i, vec.k.m_bits
+            )
+            .ok();
+            0
+        });
+        let d_bits = u32::try_from(vec.k.d_bits).unwrap_or_else(|_| {
+            writeln!(
+                uart,
+                "\rRSA vector[{}] d_bits {} exceeds u32 limit",
+                i, vec.k.d_bits
+            )
+            .ok();
+            0
+        });
+        let pubkey = RsaPrivateKey::new(vec.k.m, vec.k.e, vec.k.d, m_bits, d_bits);
 
         let mut digest = [0u8; 64];
         if vec.d_size > digest.len() {
@@ -188,3 +203,482 @@ where
     run_rsa_verification_tests(uart, engine);
     run_rsa_signing_tests(uart, engine);
 }
+
+fn private_key(vec: &RsaTestVec) -> RsaPrivateKey<'static> {
+    RsaPrivateKey::new(
+        vec.k.m,
+        vec.k.e,
+        vec.k.d,
+        u32::try_from(vec.k.m_bits).unwrap(),
+        u32::try_from(vec.k.d_bits).unwrap(),
+    )
+}
+
+fn public_key(vec: &RsaTestVec) -> RsaPublicKey<'static> {
+    RsaPublicKey {
+        m: vec.k.m,
+        e: vec.k.e,
+        m_bits: u32::try_from(vec.k.m_bits).unwrap(),
+        e_bits: u32::try_from(vec.k.e_bits).unwrap(),
+    }
+}
+
+fn digest_of(vec: &RsaTestVec) -> RsaDigest {
+    let mut data = [0u8; 64];
+    data[..vec.d_size].copy_from_slice(&vec.digest[..vec.d_size]);
+    RsaDigest {
+        data,
+        len: vec.d_size,
+    }
+}
+
+fn signature_of(bytes: &[u8]) -> RsaSignatureData {
+    let mut data = [0u8; 512];
+    data[..bytes.len()].copy_from_slice(bytes);
+    RsaSignatureData {
+        data,
+        len: bytes.len(),
+    }
+}
+
+/// Verify the PSS vectors, sign the salt-less ones, check that a changed
+/// digest is rejected, that the edge cases verify as expected, and that
+/// the traits, which cannot take the hash engine, refuse PSS.
+pub fn run_rsa_pss_tests<D: DelayNs>(
+    uart: &mut UartController,
+    rsa: &mut AspeedRsa<'_, D>,
+    hace: &mut HaceController,
+) {
+    writeln!(uart, "\rRunning RSA-PSS tests...").unwrap();
+
+    for (i, pss) in RSA_PSS_TV.iter().enumerate() {
+        let vec = &RSA_VERIFY_TV[pss.key];
+        let signature = signature_of(pss.signature);
+
+        let verified = rsa
+            .verify_pss(hace, &public_key(vec), &digest_of(vec), &signature)
+            .is_ok();
+
+        let mut tampered = digest_of(vec);
+        tampered.data[0] ^= 1;
+        let rejected = rsa
+            .verify_pss(hace, &public_key(vec), &tampered, &signature)
+            .is_err();
+
+        writeln!(uart, "\r\nRSA-PSS vector[{i}]:").unwrap();
+        report(uart, "RSA-PSS verify", verified && rejected);
+
+        if pss.salt_len == 0 {
+            rsa.set_pss_salt_len(Some(0)).unwrap();
+            let signed = rsa.sign_pss(hace, &private_key(vec), &digest_of(vec));
+            rsa.set_pss_salt_len(None).unwrap();
+            let passed = signed.is_ok_and(|sig| sig.data[..sig.len] == *pss.signature);
+            report(uart, "RSA-PSS sign", passed);
+        }
+    }
+
+    let vec = &RSA_VERIFY_TV[0];
+    writeln!(uart, "\r\nRSA-PSS edge cases:").unwrap();
+    for edge in RSA_PSS_EDGE_TV {
+        let verified = rsa
+            .verify_pss(
+                hace,
+                &public_key(vec),
+                &digest_of(vec),
+                &signature_of(edge.signature),
+            )
+            .is_ok();
+        report(uart, edge.comment, verified == edge.valid);
+    }
+
+    let signature = signature_of(RSA_PSS_TV[0].signature);
+    let refused = rsa
+        .sign(&private_key(vec), digest_of(vec), PaddingMode::Pss)
+        .is_err_and(|e| matches!(e, RsaDriverError::NoHashEngine))
+        && rsa
+            .verify(
+                &public_key(vec),
+                digest_of(vec),
+                PaddingMode::Pss,
+                &signature,
+            )
+            .is_err_and(|e| matches!(e, RsaDriverError::NoHashEngine));
+    report(uart, "RSA-PSS through the traits refused", refused);
+}
+
+/// Sign twice with the default salt length, the digest length, and check
+/// that each signature got a fresh salt and that verification enforces the
+/// configured salt length.
+pub fn run_rsa_pss_salt_tests<D: DelayNs>(
+    uart: &mut UartController,
+    rsa: &mut AspeedRsa<'_, D>,
+    hace: &mut HaceController,
+) {
+    let vec = &RSA_VERIFY_TV[0];
+    let salt_len = digest_of(vec).len;
+
+    let (Ok(first), Ok(second)) = (
+        rsa.sign_pss(hace, &private_key(vec), &digest_of(vec)),
+        rsa.sign_pss(hace, &private_key(vec), &digest_of(vec)),
+    ) else {
+        report(uart, "RSA-PSS salted sign", false);
+        return;
+    };
+    let fresh = first.data[..first.len] != second.data[..second.len];
+
+    rsa.set_pss_verify_salt_len(Some(salt_len));
+    let matching = rsa
+        .verify_pss(hace, &public_key(vec), &digest_of(vec), &first)
+        .is_ok()
+        && rsa
+            .verify_pss(hace, &public_key(vec), &digest_of(vec), &second)
+            .is_ok();
+    rsa.set_pss_verify_salt_len(Some(salt_len - 1));
+    let mismatched = rsa
+        .verify_pss(hace, &public_key(vec), &digest_of(vec), &first)
+        .is_err();
+    rsa.set_pss_verify_salt_len(None);
+
+    report(uart, "RSA-PSS salted sign", fresh && matching && mismatched);
+}
+
+/// Raw private-key operation on `block`, for signing forged encodings
+fn sign_raw<D: DelayNs>(
+    rsa: &mut AspeedRsa<'_, D>,
+    key: &RsaPrivateKey<'_>,
+    block: &[u8],
+) -> Option<RsaSignatureData> {
+    let mut output = [0u8; 512];
+    let len = rsa
+        .aspeed_rsa_trigger(block, &mut output, key.m, key.d, key.m_bits, key.d_bits)
+        .ok()?;
+    let m_len = block.len();
+    let mut data = [0u8; 512];
+    data[m_len - len..m_len].copy_from_slice(&output[..len]);
+    Some(RsaSignatureData { data, len: m_len })
+}
+
+/// Check that PKCS#1 v1.5 verification rejects malformed padding and a
+/// missing DigestInfo, and reports them apart from a digest mismatch.
+pub fn run_rsa_pkcs1_strict_tests<D: DelayNs>(
+    uart: &mut UartController,
+    rsa: &mut AspeedRsa<'_, D>,
+) {
+    writeln!(uart, "\rRunning RSA PKCS#1 v1.5 strict verify tests...").unwrap();
+
+    let vec = &RSA_VERIFY_TV[0];
+    let key = private_key(vec);
+    let m_len = vec.s_size;
+    let digest = &vec.digest[..vec.d_size];
+
+    let mut good = [0u8; 512];
+    AspeedRsa::<D>::pkcs1_v1_5_pad_inplace(digest, &mut good[..m_len]).unwrap();
+
+    // One padding byte changed from 0xff
+    let mut bad_ps = good;
+    bad_ps[10] = 0xfe;
+
+    // 00 01 FF..FF 00 followed by the bare digest, no DigestInfo
+    let mut no_info = [0u8; 512];
+    no_info[1] = 0x01;
+    no_info[2..m_len - digest.len() - 1].fill(0xff);
+    no_info[m_len - digest.len()..m_len].copy_from_slice(digest);
+
+    let cases: [(&str, &[u8]); 2] = [
+        ("RSA PKCS#1 v1.5 bad padding rejected", &bad_ps[..m_len]),
+        (
+            "RSA PKCS#1 v1.5 missing DigestInfo rejected",
+            &no_info[..m_len],
+        ),
+    ];
+
+    for (name, block) in cases {
+        let passed = sign_raw(rsa, &key, block).is_some_and(|signature| {
+            rsa.verify(
+                &public_key(vec),
+                digest_of(vec),
+                PaddingMode::Pkcs1v15,
+                &signature,
+            )
+            .is_err_and(|e| matches!(e, RsaDriverError::InvalidPadding))
+        });
+        report(uart, name, passed);
+    }
+
+    let mut tampered = digest_of(vec);
+    tampered.data[0] ^= 1;
+    let mismatch = rsa
+        .verify(
+            &public_key(vec),
+            tampered,
+            PaddingMode::Pkcs1v15,
+            &signature_of(&vec.signature[..vec.s_size]),
+        )
+        .is_err_and(|e| matches!(e, RsaDriverError::VerificationFailed));
+    report(uart, "RSA PKCS#1 v1.5 digest mismatch rejected", mismatch);
+}
+
+/// Encrypt with the fixed random inputs of the vectors, decrypt the
+/// expected ciphertexts, and check that a corrupted ciphertext fails.
+pub fn run_rsa_encryption_tests<D: DelayNs>(
+    uart: &mut UartController,
+    rsa: &mut AspeedRsa<'_, D>,
+    hace: &mut HaceController,
+) {
+    writeln!(uart, "\rRunning RSA encryption tests...").unwrap();
+
+    for (i, tv) in RSA_ENC_TV.iter().enumerate() {
+        let vec = &RSA_VERIFY_TV[tv.key];
+        let mut ciphertext = [0u8; 512];
+        let mut plaintext = [0u8; 512];
+
+        let encrypted = match tv.oaep {
+            Some(hash) => {
+                let params = OaepParams {
+                    hash,
+                    label: tv.label,
+                };
+                rsa.encrypt_oaep(
+                    hace,
+                    &public_key(vec),
+                    &params,
+                    tv.random,
+                    tv.msg,
+                    &mut ciphertext,
+                )
+            }
+            None => rsa.encrypt_pkcs1_v1_5(&public_key(vec), tv.random, tv.msg, &mut ciphertext),
+        };
+
+        let mut corrupted = [0u8; 512];
+        corrupted[..tv.ciphertext.len()].copy_from_slice(tv.ciphertext);
+        corrupted[tv.ciphertext.len() - 1] ^= 1;
+        let corrupted = &corrupted[..tv.ciphertext.len()];
+
+        let (decrypted, rejected) = match tv.oaep {
+            Some(hash) => {
+                let params = OaepParams {
+                    hash,
+                    label: tv.label,
+                };
+                (
+                    rsa.decrypt_oaep(
+                        hace,
+                        &private_key(vec),
+                        &params,
+                        tv.ciphertext,
+                        &mut plaintext,
+                    ),
+                    rsa.decrypt_oaep(hace, &private_key(vec), &params, corrupted, &mut [0u8; 512]),
+                )
+            }
+            None => (
+                rsa.decrypt_pkcs1_v1_5(&private_key(vec), tv.ciphertext, &mut plaintext),
+                rsa.decrypt_pkcs1_v1_5(&private_key(vec), corrupted, &mut [0u8; 512]),
+            ),
+        };
+
+        let passed = encrypted.is_ok_and(|len| ciphertext[..len] == *tv.ciphertext)
+            && decrypted.is_ok_and(|len| plaintext[..len] == *tv.msg)
+            && rejected.is_err_and(|e| matches!(e, RsaDriverError::DecryptionFailed));
+        writeln!(uart, "\r\nRSA encryption vector[{i}]:").unwrap();
+        report(uart, "RSA encryption", passed);
+    }
+}
+
+/// Check `mod_exp` on a small known answer, its operand checks, and an RSA
+/// round trip through the public and private exponents.
+pub fn run_rsa_mod_exp_tests<D: DelayNs>(uart: &mut UartController, rsa: &mut AspeedRsa<'_, D>) {
+    writeln!(uart, "\rRunning RSA mod_exp tests...").unwrap();
+
+    // 4 ^ 13 mod 497 = 445, with a zero-extended modulus
+    let mut out = [0u8; 3];
+    let small = rsa
+        .mod_exp(&[4], &[13], &[0x00, 0x01, 0xf1], &mut out)
+        .is_ok()
+        && out == [0x00, 0x01, 0xbd];
+
+    let mut one = [0xffu8; 2];
+    let zero_exponent = rsa.mod_exp(&[7], &[0], &[0x01, 0xf1], &mut one).is_ok() && one == [0, 1];
+
+    let rejected = matches!(
+        rsa.mod_exp(&[0x01, 0xf1], &[3], &[0x01, 0xf1], &mut [0u8; 2]),
+        Err(RsaDriverError::InvalidOperand)
+    ) && matches!(
+        rsa.mod_exp(&[4], &[13], &[0x01, 0xf1], &mut [0u8; 3]),
+        Err(RsaDriverError::InvalidLength)
+    ) && matches!(
+        rsa.mod_exp(&[4], &[13], &[0x01], &mut [0u8; 1]),
+        Err(RsaDriverError::InvalidOperand)
+    );
+
+    let vec = &RSA_VERIFY_TV[0];
+    let m_len = vec.k.m.len();
+    let mut encrypted = [0u8; 512];
+    let mut decrypted = [0u8; 512];
+    let round_trip = rsa
+        .mod_exp(vec.digest, vec.k.e, vec.k.m, &mut encrypted[..m_len])
+        .and_then(|()| {
+            rsa.mod_exp(
+                &encrypted[..m_len],
+                vec.k.d,
+                vec.k.m,
+                &mut decrypted[..m_len],
+            )
+        })
+        .is_ok()
+        && decrypted[m_len - vec.d_size..m_len] == *vec.digest
+        && decrypted[..m_len - vec.d_size].iter().all(|&b| b == 0);
+
+    report(
+        uart,
+        "RSA mod_exp",
+        small && zero_exponent && rejected && round_trip,
+    );
+}
+
+#[cfg(feature = "test-rng")]
+fn check_sign<D: DelayNs>(rsa: &mut AspeedRsa<'_, D>, key: &RsaPrivateKey<'_>) -> bool {
+    let vec = &RSA_VERIFY_TV[0];
+    rsa.sign(key, digest_of(vec), PaddingMode::Pkcs1v15)
+        .is_ok_and(|sig| sig.data[..sig.len] == *vec.signature)
+}
+
+/// Sign and decrypt with blinding and with CRT keys, which must give the
+/// same results as the plain private exponent, check that private-key
+/// operations are refused without a blinding source, and that a corrupted
+/// CRT parameter is caught before its result is released.
+#[cfg(feature = "test-rng")]
+pub fn run_rsa_blinding_tests<D: DelayNs + Clone>(
+    uart: &mut UartController,
+    engine: &mut SecureEngine<D>,
+    hace: &mut HaceController,
+) {
+    writeln!(uart, "\rRunning RSA blinding and CRT tests...").unwrap();
+
+    let Ok(PrivateKeyInfo::Rsa(crt_key)) = parse_pkcs8(RSA_PKCS8_DER) else {
+        report(uart, "RSA CRT key parse", false);
+        return;
+    };
+    let plain_key = private_key(&RSA_VERIFY_TV[0]);
+    let mut rng = TestRng(0x2545_f491_4f6c_dd1d);
+
+    let refused = engine
+        .rsa()
+        .sign(
+            &plain_key,
+            digest_of(&RSA_VERIFY_TV[0]),
+            PaddingMode::Pkcs1v15,
+        )
+        .is_err_and(|e| matches!(e, RsaDriverError::NoBlindingRng));
+    let mut blinded = engine.rsa();
+    blinded.set_blinding_rng(&mut rng);
+    let cases = [
+        ("RSA unblinded sign refused", refused),
+        ("RSA blinded sign", check_sign(&mut blinded, &plain_key)),
+        ("RSA blinded CRT sign", check_sign(&mut blinded, &crt_key)),
+    ];
+    for (name, passed) in cases {
+        report(uart, name, passed);
+    }
+
+    // RSA_ENC_TV[0] is OAEP under the same key
+    let tv = &RSA_ENC_TV[0];
+    let mut plaintext = [0u8; 512];
+    let params = OaepParams {
+        hash: tv.oaep.unwrap(),
+        label: tv.label,
+    };
+    let decrypted = blinded
+        .decrypt_oaep(hace, &crt_key, &params, tv.ciphertext, &mut plaintext)
+        .is_ok_and(|len| plaintext[..len] == *tv.msg);
+    report(uart, "RSA blinded CRT decrypt", decrypted);
+
+    let Some(crt) = crt_key.crt() else {
+        return;
+    };
+    let mut dp = [0u8; 512];
+    dp[..crt.dp.len()].copy_from_slice(crt.dp);
+    dp[crt.dp.len() - 1] ^= 0x02;
+    let faulty_key = RsaPrivateKey::with_crt(
+        crt_key.m,
+        crt_key.e(),
+        crt_key.d,
+        crt_key.m_bits,
+        crt_key.d_bits,
+        RsaCrtParams {
+            dp: &dp[..crt.dp.len()],
+            ..*crt
+        },
+    );
+    let caught = blinded
+        .sign(
+            &faulty_key,
+            digest_of(&RSA_VERIFY_TV[0]),
+            PaddingMode::Pkcs1v15,
+        )
+        .is_err_and(|e| matches!(e, RsaDriverError::HardwareError));
+    report(uart, "RSA CRT fault check", caught);
+}
+
+/// Generate a 2048-bit key pair from a seeded generator, and check that it
+/// signs, verifies, encrypts and decrypts consistently with and without its
+/// CRT parameters. The session is opened here, as the keys it signs with
+/// borrow from the local key pair.
+#[cfg(feature = "test-rng")]
+pub fn run_rsa_keygen_tests<D: DelayNs + Clone>(
+    uart: &mut UartController,
+    engine: &mut SecureEngine<D>,
+) {
+    writeln!(uart, "\rRunning RSA key generation tests...").unwrap();
+
+    let mut rng = TestRng(0x9e37_79b9_7f4a_7c15);
+    let mut blinding = TestRng(0x2545_f491_4f6c_dd1d);
+    let mut key = RsaKeyPair::new();
+    let mut rsa = engine.rsa();
+    rsa.set_blinding_rng(&mut blinding);
+    let size_checked = rsa.generate_key_pair(1024, &mut rng, &mut key).is_err();
+    report(uart, "RSA keygen size check", size_checked);
+    if let Err(e) = rsa.generate_key_pair(2048, &mut rng, &mut key) {
+        report(uart, "RSA keygen", false);
+        writeln!(uart, "Error: {e:?}").unwrap();
+        return;
+    }
+
+    let private = key.private_key();
+    let plain = RsaPrivateKey::new(
+        private.m,
+        private.e(),
+        private.d,
+        private.m_bits,
+        private.d_bits,
+    );
+    let vec = &RSA_VERIFY_TV[0];
+    let signed = match (
+        rsa.sign(&private, digest_of(vec), PaddingMode::Pkcs1v15),
+        rsa.sign(&plain, digest_of(vec), PaddingMode::Pkcs1v15),
+    ) {
+        (Ok(crt_sig), Ok(sig)) => {
+            crt_sig.data[..crt_sig.len] == sig.data[..sig.len]
+                && rsa
+                    .verify(
+                        &key.public_key(),
+                        digest_of(vec),
+                        PaddingMode::Pkcs1v15,
+                        &sig,
+                    )
+                    .is_ok()
+        }
+        _ => false,
+    };
+    report(uart, "RSA keygen sign", key.m_bits() == 2048 && signed);
+
+    let msg = b"generated on the device";
+    let mut ciphertext = [0u8; 512];
+    let mut plaintext = [0u8; 512];
+    let round_trip = rsa
+        .encrypt_pkcs1_v1_5(&key.public_key(), &[0x5a; 256], msg, &mut ciphertext)
+        .and_then(|len| rsa.decrypt_pkcs1_v1_5(&private, &ciphertext[..len], &mut plaintext))
+        .is_ok_and(|len| plaintext[..len] == *msg);
+    report(uart, "RSA keygen decrypt", round_trip);
+}
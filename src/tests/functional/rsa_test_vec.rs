@@ -1,5 +1,6 @@
 // Licensed under the Apache-2.0 license
 
+use crate::pkcs1::OaepHash;
 use hex_literal::hex;
 
 pub struct RsaKey {
@@ -525,3 +526,404 @@ pub static RSA_VERIFY_TV: &[RsaTestVec] = &[
         d_size: 64,
     },
 ];
+
+pub struct RsaPssTestVec {
+    /// Index of the key and digest in `RSA_VERIFY_TV`
+    pub key: usize,
+    pub salt_len: usize,
+    pub signature: &'static [u8],
+}
+
+/// RSASSA-PSS signatures (MGF1 with the digest's hash) over the keys and
+/// digests of `RSA_VERIFY_TV`, generated with OpenSSL. Salt-less signatures
+/// are deterministic and double as signing known answers.
+pub static RSA_PSS_TV: &[RsaPssTestVec] = &[
+    // RSA-2048 with SHA256, salt length 0
+    RsaPssTestVec {
+        key: 0,
+        salt_len: 0,
+        signature: &hex!(
+            "6acf0ead1aa82ca24ead86fa43905a503798b76c2ac7fb5728d2693c533d6b0f"
+            "f244a2b3f6e5553cee8d3fa973e5945495a245cea65687c1277a1f8fd339164b"
+            "02d089036770b9b649cd19b5f8f3f6cdab0188f1994ec0c4b27593062fd485c9"
+            "8246f8458ebf4fc3b99f1e11fec01a322639dca242010151030838707466ad23"
+            "a3b1a7bf1a0e7ec1b65b6ea08b613d4900d87c917fa8b1093bd98ad5c3cc31b8"
+            "43dbd2dc19c94b96a0218d30880e05b446aa9d6bb397183f861d81bd6125a1c3"
+            "8d78ec13e8ab2ee82e63d757fd4ae4ae77fbc740434448aa0ec3d25a4cc686b5"
+            "cdd4d87076976e009fe6c62450e6615271528c43a297662432e69877e85aca24"
+        ),
+    },
+    // RSA-2048 with SHA256, salt length 32
+    RsaPssTestVec {
+        key: 0,
+        salt_len: 32,
+        signature: &hex!(
+            "015ab01100ddb564e49234caf4c0f52b171ad23bf79d367cd8b4b9310d152e75"
+            "6cb00ac240c8947ec1383d8c78e9778ab8aa5bea70df9a5f43d24a6af5643dd9"
+            "b00313d483062d33692242c55bc4218dcf67c2f9afbe952b3e2d1e3cd1b2118f"
+            "2a773eff7ceeb6f14cc313b8937018073cd995bf6e45d515c9f22f8c2a6f5533"
+            "6d5f207de17e4a2ad2c35345078fbaa138d556347926c1cf295ccf8489061ca6"
+            "226a00423badaebc50d0431c9762d1189f52cf981f54fa7b283798ca58a4cd16"
+            "dfa4f0a530d9f7a08e1cf8ce0d7fd9b6dc6b132d9eeb9d45279a75911484b587"
+            "b392a4b735f0c6175108a8f1bbbf6e8996e53bc981a288dd00f68b435efd1836"
+        ),
+    },
+    // RSA-3072 with SHA384, salt length 48
+    RsaPssTestVec {
+        key: 4,
+        salt_len: 48,
+        signature: &hex!(
+            "ad8d4d907e3ac90ee40b55dd3b7738be649dfc3d669a1aa530dd5d3f7a1eb823"
+            "7b6f35e287bc5cee59385e93c8c4d0415e32a23243c60327881f465ede459585"
+            "3ff9f9db351a7811049a2b8f03db13315733caa11e81d9ae54ce48a2b3704c23"
+            "fa3ceef36b4067b9dbcb6d6810ef185d52d0ff00351ceeb059b0aa2a6c7bb5e0"
+            "134c603321114bdcb6958ad826f279bbb06f73f97a0b7478dfb77fb6146c0de9"
+            "00d507719d673e4ecb6754b886292e6e4b7ecc61656e9c84f97cf84fcb67a561"
+            "9d6aa88ed39867d68a9ffe3a68da42a05ff4fff535cb2bad4cc704d04f3c9243"
+            "38c685bb186b068b7d275f6ec263b62211023a0d87657b8ead369ea89844af1b"
+            "13de12c36f883aac46e8b922c10f3939a1f23c2f424cb7c7759e5d5fac158c2b"
+            "dbad526df5ea3e7626d97d51faeaba1da33e7a08318a433360b80507a342eada"
+            "7e4e965df842f4dc4838ecb223ace30ddfdf1ad954d0a2e85deaa3e81910dce9"
+            "7126cec5313fb2ce6c02d0eb4404874da9cb6be065b6d6bd0cacb50551690f1f"
+        ),
+    },
+    // RSA-4096 with SHA512, salt length 0
+    RsaPssTestVec {
+        key: 8,
+        salt_len: 0,
+        signature: &hex!(
+            "37cc90e549668c498b43d30df0ea11c7b05e4a73e33a6c1a7875f6d6dccb817b"
+            "a7a1fbbc5b5a354ed4732d9fa662be8421d5ca54f2a0d49560f817942b60422f"
+            "8f9ff6bd02b8a761ce90279e2740fb25c5a255dcbb329fd570510a8ab69ddf34"
+            "09f4c8a7cff4d984b21119f9cbf5035eac5b4d63afcdf20974cca83031172ae7"
+            "c3c21cb4b01dddc47b1b1c19719309cc9abdef5bc374b591991eb5c50913c65a"
+            "9b3b6fdd5ef0891f3f519f03cf4ead41fadd200dab343034c8a7d824877ede77"
+            "137105629cb0040666bc8236447c9e405e89f74ae9a65c10dff4dde23ef39e93"
+            "97c88f93cbde48e40246205d221debdd86a6c0c3b1cbdabcd997e9e2d89e0f46"
+            "03d9e912fa66a07267a811acc7e859db339e111117f24a89fd438a1dc4da719a"
+            "70a60d3b589180b6593c27c2460e92652c954df0ce0c90e6cfd2c52d754af68d"
+            "d6a251c6b5d57bd4e2ee180a938816a7ab74dded061137ff9ede02df75f5a119"
+            "6431e84916c8133bb64e1d4dd96995117c12388fcc8dc8cd22d8bfd033d8d6f6"
+            "9e9c7a1e5a6f621c690f6af95cb6f8c5b0142dd53edede1abaebaa4f1b842ff0"
+            "7e92aedfdff8ec9b9d54bf8385279e330814e6c37ea2c3173d8e3bac888140c1"
+            "cbfa6d6854ae385ff8a3fcb3ebbfe8c9d4b769c8d52a2a29bd6c8c6179d22cd7"
+            "8670393d6155c945f4ec98d4e73c4c6c2b3bf836ccf3915a124130d5df437df8"
+        ),
+    },
+];
+
+pub struct RsaEncTestVec {
+    /// Index of the key in `RSA_VERIFY_TV`
+    pub key: usize,
+    /// OAEP hash, or `None` for PKCS#1 v1.5
+    pub oaep: Option<OaepHash>,
+    pub label: &'static [u8],
+    /// OAEP seed, or the random bytes the PKCS#1 v1.5 padding is drawn from
+    pub random: &'static [u8],
+    pub msg: &'static [u8],
+    pub ciphertext: &'static [u8],
+}
+
+/// RSAES encryptions over the keys of `RSA_VERIFY_TV`, with fixed random
+/// inputs so they double as encryption known answers. Each ciphertext was
+/// checked to decrypt with OpenSSL.
+pub static RSA_ENC_TV: &[RsaEncTestVec] = &[
+    // RSA-2048 OAEP with SHA-256, empty label
+    RsaEncTestVec {
+        key: 0,
+        oaep: Some(OaepHash::Sha256),
+        label: &[],
+        random: &hex!("a5fcf44facd44c6f41064c29fd1b3741d4fad0bfddbcdf22eaa330624162ee68"),
+        msg: &hex!("7d66081046482c539638b708aa48c46f3d7312f84bc09babbe9b38503e27b710"),
+        ciphertext: &hex!(
+            "a16d0ea5dc3e949b12b63f3f92be63653379ff192687e368b1f172f6a621c355"
+            "6c61b00b19379531a7e66866701acab88768ead229c4f12a0774819d33fc4384"
+            "2bab3468fe9ed440a54c483e63edb71983573be4a66aca91f3d9d679e1ff9a2c"
+            "163e03a21ad4fe0d721ceec4fd9de323e2a31969057c9feaddc617a75b3fa8e2"
+            "053b23b68a603e7c4b488fca55dfbe3fc12695e38794853d97ced717cc5a4cb2"
+            "4d698583fef4babc4368d3c4fc0c7c2547101a46735b5c79d9052315dc987443"
+            "3bc95921bc1ee2caad12fa8642e0529662cee599525a5440eb22222384b0e9ec"
+            "e37d7c42c99520b084465cbaf4464289a1711a9477bcc351d49e56e1853540c8"
+        ),
+    },
+    // RSA-3072 OAEP with SHA-384 and a label
+    RsaEncTestVec {
+        key: 4,
+        oaep: Some(OaepHash::Sha384),
+        label: b"aspeed-ddk",
+        random: &hex!(
+            "2e17af67cc0bdd5d7b01d82c8919dc0b7bd1668d8382b3b0cbe9639d9572bfd6"
+            "3504d535bd5f4b961a6773bdda171b58"
+        ),
+        msg: &hex!("2fe42d971eeb36ec46b723189fcc821b"),
+        ciphertext: &hex!(
+            "80dc2c1f34acd26419b6da9a2e9289c1fc91c1b32798f390e58b8be0a70f56f5"
+            "a08132827ec270bc6e47bb958342982188a065f3e48522b97110a6d0bece46e6"
+            "262ce67e968b5def15c09edc067870c1a27c5b8f457972b19925ac191ac706ae"
+            "750c9cc52e8ec090210f1e95e20c7dae49a2f47e1b78f5b2ad9ca3e0f2d26b40"
+            "34190425d3356c9bc9e81fd7e7b84df5a6b9aefc989dd1d6d7fc24cca20a9034"
+            "0889c928512422379bc92f4463a50730c3cd430dd12b524095b094fd6e3615b6"
+            "9db92499af4ccf0de4e29b30eb6df19f86e1e6f437401eb7562f1491bac98d1a"
+            "e2455d83e256235cf242ade0012cc86d40abce39d8b4a760b11ce3dcd4763158"
+            "19ea775e7374c82312b4c721f09483118129fe749996f2cb04daac8447846643"
+            "3183cf31933585ada24c098676ac057b5d2780351c902e6c7858d56080164751"
+            "554f47bbab4feaf4ce2ceb384c3de6ddc8488b8b77b2073d1f8be0a9d892b4bc"
+            "c8a371ba0f061fe16e9c4f6b8b66e86b18b06d553c35437f3701d51673404e29"
+        ),
+    },
+    // RSA-2048 PKCS#1 v1.5, random bytes include zeros to skip
+    RsaEncTestVec {
+        key: 0,
+        oaep: None,
+        label: &[],
+        random: &hex!(
+            "31e29500f7116c105d9b7eaa737c5493a8b6ffef5bf1a7b7f2f95bf3e8afd600"
+            "323c9fcf9c005adb9098b61c2b90ab733d760000b1a97448c20f1c94190cbb34"
+            "e0e8aafe8df22881aca71502149261f2accb548f4cf80e632a48acbaa7e88611"
+            "5e841e8c3f4149457dddd6fa04c39f96bdd335bff27564807831a2bc686de7bb"
+            "1a773b28230eed9e23d971a15f68175d1ac873e55273bb9156cbc725dc94d745"
+            "696c8c93c914ba232d49b790c266b3e88e0eff9ed1b58dcaf946f4801dfd86ed"
+            "c98820101f872fbfea358e8dcac7de0d5a162a316e04c5561837e498c12745b3"
+            "fe929c1766"
+        ),
+        msg: &hex!("d0f3330d4a0822016e5119ad5483181d1cba3586617a534fadccbdfdb0dcc15b"),
+        ciphertext: &hex!(
+            "288cd99d0be3abf8070da1aaaf77bcbc25a065b3faee22aa41ce39d6097599f4"
+            "bb3468d2514114ce58720cadaa3aad255e96bd3545c87f88e45b635714dd643e"
+            "92503c9e3f51b2ae94dd9d92255cf20dfa6d9faf8d25cf7155bd77dfea91253c"
+            "b64ddb2a6047da360397a4bab257f6b8572f3ff2d65dd8e15410bf4eed641aa0"
+            "7a718d9b5ee191ffbffda35a029acece56a75892e2c7db006313abce29fd6a8d"
+            "210d1dbd5abbd02b4d8c45c9c432fed03f2a909011e04a8a80ef3b32aa43b605"
+            "89e1058998f0a4ab690c50570fa139dc92055f1dcbfe1f6297a7ce567bfb77c4"
+            "3bce24ab9fcfaf3dbd44ddb0d0f806c8185153b4a55b4f38e00e9cb07d33b7e4"
+        ),
+    },
+];
+
+/// RSASSA-PSS edge case over the first key and digest of `RSA_VERIFY_TV`
+/// (RSA-2048, SHA-256, MGF1 with SHA-256), verified with any salt length
+pub struct RsaPssEdgeTestVec {
+    pub comment: &'static str,
+    pub signature: &'static [u8],
+    pub valid: bool,
+}
+
+/// Valid, malformed and out-of-range signatures covering the cases of
+/// Wycheproof's `rsa_pss_*` groups: salt lengths up to the largest the
+/// encoding allows, a wrong trailer, separator or padding byte, the top bit
+/// of `maskedDB`, a wrong hash or MGF1 hash, and signatures that are not a
+/// modulus-length integer below the modulus. Built in Python from the raw
+/// RSA operation and cross-checked with pyca/cryptography.
+pub static RSA_PSS_EDGE_TV: &[RsaPssEdgeTestVec] = &[
+    RsaPssEdgeTestVec {
+        comment: "salt length 20",
+        signature: &hex!(
+            "95b9173014bc527d4fe6e4b22406918bb21362e2c7d804948d05874158b83e9c"
+            "72ddda0d4f6a330cba59a2fc6e272e14b866ba4526f75c98fad443841a6e3d9b"
+            "e3a18441738d2ed9b127b3d77fe10ea90bea2f6f504c4078e3b03ef9b9880587"
+            "2ef5dfcaf65f1d0e2412305c50a2eca73edc6e8fa5094dbbfb7ea143203570f9"
+            "e405df50b19ec7dc43a115cc7f83bd5c9f3a8674564a293edb1abe9022255abd"
+            "be87599d1a54b7a8f881b49731dc66c6631954d8579a6e7053f732796a24a9e4"
+            "9b03067c47ccda1a617c49fbcd7e1b6cf7f17c91d790b253076bc407d4b4e768"
+            "7bc91dcbb2eb5798ce7cd98ee47feb5dff6612817c54cf5a708d207a3c4a44ba"
+        ),
+        valid: true,
+    },
+    RsaPssEdgeTestVec {
+        comment: "largest salt, no padding string",
+        signature: &hex!(
+            "5f1de501652b0f375383c9fbc86b178b5c007b1b2ec204e4d46ec268a43221c8"
+            "8c40112bf736b6c199bf73d0f79889795c85dc3e3650a2fe839115d05b6a80b3"
+            "480378f567c18bea69baa73e912f7ec5dbc08abd3827f3d4e1fd03a8d3894ebc"
+            "fbf99921be86d13e85d7bcf4a582b45814149e0b1b0b6bf9c53b8454d3ea805a"
+            "e1b59c709feecd2f359037fa208bd4b7e155a68b8b565923c496e18188893bc5"
+            "ed0763ee93e8d94b618e3015ec1e6d0675d64b73dc2396dec329153565a698a4"
+            "34aa66b04fdb77e96a0feb5e73d895b418b1510a4ccb7f4671ef35ec1c449c85"
+            "d15679a7424a61e0d9ea07a6e2c54b3ba5bfd2c3732917aa6364395c5f402afb"
+        ),
+        valid: true,
+    },
+    RsaPssEdgeTestVec {
+        comment: "salt length 32",
+        signature: &hex!(
+            "1fdfc3bcf6e549264e04780425463d5395a539e6731266b577c4a33deddc5677"
+            "80c412be593440e1eade70fb5fa9193a4eed12f5e1e9627e286b59d468955951"
+            "cc0f50796e6857007631ad40ab08b36f407e94ff3025a96d2479f3df3c0d04b2"
+            "6d936565283c723bccd44e4f0d65605dbed7c16988241bd25f65cd3a7fdf2d92"
+            "e2a387f4c600c88b473fbac439283bd306467ea7aa5a278711b38b675776f505"
+            "a0f5cdaa073cfce39bb494c4a584ddbc8805950e5aefb3ba8aee8e707306837f"
+            "c83080b477f3cbf5c57a08670ff305556fb55b0b80dc43d7b55d3a42ffe71659"
+            "c7668c212b08344ff6ddbceb17395571da98a289f7ea8738fd90cf205740d89c"
+        ),
+        valid: true,
+    },
+    RsaPssEdgeTestVec {
+        comment: "trailer 0xbd",
+        signature: &hex!(
+            "7d61db19076d4e143c4cc4ca2f2b62d34c1ff8f124ff778c9b619e91309cca71"
+            "acfd5a67b835dfef0dd3b20da6798a0b0b8925ec0e5622dd62486937982cb653"
+            "3b5ed714b70527e67d988a12b60cdec1783b214398cbe9988a62156378dc7287"
+            "00f1c314a9ed2ebf30cd29c86d6d071f15e5269394ee53dc6642ad08ab9dba1f"
+            "91204b252e2cfe78d6dd954538195681c61ebf9728e013a3c901c3b80b025605"
+            "0657f263a99fcf1f4bec66cf02c78bf55f39548d270f17ad9b32b3d0702610f0"
+            "40ae4ec959b0d58dfb2f55947369fb63739b64e07a43bfbd8d2ab795181857dc"
+            "87690364ce553d07abee875556485f91c72188999bf7648ec96762ff5545da6f"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "top bit of maskedDB set",
+        signature: &hex!(
+            "871069b95a9369cda96bbd448be07f21f3be7819cdb9f11646631dbdb2b60ea0"
+            "e8d822ccd052750a904ccf9409928a3e28d8fb419d4ec21a6dd503d62fee21e5"
+            "9b550f6fb12a2805879b753da4fa77e99557d7ba6c59757603f84928966855bc"
+            "a1bae05ac8f9ff0843ef02bfa04367b71b1acc0b992773cc8ba27c4e091d729f"
+            "4fd53c360f110fccf25c36d5dc847687e41774a33a1ab9d4ea1f11e1c7c81205"
+            "43c056463a697e1dfd194cd8a3aa0b5abd1bac4d113433cc4e488583c800326c"
+            "2d7c5cdfba5400a7de19fcf98e2a1341052679c17c531ce602a71c08df2c5d76"
+            "8eddb5c633f17291569e05b88f9abaa27e2b63ee5b7fdfb5c4c1863f9650f328"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "separator 0x00",
+        signature: &hex!(
+            "49024cdbc67af6ca677a1bccb928fa9b4bc2e260dfdd2e51189a4242bcb152f1"
+            "0909dd91b0dcfee99037cb7301f0e29eec98d00bc2e2e4489e663c60558e7168"
+            "543ce6a90fbbabed8381449aebcbfe591ebe13068eff23c430cb121b464c198b"
+            "cda7763afc5e9a3c58570c24ba9e2d09b83a21599c6a3340518417b49cf9af7c"
+            "89ba24cc56d9b1f9525d2658b21d59e6e7a39bbef65b07e516dbf45f4665b876"
+            "d43aff1921df5a866ca56664f5313a58bd00fcf20033ebf37e1fb472d044ea4e"
+            "a8da108340e5599af0818b03c50b37643addc43369af2c59d51bfa0022ffb0c7"
+            "0086e4bb0ff3d9280cec82ea91abc23a29098b19761100cefe484d63003b08bb"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "separator 0x02",
+        signature: &hex!(
+            "383f6af6a82658706947db8569d489373565555da557a4795ddc7c73826a5e37"
+            "85f5d4929d45405a3a77065f5c01d1eb360f3355879fd3d8eef065b958f47cfe"
+            "909d3d8ece8c3eadffa995351825221ddcc053d807f5d5399ebb262a38e04a9d"
+            "6ca35f01d39b7580ca39ae214e43a953e464aa758dfc539fedb7e81ccfa0a21f"
+            "df7737d1906362d39698f8d5a533e589a12670c8dddfb320e9d67318992ed629"
+            "349593cdc08f3bdd9e84497dde825706088a44dab3404abf305517e6346600e5"
+            "b1c0982195296e19ce5ad2333f7861559b0e18cb792d5a754448ae21c013c266"
+            "dd86351497a067fb6de39d99e0996538b67fd7fced0b417b46e8ce23d61e2447"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "non-zero padding string",
+        signature: &hex!(
+            "7c66c736581f3d141cc46512bb64fd2947fba9ccdb78965fbdefc4a7f71ade3b"
+            "88e2462a4f5b17fef0024050622136b6585ba21cfb45f9c9f9235d69c1a04a4e"
+            "591a2b05705def520202fc86facf75642c1260249e6beacba7ca64f81d317832"
+            "20a02dcf49f3bc1e3d03648c82d6484a2d433618f71dd40b26826ab3697a8329"
+            "813fe2789552dc2e903251c995b0dfb8e478bfd9d8e6f9747fbec07e1853e9de"
+            "ace17b4538d7f214b869407d25cbca0c2e1e862b76cbac3b584bf2c893ad41ea"
+            "37a3d67ee9e8c7b1607dae87d147e62782fc2d2ef32210590a45e7a01f75dad7"
+            "59e95010d7ce43dc9a0b699439cf8a135b8076e57d6d4c1f99ade229703c1ef5"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "modified hash",
+        signature: &hex!(
+            "a0b4aca53200ca369d0523b576a0d9d4bbb306bdff6ad12fbf882eaf588b72a2"
+            "5e0d7012f8095098148807efe218f8cfb332818ef7e9864b3edcffd7dab87c39"
+            "67046c2611f0aac46533c1983e46473b7c931ad7eb8cc3ebe1750d06d8cd3dc8"
+            "28caf111d815e5e825881b99a91da4e27ca277d08096ef9b6a2f668bb4e248f6"
+            "24f4f8a336f49c48a6323af64541f5cebaf02f495540f6642e7a2d5bdf29cf41"
+            "33d6bbe6bea817855c4b59cb574a965d16c7f9da3c0f1ccb2d164f0c6149e02d"
+            "f8e32feed563f20e14d8a372adb0908cfbe62872c5b3fb23cdafd9b49b3e0ac3"
+            "2ab9d5e417d277c63e5ce2be23f89b6e9e4a72564306bed09973438e943957b1"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "MGF1 with SHA-1",
+        signature: &hex!(
+            "7583fd7d8d5f1206ea20f61f71318df72b588ddf10f5a962ea739991b62983c6"
+            "310284133e9575e389f441c7dfde86cd89e27bed9e693d8ed905b18545ca1df4"
+            "d112a43f9acc26e9578b0d32e708cd3196074d936920d8b2d6e53e9e3d287c36"
+            "d456005328673b6183924e9dbe7a449a2065ef7d3d718bb0f39975fd09c9a2c6"
+            "dbd74174a5fbd42d9277637044958ef76b8206a107c0c9fa7ecf47a9e23ca9c8"
+            "73f84f3daaeb10a51b17f6e54de3741f6486abdfa05926d7cf2b25d667329d99"
+            "27413b753f4665f23ec958f0ebdfef6b57d90981edfc99d9cc2329cc30881496"
+            "31025f7076621e63b267658d08be48f7f82bd77007685e209747172729aec1d2"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "signature plus n",
+        signature: &hex!(
+            "d1fb0e9e635bb51a5c263fac2f998943cc615f743e32986915f52eb8cbab01f3"
+            "cdbd95cf9e8e92780675fd623cc51fd7607992a9bf57eedcdd5c6dc268f84986"
+            "4df29337e37f0df11cb5bb003a2815b6dc1a8c5d9dbe0c4f17e3bd98bd67e993"
+            "bd9f5f5f9c4006cff38190c387c788572380c41d2b2b4fefc8dc119978dd5355"
+            "6e8073d641371536ea81a9b57ae2172748f5108f0c55cecb137dee32b8c02b47"
+            "f7ce2b0a524557c6a35f08fb4a6ed428c1c6d91345a1933eb403676865f42692"
+            "5acaa7eb81092fff29f0cefae31f157b3d1034208fd06d237f24ab1c3d6f05d9"
+            "d23ee840cb26ab14d1182a26921d00b77f8a25827d513c23ab9a91d55676ee7b"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "signature of zero",
+        signature: &hex!(
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000000000000000000000000000000000000000000000000000000"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "signature equal to n",
+        signature: &hex!(
+            "b21b4ae16c766bf40e21c7a80a534bf036bc258dcb2031b39e308b7addceab7c"
+            "4cf98311455a51961b978c66dd1c069d118c7fb3dd6e8c5eb4f113ee0062f034"
+            "81e342be7516b6f0a6840dbf8f1f62479b9bf75e6d9862e1f369c9b9815ae4e1"
+            "500bf9fa7403949426ad42747a6227f964a902b3a307341d6976445ef8fe25c2"
+            "8bdcebe17b364caba341eef141b9db5442ae91e761fba74401ca62cb61493642"
+            "56d85d604b085ae307aa7436a4e9f66c39c14404eab1df842914d8f7f2eda312"
+            "929a2737091564096476c693d32c1025cd5ad9150ef4294bc9c770d93d87ef80"
+            "0ad85c1fa01e76c4da3a6d3b7ae3ab45a4f182f88566b4eaae09c2b4ff3615df"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "leading zero byte added",
+        signature: &hex!(
+            "001fdfc3bcf6e549264e04780425463d5395a539e6731266b577c4a33deddc56"
+            "7780c412be593440e1eade70fb5fa9193a4eed12f5e1e9627e286b59d4689559"
+            "51cc0f50796e6857007631ad40ab08b36f407e94ff3025a96d2479f3df3c0d04"
+            "b26d936565283c723bccd44e4f0d65605dbed7c16988241bd25f65cd3a7fdf2d"
+            "92e2a387f4c600c88b473fbac439283bd306467ea7aa5a278711b38b675776f5"
+            "05a0f5cdaa073cfce39bb494c4a584ddbc8805950e5aefb3ba8aee8e70730683"
+            "7fc83080b477f3cbf5c57a08670ff305556fb55b0b80dc43d7b55d3a42ffe716"
+            "59c7668c212b08344ff6ddbceb17395571da98a289f7ea8738fd90cf205740d8"
+            "9c"
+        ),
+        valid: false,
+    },
+    RsaPssEdgeTestVec {
+        comment: "last byte dropped",
+        signature: &hex!(
+            "1fdfc3bcf6e549264e04780425463d5395a539e6731266b577c4a33deddc5677"
+            "80c412be593440e1eade70fb5fa9193a4eed12f5e1e9627e286b59d468955951"
+            "cc0f50796e6857007631ad40ab08b36f407e94ff3025a96d2479f3df3c0d04b2"
+            "6d936565283c723bccd44e4f0d65605dbed7c16988241bd25f65cd3a7fdf2d92"
+            "e2a387f4c600c88b473fbac439283bd306467ea7aa5a278711b38b675776f505"
+            "a0f5cdaa073cfce39bb494c4a584ddbc8805950e5aefb3ba8aee8e707306837f"
+            "c83080b477f3cbf5c57a08670ff305556fb55b0b80dc43d7b55d3a42ffe71659"
+            "c7668c212b08344ff6ddbceb17395571da98a289f7ea8738fd90cf205740d8"
+        ),
+        valid: false,
+    },
+];
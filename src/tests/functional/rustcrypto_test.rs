@@ -0,0 +1,78 @@
+// Licensed under the Apache-2.0 license
+
+use crate::hace_controller::HASH_CTX_POOL_SIZE;
+use crate::rustcrypto::{HmacSha256, HmacSha512_224, HmacSha512_256, Sha256, Sha384};
+use crate::tests::functional::report;
+use crate::uart::UartController;
+use digest::{Digest, Mac};
+use embedded_io::Write;
+use hex_literal::hex;
+
+/// Drive the engine through the RustCrypto `Digest` and `Mac` traits
+pub fn run_rustcrypto_tests(uart: &mut UartController) {
+    writeln!(uart, "\r\nRunning RustCrypto adapter tests...").unwrap();
+
+    // FIPS 180-2 "abc"
+    let output = Sha256::digest(b"abc");
+    report(
+        uart,
+        "Digest SHA-256",
+        output[..] == hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+    );
+
+    let mut hasher = Sha384::new();
+    hasher.update(b"a");
+    hasher.update(b"bc");
+    let output = hasher.finalize_reset();
+    hasher.update(b"abc");
+    let again = hasher.finalize();
+    report(
+        uart,
+        "Digest SHA-384 reset",
+        output[..]
+            == hex!(
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed"
+                "8086072ba1e7cc2358baeca134c825a7"
+            )
+            && output == again,
+    );
+
+    // RFC 4231 test case 2
+    let mut mac = HmacSha256::new_from_slice(b"Jefe").unwrap();
+    mac.update(b"what do ya want ");
+    mac.update(b"for nothing?");
+    let verified = mac
+        .verify_slice(&hex!(
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        ))
+        .is_ok();
+    report(uart, "Mac HMAC-SHA-256", verified);
+
+    // RFC 4231 test case 2 message, expected MACs from OpenSSL
+    let verified = HmacSha512_224::new_from_slice(b"Jefe").is_ok_and(|mut mac| {
+        mac.update(b"what do ya want for nothing?");
+        mac.verify_slice(&hex!(
+            "4a530b31a79ebcce36916546317c45f247d83241dfb818fd37254bde"
+        ))
+        .is_ok()
+    }) && HmacSha512_256::new_from_slice(b"Jefe").is_ok_and(|mut mac| {
+        mac.update(b"what do ya want for nothing?");
+        mac.verify_slice(&hex!(
+            "6df7b24630d5ccb2ee335407081a87188c221489768fa2020513b2d593359456"
+        ))
+        .is_ok()
+    });
+    report(uart, "Mac HMAC-SHA-512/224 and SHA-512/256", verified);
+
+    // Take every free context, then check that both constructors report it
+    let held: [Option<Sha256>; HASH_CTX_POOL_SIZE] = core::array::from_fn(|_| Sha256::try_new());
+    let exhausted = held.iter().any(Option::is_some)
+        && Sha256::try_new().is_none()
+        && HmacSha256::new_from_slice(b"Jefe").is_err();
+    drop(held);
+    report(
+        uart,
+        "Context pool exhaustion",
+        exhausted && Sha256::try_new().is_some(),
+    );
+}
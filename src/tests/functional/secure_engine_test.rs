@@ -0,0 +1,74 @@
+// Licensed under the Apache-2.0 license
+
+use crate::ecdsa::{AspeedEcdsaError, PublicKey, Scalar48, Secp384r1Curve, Signature};
+use crate::rsa::RsaDriverError;
+use crate::secure_engine::{EngineLock, SecureEngine};
+use crate::tests::functional::ecdsa_test::SECP384R1_TESTVEC;
+use crate::tests::functional::report;
+use crate::uart::UartController;
+use core::ptr::{read_volatile, write_volatile};
+use embedded_hal::delay::DelayNs;
+use embedded_io::Write;
+use proposed_traits::ecdsa::EcdsaVerify;
+
+/// Start of the shared SRAM, where the RSA exponent is written
+const SRAM_BASE: usize = 0x7900_0000;
+
+/// Check that operations fail as busy while the engine is held, and that
+/// operands left in the SRAM are gone once a session ends.
+pub fn run_secure_engine_tests<D: DelayNs + Clone>(
+    uart: &mut UartController,
+    engine: &mut SecureEngine<D>,
+) {
+    writeln!(uart, "\r\nRunning secure engine tests").unwrap();
+
+    let vec = &SECP384R1_TESTVEC[0];
+    let key = PublicKey {
+        qx: Scalar48(vec.qx),
+        qy: Scalar48(vec.qy),
+    };
+    let sig = Signature {
+        r: Scalar48(vec.r),
+        s: Scalar48(vec.s),
+    };
+    let ecc_busy;
+    let ecc_free;
+    {
+        let mut ecc = engine.ecc();
+        let lock = EngineLock::acquire();
+        ecc_busy = matches!(
+            EcdsaVerify::<Secp384r1Curve>::verify(&mut *ecc, &key, Scalar48(vec.m), &sig),
+            Err(AspeedEcdsaError::Busy)
+        );
+        drop(lock);
+        ecc_free =
+            EcdsaVerify::<Secp384r1Curve>::verify(&mut *ecc, &key, Scalar48(vec.m), &sig).is_ok();
+    }
+    report(uart, "ECC busy while engine held", ecc_busy && ecc_free);
+
+    let rsa_busy;
+    let rsa_free;
+    {
+        let mut rsa = engine.rsa();
+        let mut out = [0u8; 2];
+        let lock = EngineLock::acquire();
+        rsa_busy = matches!(
+            rsa.mod_exp(&[4], &[13], &[0x01, 0xf1], &mut out),
+            Err(RsaDriverError::Busy)
+        );
+        drop(lock);
+        // 4 ^ 13 mod 497 = 445
+        rsa_free = rsa.mod_exp(&[4], &[13], &[0x01, 0xf1], &mut out).is_ok() && out == [0x01, 0xbd];
+
+        // Leave stale operands behind, as an interrupted operation would
+        for offset in (0..64).step_by(4) {
+            unsafe { write_volatile((SRAM_BASE + offset) as *mut u32, 0xa5a5_a5a5) };
+        }
+    }
+    report(uart, "RSA busy while engine held", rsa_busy && rsa_free);
+
+    let scrubbed = (0..64)
+        .step_by(4)
+        .all(|offset| unsafe { read_volatile((SRAM_BASE + offset) as *const u32) } == 0);
+    report(uart, "SRAM zeroed after session", scrubbed);
+}
@@ -0,0 +1,216 @@
+// Licensed under the Apache-2.0 license
+
+use crate::hace_controller::HaceController;
+use crate::keys::{EcCurve, PublicKeyInfo};
+use crate::secure_engine::SecureEngine;
+use crate::tests::functional::report;
+use crate::tests::functional::x509_test_vec::{
+    X509_FAKE_ROOT, X509_INTERMEDIATE, X509_LEAF_PKCS1, X509_LEAF_PSS, X509_ROGUE, X509_ROOT,
+    X509_SUB_CA, X509_SUB_CA_INTERMEDIATE, X509_SUB_CA_LEAF,
+};
+use crate::uart::UartController;
+use crate::x509::{
+    verify_chain, BasicConstraints, Certificate, KeyUsage, SignatureAlgorithm, Time, X509Error,
+};
+use embedded_hal::delay::DelayNs;
+use embedded_io::Write;
+
+/// Within the validity of every sample certificate
+const NOW: Time = Time {
+    year: 2026,
+    month: 1,
+    day: 1,
+    hour: 0,
+    minute: 0,
+    second: 0,
+};
+
+/// Decode the sample certificates and check that they yield the fields
+/// OpenSSL wrote
+fn run_x509_parse_tests(uart: &mut UartController) {
+    let passed = Certificate::from_der(X509_ROOT).is_ok_and(|root| {
+        root.issuer == root.subject
+            && root.serial == [1]
+            && root.signature_algorithm == SignatureAlgorithm::Ecdsa { hash_len: 48 }
+            && root.basic_constraints
+                == Some(BasicConstraints {
+                    ca: true,
+                    path_len: None,
+                })
+            && root.key_usage == Some(KeyUsage(KeyUsage::KEY_CERT_SIGN.0 | KeyUsage::CRL_SIGN.0))
+            && root.not_before
+                == Time {
+                    year: 2024,
+                    ..NOW
+                }
+            // notAfter is a GeneralizedTime from 2050 on
+            && root.not_after
+                == Time {
+                    year: 2054,
+                    ..NOW
+                }
+            && matches!(root.public_key, PublicKeyInfo::Ec(ref key) if key.curve == EcCurve::P384)
+    });
+    report(uart, "X.509 root CA", passed);
+
+    let passed = Certificate::from_der(X509_INTERMEDIATE).is_ok_and(|ca| {
+        ca.basic_constraints
+            == Some(BasicConstraints {
+                ca: true,
+                path_len: Some(0),
+            })
+            && matches!(ca.public_key, PublicKeyInfo::Rsa(ref key) if key.m_bits == 2048)
+    });
+    report(uart, "X.509 intermediate CA", passed);
+
+    let passed = Certificate::from_der(X509_LEAF_PSS).is_ok_and(|leaf| {
+        leaf.signature_algorithm
+            == SignatureAlgorithm::RsaPss {
+                hash_len: 32,
+                salt_len: 32,
+            }
+            && !leaf.is_ca()
+            && leaf.key_usage == Some(KeyUsage::DIGITAL_SIGNATURE)
+            && leaf.check_validity(NOW).is_ok()
+    }) && Certificate::from_der(X509_LEAF_PKCS1).is_ok_and(|leaf| {
+        leaf.signature_algorithm == SignatureAlgorithm::RsaPkcs1v15 { hash_len: 32 }
+    });
+    report(uart, "X.509 leaf", passed);
+
+    let passed = Certificate::from_der(&X509_ROOT[..X509_ROOT.len() - 1]).is_err()
+        && Certificate::from_der(&X509_ROOT[4..]).is_err();
+    report(uart, "X.509 malformed certificate", passed);
+
+    // The root's notBefore is 2024-01-01, a leap year
+    let mut der = [0u8; 1024];
+    let der = &mut der[..X509_ROOT.len()];
+    der.copy_from_slice(X509_ROOT);
+    let Some(at) = der.windows(13).position(|w| w == b"240101000000Z") else {
+        report(uart, "X.509 calendar dates", false);
+        return;
+    };
+    let mut with_date = |date: &[u8; 6]| {
+        der[at..at + 6].copy_from_slice(date);
+        Certificate::from_der(der).map(|cert| cert.not_before)
+    };
+    let passed = with_date(b"240229").is_ok_and(|t| t.month == 2 && t.day == 29)
+        && with_date(b"000229").is_ok()
+        && with_date(b"250229").is_err()
+        && with_date(b"240230").is_err()
+        && with_date(b"240431").is_err()
+        && with_date(b"240132").is_err();
+    report(uart, "X.509 calendar dates", passed);
+}
+
+/// Validate the sample chains on the secure-boot engines, and check that
+/// each broken chain fails for its reason
+pub fn run_x509_tests<D: DelayNs + Clone>(
+    uart: &mut UartController,
+    engine: &mut SecureEngine<D>,
+    hace: &mut HaceController,
+) {
+    writeln!(uart, "\r\nRunning X.509 tests...").unwrap();
+
+    run_x509_parse_tests(uart);
+
+    // ECDSA P-384 with SHA-384, then RSA-PSS
+    let passed = verify_chain(
+        engine,
+        hace,
+        &[X509_LEAF_PSS, X509_INTERMEDIATE],
+        X509_ROOT,
+        NOW,
+    )
+    .is_ok_and(|leaf| leaf.serial == [3]);
+    report(uart, "X.509 chain, RSA-PSS leaf", passed);
+
+    let passed = verify_chain(
+        engine,
+        hace,
+        &[X509_LEAF_PKCS1, X509_INTERMEDIATE],
+        X509_ROOT,
+        NOW,
+    )
+    .is_ok_and(|leaf| leaf.serial == [4]);
+    report(uart, "X.509 chain, RSA PKCS#1 v1.5 leaf", passed);
+
+    // ECDSA P-384 with SHA-256, then P-256 with SHA-256
+    let passed = verify_chain(
+        engine,
+        hace,
+        &[X509_SUB_CA_LEAF, X509_SUB_CA],
+        X509_ROOT,
+        NOW,
+    )
+    .is_ok();
+    report(uart, "X.509 chain, ECDSA P-256 leaf", passed);
+
+    let expired = Time { year: 2036, ..NOW };
+    let early = Time { year: 2024, ..NOW };
+    let chain = [X509_LEAF_PSS, X509_INTERMEDIATE];
+    let passed = verify_chain(engine, hace, &chain, X509_ROOT, expired).err()
+        == Some(X509Error::Expired)
+        && verify_chain(engine, hace, &chain, X509_ROOT, early).err()
+            == Some(X509Error::NotYetValid);
+    report(uart, "X.509 validity period", passed);
+
+    // Same name as the root, different key
+    let passed = verify_chain(engine, hace, &chain, X509_FAKE_ROOT, NOW).err()
+        == Some(X509Error::InvalidSignature);
+    report(uart, "X.509 untrusted root", passed);
+
+    // Change a character of the subject names, under RSA and ECDSA
+    let mut leaf = [0u8; 1024];
+    let leaf = &mut leaf[..X509_LEAF_PKCS1.len()];
+    leaf.copy_from_slice(X509_LEAF_PKCS1);
+    let mut ca = [0u8; 1024];
+    let ca = &mut ca[..X509_INTERMEDIATE.len()];
+    ca.copy_from_slice(X509_INTERMEDIATE);
+    let tampered = [&mut *leaf, &mut *ca].into_iter().all(|der| {
+        match der.windows(6).rposition(|w| w == b"Device") {
+            Some(pos) => {
+                der[pos] = b'd';
+                true
+            }
+            None => false,
+        }
+    });
+    let passed = tampered
+        && verify_chain(engine, hace, &[&*leaf, X509_INTERMEDIATE], X509_ROOT, NOW).err()
+            == Some(X509Error::InvalidSignature)
+        && verify_chain(engine, hace, &[&*ca], X509_ROOT, NOW).err()
+            == Some(X509Error::InvalidSignature);
+    report(uart, "X.509 tampered certificate", passed);
+
+    let passed = verify_chain(engine, hace, &[X509_LEAF_PSS], X509_ROOT, NOW).err()
+        == Some(X509Error::IssuerMismatch)
+        && verify_chain(engine, hace, &[], X509_ROOT, NOW).err()
+            == Some(X509Error::InvalidChainLength);
+    report(uart, "X.509 incomplete chain", passed);
+
+    let passed = verify_chain(
+        engine,
+        hace,
+        &[X509_ROGUE, X509_LEAF_PKCS1, X509_INTERMEDIATE],
+        X509_ROOT,
+        NOW,
+    )
+    .err()
+        == Some(X509Error::NotCa);
+    report(uart, "X.509 certificate issued by a leaf", passed);
+
+    let passed = verify_chain(
+        engine,
+        hace,
+        &[
+            X509_SUB_CA_LEAF,
+            X509_SUB_CA_INTERMEDIATE,
+            X509_INTERMEDIATE,
+        ],
+        X509_ROOT,
+        NOW,
+    )
+    .err()
+        == Some(X509Error::PathLenExceeded);
+    report(uart, "X.509 path length constraint", passed);
+}
@@ -0,0 +1,205 @@
+// Licensed under the Apache-2.0 license
+
+use hex_literal::hex;
+
+// Sample certificate chain generated with OpenSSL. Every name is under
+// `O=Example RoT`.
+
+/// Root CA: P-384, self-signed with ecdsa-with-SHA384, valid 2024-01-01 to
+/// 2054-01-01
+pub static X509_ROOT: &[u8] = &hex!(
+    "308201cf30820156a003020102020101300a06082a8648ce3d04030330303114"
+    "3012060355040a0c0b4578616d706c6520526f543118301606035504030c0f45"
+    "78616d706c6520526f6f742043413020170d3234303130313030303030305a18"
+    "0f32303534303130313030303030305a303031143012060355040a0c0b457861"
+    "6d706c6520526f543118301606035504030c0f4578616d706c6520526f6f7420"
+    "43413076301006072a8648ce3d020106052b8104002203620004970098d7c081"
+    "c16962a2b0e68388eb34595e259d1551a880a567c4bc010c8c2ff5d0b9ac3ce4"
+    "d357f130c1c598ffff16eb7e9d49cdc6885b7db777000499941ea7d3ae171c58"
+    "86c91e423f5d95f88c7d817dd8ae0de99a70d52898d786016a8ca3423040300f"
+    "0603551d130101ff040530030101ff300e0603551d0f0101ff04040302010630"
+    "1d0603551d0e04160414d40e606ba1e4c34fe43cda85cd37621d74b02b05300a"
+    "06082a8648ce3d0403030367003064023042c1afbafa3645a547a625a90a7ebe"
+    "41b9e111d6912d167f098ad434cffa6d54b249eb98c587724618bd76f0de24fb"
+    "2702305ebc24289808500659c5133fed682e659d17bd55e5ad658593bfed4629"
+    "65aab373dd255016730b5c36b7c32fc689a572"
+);
+
+/// Root with the same name as `X509_ROOT` and another P-384 key
+pub static X509_FAKE_ROOT: &[u8] = &hex!(
+    "308201d030820156a003020102020101300a06082a8648ce3d04030330303114"
+    "3012060355040a0c0b4578616d706c6520526f543118301606035504030c0f45"
+    "78616d706c6520526f6f742043413020170d3234303130313030303030305a18"
+    "0f32303534303130313030303030305a303031143012060355040a0c0b457861"
+    "6d706c6520526f543118301606035504030c0f4578616d706c6520526f6f7420"
+    "43413076301006072a8648ce3d020106052b81040022036200047ed093bcba0d"
+    "283f61557936fe24f39213bcd90ea3e41442f64f1ae048855e9c47bc6dee5726"
+    "8c35e794b6e013b6bef6285c648091e832534cfff87dd66d289b977eb693c035"
+    "963b61b0f6db576de3f6c789e6df0a2643a051f18c2a61b996b7a3423040300f"
+    "0603551d130101ff040530030101ff300e0603551d0f0101ff04040302010630"
+    "1d0603551d0e0416041401a40325515d3b6c00c529775432071980f3014b300a"
+    "06082a8648ce3d04030303680030650231008386787818053f5c917a4d1446ff"
+    "215bbff46854588253d40864e0ea5b00c3289c3fda69ab1e04c395bd6e615a61"
+    "6c5302307958d3cb10688dc63d02419a0b1fbbac72b882db1e2de76b934b82a1"
+    "b5dc770282ba00dddfdb5cae845771a33ef55f9e"
+);
+
+/// Device CA: RSA-2048, pathLenConstraint 0, issued by the root with
+/// ecdsa-with-SHA384, valid 2024-01-01 to 2044-01-01
+pub static X509_INTERMEDIATE: &[u8] = &hex!(
+    "308202a330820228a003020102020102300a06082a8648ce3d04030330303114"
+    "3012060355040a0c0b4578616d706c6520526f543118301606035504030c0f45"
+    "78616d706c6520526f6f74204341301e170d3234303130313030303030305a17"
+    "0d3434303130313030303030305a303231143012060355040a0c0b4578616d70"
+    "6c6520526f54311a301806035504030c114578616d706c652044657669636520"
+    "434130820122300d06092a864886f70d01010105000382010f003082010a0282"
+    "010100a81d5db8a8db9ea4fe8055aa8e9f02e29f13ac3b5309198cc615238b5e"
+    "bd00f18ffaa9099a50496c8dabd6809e93f21ce66ba6a3f243c47df1ebd9b2f9"
+    "dc4cdc0b4a05a8e739fec1f9d6e412612f6a7a0f053ffd20cd4a4995a647ac91"
+    "57330f84abbcc8461ff3b71913156b7aacf2157bd6bf5bc13966db264f8dc096"
+    "9661741660856c0033faed431f056f9ca84b66bed19ac54487e8164c8c67fd31"
+    "286cc4b9ff29b7e0df2bf71370ee68fba6cbc27356babd9da1822497758d3a31"
+    "1b208ce2d542abc32850213cc9f50c74f06b4a0df67a5c92e948f0f0a166de50"
+    "0265a2bb0bac1a0ad29def71d96d7b9a3eaa9fb12c284995931c1f9210b35b93"
+    "ffe06d0203010001a366306430120603551d130101ff040830060101ff020100"
+    "300e0603551d0f0101ff040403020106301d0603551d0e041604147fea7ebccc"
+    "3604ecec19472f8d4df3e08e877a19301f0603551d23041830168014d40e606b"
+    "a1e4c34fe43cda85cd37621d74b02b05300a06082a8648ce3d04030303690030"
+    "66023100888d6a50750be34104fff44773f0e765c8bf2d23a03a447d0b11c21d"
+    "b7fb2322ec5bd1085abc21623a8474de926a91a8023100910c128dfc68d6f0c0"
+    "64ac047f8c3df3d6a92a04a4d99f295896ea09d608b0b65aab9b78fd62299100"
+    "acef90cae7e7ae"
+);
+
+/// Device leaf: P-256, issued by the device CA with RSASSA-PSS (SHA-256,
+/// salt length 32), valid 2025-01-01 to 2035-01-01
+pub static X509_LEAF_PSS: &[u8] = &hex!(
+    "308202de30820192a003020102020103304106092a864886f70d01010a3034a0"
+    "0f300d06096086480165030402010500a11c301a06092a864886f70d01010830"
+    "0d06096086480165030402010500a203020120303231143012060355040a0c0b"
+    "4578616d706c6520526f54311a301806035504030c114578616d706c65204465"
+    "76696365204341301e170d3235303130313030303030305a170d333530313031"
+    "3030303030305a303431143012060355040a0c0b4578616d706c6520526f5431"
+    "1c301a06035504030c1341535431303630204465766963652030303031305930"
+    "1306072a8648ce3d020106082a8648ce3d03010703420004aa575bf309b13e4c"
+    "6e1ad0fbafedc7c7e660fcc04027d67b9a0bd1c55b49e32973118e3f3d3ca7e9"
+    "64f283a0dfc2f3fb73be09a51e470bf7188833f3582cf5e8a360305e300c0603"
+    "551d130101ff04023000300e0603551d0f0101ff040403020780301d0603551d"
+    "0e04160414b763e0c89bb3931208e011f0a6dd53a30cf21007301f0603551d23"
+    "0418301680147fea7ebccc3604ecec19472f8d4df3e08e877a19304106092a86"
+    "4886f70d01010a3034a00f300d06096086480165030402010500a11c301a0609"
+    "2a864886f70d010108300d06096086480165030402010500a203020120038201"
+    "01004ee839addde53f10a82fdd060a8116593d27675d1ecff34539b89010c20e"
+    "31245e76560923f480f990dec2023d91d9d4ee40afa04ca9bf3d0a93dd6f4720"
+    "64dab5e096b3a9da36a4774c302114e6f19a582918fc64e2035a939e350e55e4"
+    "54ab04a9a8fb4f3017448abf0694d92e3c1b7137927da7cf4db6665e7e1c8a4c"
+    "d7e8a2f0642c467aaf8ed688ba8352a6fa0de737221d9bfc93a4dafea3bfbb25"
+    "a3bb440e502402f768cfb0ec04f44ed06cb6b29b30214250b60f843a36a6eff1"
+    "e8f6c1efb065a558ee581fd3341565a640b7ce3ab0ffd27d6e1a64c4d1a3686b"
+    "415944cd262463824286b6368760dd620d8f5ac6fdef97aa127d42a4d4f1e33c"
+    "5305"
+);
+
+/// The same leaf, issued with sha256WithRSAEncryption
+pub static X509_LEAF_PKCS1: &[u8] = &hex!(
+    "308202763082015ea003020102020104300d06092a864886f70d01010b050030"
+    "3231143012060355040a0c0b4578616d706c6520526f54311a30180603550403"
+    "0c114578616d706c6520446576696365204341301e170d323530313031303030"
+    "3030305a170d3335303130313030303030305a303431143012060355040a0c0b"
+    "4578616d706c6520526f54311c301a06035504030c1341535431303630204465"
+    "7669636520303030313059301306072a8648ce3d020106082a8648ce3d030107"
+    "03420004aa575bf309b13e4c6e1ad0fbafedc7c7e660fcc04027d67b9a0bd1c5"
+    "5b49e32973118e3f3d3ca7e964f283a0dfc2f3fb73be09a51e470bf7188833f3"
+    "582cf5e8a360305e300c0603551d130101ff04023000300e0603551d0f0101ff"
+    "040403020780301d0603551d0e04160414b763e0c89bb3931208e011f0a6dd53"
+    "a30cf21007301f0603551d230418301680147fea7ebccc3604ecec19472f8d4d"
+    "f3e08e877a19300d06092a864886f70d01010b0500038201010035412deba863"
+    "b95f644ed30f8557306e25ce9ec2cd7e1c4cacb272d3272fac12b1bac0aab655"
+    "e26caaaa20a802d83c63297041b9ccfbe60a17e844e3b2d430529fd44dd4cd7b"
+    "bcf674b0a2b2b22103b2daee41bdbd3d33ecec9039d43b8a56b45ced591aebe3"
+    "6463e927628e7431a4160b62f0eae31a44f976591d093893da6a48fe143a3278"
+    "bbcffbb0c5d63a6b97c91f0988ed495e85d7510155980e57c3d0e87b8c1dcfe9"
+    "54d70808455e0e785d6aca2620c4dbaaf4fb73c962432dfb7832072665049d39"
+    "5c3ba48781d35bed1bcefeeeab8c11b8e0ec4472bca0fba9e01250786c1959be"
+    "3a71778d7212063d0d523cd42878050e16786132bb31d5b7e832"
+);
+
+/// Certificate issued by the (non-CA) leaf key
+pub static X509_ROGUE: &[u8] = &hex!(
+    "308201a83082014fa003020102020105300a06082a8648ce3d04030230343114"
+    "3012060355040a0c0b4578616d706c6520526f54311c301a06035504030c1341"
+    "535431303630204465766963652030303031301e170d32353031303130303030"
+    "30305a170d3335303130313030303030305a302631143012060355040a0c0b45"
+    "78616d706c6520526f54310e300c06035504030c05526f677565305930130607"
+    "2a8648ce3d020106082a8648ce3d03010703420004f7811390e34e6cb1d221c7"
+    "414000ddbb92b1c89c9efd39443bde0a62dae77fa9afc63a34158cf08dcb0e9a"
+    "f6895b3f2a50f77c6d595e55876ca54e87a706b27ba360305e300c0603551d13"
+    "0101ff04023000300e0603551d0f0101ff040403020780301d0603551d0e0416"
+    "041459d5ebe426617b74143761a6cb7f5f44b7c8c966301f0603551d23041830"
+    "168014b763e0c89bb3931208e011f0a6dd53a30cf21007300a06082a8648ce3d"
+    "0403020347003044022040f1895bcafda82f960f3fb017c7f08c4bc5e3c0e4f7"
+    "3e83fff4e92a370de8cf02202b900ac5693b5a5bc2ed0e117c11f482e554cd51"
+    "54f96f03c0c6564728455d81"
+);
+
+/// Sub CA: P-256, issued by the root with ecdsa-with-SHA256
+pub static X509_SUB_CA: &[u8] = &hex!(
+    "308201d230820157a003020102020108300a06082a8648ce3d04030230303114"
+    "3012060355040a0c0b4578616d706c6520526f543118301606035504030c0f45"
+    "78616d706c6520526f6f74204341301e170d3235303130313030303030305a17"
+    "0d3335303130313030303030305a302f31143012060355040a0c0b4578616d70"
+    "6c6520526f543117301506035504030c0e4578616d706c652053756220434130"
+    "59301306072a8648ce3d020106082a8648ce3d03010703420004f7811390e34e"
+    "6cb1d221c7414000ddbb92b1c89c9efd39443bde0a62dae77fa9afc63a34158c"
+    "f08dcb0e9af6895b3f2a50f77c6d595e55876ca54e87a706b27ba3633061300f"
+    "0603551d130101ff040530030101ff300e0603551d0f0101ff04040302020430"
+    "1d0603551d0e0416041459d5ebe426617b74143761a6cb7f5f44b7c8c966301f"
+    "0603551d23041830168014d40e606ba1e4c34fe43cda85cd37621d74b02b0530"
+    "0a06082a8648ce3d0403020369003066023100c7e7095fdba61a8aa48b7e52b0"
+    "45915d2168532ddc5cb31179a34f3f5d115888b413778d4c37360c29bc5e17a6"
+    "bcaab2023100a8c22ab4af8e9bb5d7a844d3a5fafcd6686b4774ab5379baa1ed"
+    "af945178e29fb0811cb0c7eca7d7362fc503f8d024b1"
+);
+
+/// The same sub CA, issued by the device CA, whose path length does not
+/// admit it
+pub static X509_SUB_CA_INTERMEDIATE: &[u8] = &hex!(
+    "308202743082015ca003020102020106300d06092a864886f70d01010b050030"
+    "3231143012060355040a0c0b4578616d706c6520526f54311a30180603550403"
+    "0c114578616d706c6520446576696365204341301e170d323530313031303030"
+    "3030305a170d3335303130313030303030305a302f31143012060355040a0c0b"
+    "4578616d706c6520526f543117301506035504030c0e4578616d706c65205375"
+    "622043413059301306072a8648ce3d020106082a8648ce3d03010703420004f7"
+    "811390e34e6cb1d221c7414000ddbb92b1c89c9efd39443bde0a62dae77fa9af"
+    "c63a34158cf08dcb0e9af6895b3f2a50f77c6d595e55876ca54e87a706b27ba3"
+    "633061300f0603551d130101ff040530030101ff300e0603551d0f0101ff0404"
+    "03020204301d0603551d0e0416041459d5ebe426617b74143761a6cb7f5f44b7"
+    "c8c966301f0603551d230418301680147fea7ebccc3604ecec19472f8d4df3e0"
+    "8e877a19300d06092a864886f70d01010b0500038201010019d5d490c10c2af3"
+    "244a955c08c02576d3fee597e46a25620602bce8cf7a5d07dda0a2b464a3d19f"
+    "99dfee1fb7797c39cfc07766ba695bb996c15b6be1ab09c56b81370a1b7a85a7"
+    "5b6e3b80a9d427ff5b7ac75707dc1613642518505fb0ccff88b5b8873170a203"
+    "c29b7a33be7430e187648d9410e68dbfe3f061c47d7ed146fa4dae1fec399312"
+    "cf507e3be8712ddac123d446f4aa6f4738799d8c9aa891c8f5d96b8b0ac83e36"
+    "97903b9871e2699c1cbe378552a44c431bf3133c81fe5aee3b04e6b4020ba153"
+    "df401872589d2e8c45b995c4d1cc128c1ec7fc545cdefe7e4fab0e88a9a104f3"
+    "46a72b5a683f40ee72f2ff8a75114e9e9e2052cf33fb8048"
+);
+
+/// Leaf issued by the sub CA with ecdsa-with-SHA256
+pub static X509_SUB_CA_LEAF: &[u8] = &hex!(
+    "308201b230820158a003020102020107300a06082a8648ce3d040302302f3114"
+    "3012060355040a0c0b4578616d706c6520526f543117301506035504030c0e45"
+    "78616d706c6520537562204341301e170d3235303130313030303030305a170d"
+    "3335303130313030303030305a303431143012060355040a0c0b4578616d706c"
+    "6520526f54311c301a06035504030c1341535431303630204465766963652030"
+    "3030313059301306072a8648ce3d020106082a8648ce3d03010703420004aa57"
+    "5bf309b13e4c6e1ad0fbafedc7c7e660fcc04027d67b9a0bd1c55b49e3297311"
+    "8e3f3d3ca7e964f283a0dfc2f3fb73be09a51e470bf7188833f3582cf5e8a360"
+    "305e300c0603551d130101ff04023000300e0603551d0f0101ff040403020780"
+    "301d0603551d0e04160414b763e0c89bb3931208e011f0a6dd53a30cf2100730"
+    "1f0603551d2304183016801459d5ebe426617b74143761a6cb7f5f44b7c8c966"
+    "300a06082a8648ce3d040302034800304502210087625bdd31d92dec0e2bd867"
+    "384ddebdeb09a3814ef7578cfb262234fd30a71302204c8b21136d082c667abe"
+    "d3845691e35df4a57594cb5899da9b77f39ea44f5a64"
+);
@@ -0,0 +1,702 @@
+// Licensed under the Apache-2.0 license
+
+//! X.509 v3 certificates (RFC 5280) and validation of a certificate chain
+//! up to a provisioned root.
+//!
+//! [`Certificate::from_der`] decodes the fields a root of trust acts on and
+//! borrows everything else from the input. [`verify_chain`] checks validity
+//! periods, issuer names, basic constraints, key usage and path lengths,
+//! hashes each `TBSCertificate` on the HACE and verifies its signature on
+//! the secure-boot engines: ECDSA P-256 and P-384 on the ECC engine, RSA
+//! PKCS#1 v1.5 and PSS on the RSA engine.
+//!
+//! Names are compared as encoded, without the string folding of RFC 5280
+//! section 7.1. Only the basicConstraints and keyUsage extensions are
+//! interpreted, so a certificate with any other critical extension is
+//! rejected. Revocation is left to the caller.
+
+use crate::der::{
+    parse_single, tag_context, tag_implicit, DerError, Reader, TAG_BIT_STRING, TAG_BOOLEAN,
+    TAG_GENERALIZED_TIME, TAG_INTEGER, TAG_NULL, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE,
+    TAG_UTC_TIME,
+};
+use crate::ecdsa::{
+    AspeedEcdsaError, Scalar32, Scalar48, Secp256r1Curve, Secp384r1Curve, Signature,
+};
+use crate::hace_controller::HaceController;
+use crate::keys::{parse_spki, EcCurve, KeyError, PublicKeyInfo};
+use crate::pkcs1::digest_parts;
+use crate::rsa::{RsaDigest, RsaDriverError, RsaSignatureData};
+use crate::secure_engine::SecureEngine;
+use embedded_hal::delay::DelayNs;
+use proposed_traits::ecdsa::EcdsaVerify;
+use proposed_traits::rsa::{PaddingMode, RsaVerify};
+
+/// ecdsa-with-SHA256, 1.2.840.10045.4.3.2
+const OID_ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+/// ecdsa-with-SHA384, 1.2.840.10045.4.3.3
+const OID_ECDSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+/// ecdsa-with-SHA512, 1.2.840.10045.4.3.4
+const OID_ECDSA_SHA512: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x04];
+/// sha256WithRSAEncryption, 1.2.840.113549.1.1.11
+const OID_RSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+/// sha384WithRSAEncryption, 1.2.840.113549.1.1.12
+const OID_RSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c];
+/// sha512WithRSAEncryption, 1.2.840.113549.1.1.13
+const OID_RSA_SHA512: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d];
+/// id-RSASSA-PSS, 1.2.840.113549.1.1.10
+const OID_RSASSA_PSS: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0a];
+/// id-mgf1, 1.2.840.113549.1.1.8
+const OID_MGF1: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x08];
+/// id-sha256, 2.16.840.1.101.3.4.2.1
+const OID_SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+/// id-sha384, 2.16.840.1.101.3.4.2.2
+const OID_SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+/// id-sha512, 2.16.840.1.101.3.4.2.3
+const OID_SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+/// id-ce-keyUsage, 2.5.29.15
+const OID_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x0f];
+/// id-ce-basicConstraints, 2.5.29.19
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+
+/// PSS salt length when the parameters leave it out
+const PSS_DEFAULT_SALT_LEN: u32 = 20;
+
+/// Most certificates [`verify_chain`] takes below the root
+pub const MAX_CHAIN_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum X509Error {
+    /// The certificate is not well-formed DER
+    Der(DerError),
+    /// The subject public key cannot be decoded
+    Key(KeyError),
+    /// The certificate version is not v1, v2 or v3
+    UnsupportedVersion,
+    /// The signature algorithm or its parameters are not supported
+    UnsupportedAlgorithm,
+    /// The signature algorithms inside and outside the TBS differ
+    AlgorithmMismatch,
+    /// A validity time is malformed
+    InvalidTime,
+    /// A basicConstraints or keyUsage value is malformed or repeated
+    InvalidExtension,
+    /// A critical extension is not one this module interprets
+    UnsupportedCriticalExtension,
+    /// The current time is before `notBefore`
+    NotYetValid,
+    /// The current time is after `notAfter`
+    Expired,
+    /// The chain is empty or longer than [`MAX_CHAIN_LEN`]
+    InvalidChainLength,
+    /// A certificate's issuer is not the subject of the next certificate
+    IssuerMismatch,
+    /// An issuing certificate is not a CA
+    NotCa,
+    /// An issuing certificate's key usage does not include keyCertSign
+    KeyUsage,
+    /// More intermediates follow a CA than its pathLenConstraint allows
+    PathLenExceeded,
+    /// The issuer's key cannot make signatures of this algorithm
+    KeyMismatch,
+    /// The signature does not verify
+    InvalidSignature,
+    /// Failure hashing the TBS on the HACE engine
+    Hash,
+    /// Another RSA or ECC operation is using the engine
+    Busy,
+    /// The RSA or ECC engine failed
+    Engine,
+}
+
+impl From<DerError> for X509Error {
+    fn from(err: DerError) -> Self {
+        X509Error::Der(err)
+    }
+}
+
+impl From<KeyError> for X509Error {
+    fn from(err: KeyError) -> Self {
+        X509Error::Key(err)
+    }
+}
+
+impl From<AspeedEcdsaError> for X509Error {
+    fn from(err: AspeedEcdsaError) -> Self {
+        match err {
+            AspeedEcdsaError::InvalidSignature | AspeedEcdsaError::BadInput => {
+                X509Error::InvalidSignature
+            }
+            AspeedEcdsaError::Busy => X509Error::Busy,
+            AspeedEcdsaError::Mac(_) | AspeedEcdsaError::FaultDetected => X509Error::Engine,
+        }
+    }
+}
+
+impl From<RsaDriverError> for X509Error {
+    fn from(err: RsaDriverError) -> Self {
+        match err {
+            RsaDriverError::InvalidLength
+            | RsaDriverError::InvalidOperand
+            | RsaDriverError::InvalidPadding
+            | RsaDriverError::VerificationFailed
+            | RsaDriverError::DecryptionFailed => X509Error::InvalidSignature,
+            RsaDriverError::Busy => X509Error::Busy,
+            RsaDriverError::HardwareError
+            | RsaDriverError::NoBlindingRng
+            | RsaDriverError::NoHashEngine => X509Error::Engine,
+        }
+    }
+}
+
+/// UTC time to the second; times order chronologically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Time {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl Time {
+    /// Decode a `UTCTime` (`YYMMDDHHMMSSZ`, years 1950 to 2049) or a
+    /// `GeneralizedTime` (`YYYYMMDDHHMMSSZ`), the forms RFC 5280 allows
+    fn from_der(tag: u8, value: &[u8]) -> Result<Self, X509Error> {
+        let (year, rest) = match (tag, value.len()) {
+            (TAG_UTC_TIME, 13) => {
+                let yy = digits(&value[..2])?;
+                (if yy < 50 { 2000 + yy } else { 1900 + yy }, &value[2..])
+            }
+            (TAG_GENERALIZED_TIME, 15) => (digits(&value[..4])?, &value[4..]),
+            _ => return Err(X509Error::InvalidTime),
+        };
+        if rest[10] != b'Z' {
+            return Err(X509Error::InvalidTime);
+        }
+        let field = |i: usize| -> Result<u8, X509Error> {
+            u8::try_from(digits(&rest[i..i + 2])?).map_err(|_| X509Error::InvalidTime)
+        };
+        let time = Time {
+            year,
+            month: field(0)?,
+            day: field(2)?,
+            hour: field(4)?,
+            minute: field(6)?,
+            second: field(8)?,
+        };
+        let valid = (1..=12).contains(&time.month)
+            && (1..=days_in_month(time.year, time.month)).contains(&time.day)
+            && time.hour < 24
+            && time.minute < 60
+            && time.second < 60;
+        if valid {
+            Ok(time)
+        } else {
+            Err(X509Error::InvalidTime)
+        }
+    }
+}
+
+/// Decimal value of a run of ASCII digits
+fn digits(bytes: &[u8]) -> Result<u16, X509Error> {
+    bytes.iter().try_fold(0u16, |acc, &b| {
+        if b.is_ascii_digit() {
+            Ok(acc * 10 + u16::from(b - b'0'))
+        } else {
+            Err(X509Error::InvalidTime)
+        }
+    })
+}
+
+/// Length of a month in the Gregorian calendar
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Signature algorithm of a certificate. Hashes are named by their digest
+/// length: 32, 48 or 64 bytes for SHA-256, SHA-384 or SHA-512.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    Ecdsa {
+        hash_len: usize,
+    },
+    RsaPkcs1v15 {
+        hash_len: usize,
+    },
+    /// RSASSA-PSS with MGF1 over the message hash
+    RsaPss {
+        hash_len: usize,
+        salt_len: usize,
+    },
+}
+
+impl SignatureAlgorithm {
+    /// Length of the digest of the TBS
+    #[must_use]
+    pub fn hash_len(self) -> usize {
+        match self {
+            SignatureAlgorithm::Ecdsa { hash_len }
+            | SignatureAlgorithm::RsaPkcs1v15 { hash_len }
+            | SignatureAlgorithm::RsaPss { hash_len, .. } => hash_len,
+        }
+    }
+}
+
+/// The basicConstraints extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasicConstraints {
+    pub ca: bool,
+    /// Most intermediates that may follow this CA in a chain
+    pub path_len: Option<u32>,
+}
+
+/// The keyUsage extension; bit `n` of the named bit list is `1 << n`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyUsage(pub u16);
+
+impl KeyUsage {
+    pub const DIGITAL_SIGNATURE: Self = Self(1 << 0);
+    pub const NON_REPUDIATION: Self = Self(1 << 1);
+    pub const KEY_ENCIPHERMENT: Self = Self(1 << 2);
+    pub const DATA_ENCIPHERMENT: Self = Self(1 << 3);
+    pub const KEY_AGREEMENT: Self = Self(1 << 4);
+    pub const KEY_CERT_SIGN: Self = Self(1 << 5);
+    pub const CRL_SIGN: Self = Self(1 << 6);
+    pub const ENCIPHER_ONLY: Self = Self(1 << 7);
+    pub const DECIPHER_ONLY: Self = Self(1 << 8);
+
+    /// Whether every bit of `usage` is set
+    #[must_use]
+    pub fn contains(self, usage: Self) -> bool {
+        self.0 & usage.0 == usage.0
+    }
+}
+
+/// Decoded X.509 certificate, borrowing from its DER encoding
+pub struct Certificate<'a> {
+    /// The signed `TBSCertificate`, as encoded
+    pub tbs: &'a [u8],
+    /// Serial number INTEGER contents
+    pub serial: &'a [u8],
+    pub signature_algorithm: SignatureAlgorithm,
+    /// Issuer `Name`, as encoded
+    pub issuer: &'a [u8],
+    pub not_before: Time,
+    pub not_after: Time,
+    /// Subject `Name`, as encoded
+    pub subject: &'a [u8],
+    pub public_key: PublicKeyInfo<'a>,
+    pub basic_constraints: Option<BasicConstraints>,
+    pub key_usage: Option<KeyUsage>,
+    /// Signature BIT STRING contents
+    pub signature: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+    /// Decode a DER certificate. The subject key must be RSA, P-256 or
+    /// P-384, as for [`parse_spki`].
+    pub fn from_der(der: &'a [u8]) -> Result<Self, X509Error> {
+        let mut cert = Reader::new(parse_single(der, TAG_SEQUENCE)?);
+        let (tbs_contents, tbs) = read_whole(&mut cert, TAG_SEQUENCE)?;
+        let (signature_algorithm, algorithm) = read_signature_algorithm(&mut cert)?;
+        let signature = cert.read_bit_string()?;
+        cert.finish()?;
+
+        let mut tbs_reader = Reader::new(tbs_contents);
+        // Absent for v1; the INTEGER holds the version minus one
+        let version = match tbs_reader.read_optional(tag_context(0))? {
+            Some(version) => explicit_u32(version)?,
+            None => 0,
+        };
+        if version > 2 {
+            return Err(X509Error::UnsupportedVersion);
+        }
+        let serial = tbs_reader.read(TAG_INTEGER)?;
+        if read_signature_algorithm(&mut tbs_reader)?.1 != algorithm {
+            return Err(X509Error::AlgorithmMismatch);
+        }
+        let (_, issuer) = read_whole(&mut tbs_reader, TAG_SEQUENCE)?;
+        let mut validity = tbs_reader.read_sequence()?;
+        let not_before = read_time(&mut validity)?;
+        let not_after = read_time(&mut validity)?;
+        validity.finish()?;
+        let (_, subject) = read_whole(&mut tbs_reader, TAG_SEQUENCE)?;
+        let (_, spki) = read_whole(&mut tbs_reader, TAG_SEQUENCE)?;
+        let public_key = parse_spki(spki)?;
+
+        let mut basic_constraints = None;
+        let mut key_usage = None;
+        if version >= 1 {
+            // issuerUniqueID and subjectUniqueID, not used here
+            tbs_reader.read_optional(tag_implicit(1))?;
+            tbs_reader.read_optional(tag_implicit(2))?;
+        }
+        if version == 2 {
+            if let Some(extensions) = tbs_reader.read_optional(tag_context(3))? {
+                (basic_constraints, key_usage) = parse_extensions(extensions)?;
+            }
+        }
+        tbs_reader.finish()?;
+
+        Ok(Certificate {
+            tbs,
+            serial,
+            signature_algorithm,
+            issuer,
+            not_before,
+            not_after,
+            subject,
+            public_key,
+            basic_constraints,
+            key_usage,
+            signature,
+        })
+    }
+
+    /// Check that `now` is within the validity period
+    pub fn check_validity(&self, now: Time) -> Result<(), X509Error> {
+        if now < self.not_before {
+            Err(X509Error::NotYetValid)
+        } else if now > self.not_after {
+            Err(X509Error::Expired)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether basicConstraints marks the certificate as a CA
+    #[must_use]
+    pub fn is_ca(&self) -> bool {
+        self.basic_constraints.is_some_and(|bc| bc.ca)
+    }
+}
+
+/// Next element carrying `tag`, as `(contents, whole encoding)`
+fn read_whole<'a>(reader: &mut Reader<'a>, tag: u8) -> Result<(&'a [u8], &'a [u8]), DerError> {
+    let (found, contents, whole) = reader.read_any()?;
+    if found == tag {
+        Ok((contents, whole))
+    } else {
+        Err(DerError::UnexpectedTag)
+    }
+}
+
+/// INTEGER inside an explicitly tagged element
+fn explicit_u32(contents: &[u8]) -> Result<u32, DerError> {
+    let mut reader = Reader::new(contents);
+    let value = reader.read_u32()?;
+    reader.finish()?;
+    Ok(value)
+}
+
+fn read_time(reader: &mut Reader<'_>) -> Result<Time, X509Error> {
+    let (tag, value, _) = reader.read_any()?;
+    Time::from_der(tag, value)
+}
+
+/// Digest length of a hash `AlgorithmIdentifier`, whose parameters are
+/// NULL or absent
+fn hash_algorithm(contents: &[u8]) -> Result<usize, X509Error> {
+    let mut alg = Reader::new(contents);
+    let hash_len = match alg.read(TAG_OID)? {
+        OID_SHA256 => 32,
+        OID_SHA384 => 48,
+        OID_SHA512 => 64,
+        _ => return Err(X509Error::UnsupportedAlgorithm),
+    };
+    if alg.peek_tag() == Some(TAG_NULL) {
+        alg.read_null()?;
+    }
+    alg.finish()?;
+    Ok(hash_len)
+}
+
+/// `RSASSA-PSS-params`. SHA-1, the default hash, is not supported, so the
+/// hash and mask generation function must both be present.
+fn pss_params(params: &[u8]) -> Result<SignatureAlgorithm, X509Error> {
+    let mut params = Reader::new(params);
+    let hash = params
+        .read_optional(tag_context(0))?
+        .ok_or(X509Error::UnsupportedAlgorithm)?;
+    let hash_len = hash_algorithm(parse_single(hash, TAG_SEQUENCE)?)?;
+
+    let mgf = params
+        .read_optional(tag_context(1))?
+        .ok_or(X509Error::UnsupportedAlgorithm)?;
+    let mut mgf = Reader::new(parse_single(mgf, TAG_SEQUENCE)?);
+    // The engine driver runs MGF1 over the message hash
+    if mgf.read(TAG_OID)? != OID_MGF1 || hash_algorithm(mgf.read(TAG_SEQUENCE)?)? != hash_len {
+        return Err(X509Error::UnsupportedAlgorithm);
+    }
+    mgf.finish()?;
+
+    let salt_len = match params.read_optional(tag_context(2))? {
+        Some(salt_len) => explicit_u32(salt_len)?,
+        None => PSS_DEFAULT_SALT_LEN,
+    };
+    // trailerFieldBC is the only trailer defined
+    if let Some(trailer) = params.read_optional(tag_context(3))? {
+        if explicit_u32(trailer)? != 1 {
+            return Err(X509Error::UnsupportedAlgorithm);
+        }
+    }
+    params.finish()?;
+
+    Ok(SignatureAlgorithm::RsaPss {
+        hash_len,
+        salt_len: salt_len as usize,
+    })
+}
+
+/// Next signature `AlgorithmIdentifier`, with its whole encoding
+fn read_signature_algorithm<'a>(
+    reader: &mut Reader<'a>,
+) -> Result<(SignatureAlgorithm, &'a [u8]), X509Error> {
+    let (contents, whole) = read_whole(reader, TAG_SEQUENCE)?;
+    let mut alg = Reader::new(contents);
+    let oid = alg.read(TAG_OID)?;
+    let algorithm = match oid {
+        // ECDSA parameters must be absent
+        OID_ECDSA_SHA256 => SignatureAlgorithm::Ecdsa { hash_len: 32 },
+        OID_ECDSA_SHA384 => SignatureAlgorithm::Ecdsa { hash_len: 48 },
+        OID_ECDSA_SHA512 => SignatureAlgorithm::Ecdsa { hash_len: 64 },
+        OID_RSA_SHA256 | OID_RSA_SHA384 | OID_RSA_SHA512 => {
+            // The parameters are NULL, though some encoders omit them
+            if !alg.is_empty() {
+                alg.read_null()?;
+            }
+            let hash_len = match oid {
+                OID_RSA_SHA256 => 32,
+                OID_RSA_SHA384 => 48,
+                _ => 64,
+            };
+            SignatureAlgorithm::RsaPkcs1v15 { hash_len }
+        }
+        OID_RSASSA_PSS => pss_params(alg.read(TAG_SEQUENCE)?)?,
+        _ => return Err(X509Error::UnsupportedAlgorithm),
+    };
+    alg.finish()?;
+    Ok((algorithm, whole))
+}
+
+/// `BasicConstraints ::= SEQUENCE { cA BOOLEAN DEFAULT FALSE,
+/// pathLenConstraint INTEGER OPTIONAL }`
+fn parse_basic_constraints(value: &[u8]) -> Result<BasicConstraints, X509Error> {
+    let mut seq = Reader::new(parse_single(value, TAG_SEQUENCE)?);
+    let ca = if seq.peek_tag() == Some(TAG_BOOLEAN) {
+        seq.read_bool()?
+    } else {
+        false
+    };
+    let path_len = if seq.peek_tag() == Some(TAG_INTEGER) {
+        Some(seq.read_u32()?)
+    } else {
+        None
+    };
+    seq.finish()?;
+    // A path length only constrains a CA
+    if path_len.is_some() && !ca {
+        return Err(X509Error::InvalidExtension);
+    }
+    Ok(BasicConstraints { ca, path_len })
+}
+
+/// `KeyUsage ::= BIT STRING`, whose trailing zero bits DER drops
+fn parse_key_usage(value: &[u8]) -> Result<KeyUsage, X509Error> {
+    let (&unused, bits) = parse_single(value, TAG_BIT_STRING)?
+        .split_first()
+        .ok_or(DerError::InvalidBitString)?;
+    if unused > 7 || (bits.is_empty() && unused != 0) {
+        return Err(DerError::InvalidBitString.into());
+    }
+    let usage = (0..9)
+        .filter(|&n| bits.get(n / 8).is_some_and(|b| b & (0x80 >> (n % 8)) != 0))
+        .fold(0u16, |acc, n| acc | (1 << n));
+    Ok(KeyUsage(usage))
+}
+
+/// Walk `[3] Extensions`, returning the basicConstraints and keyUsage
+fn parse_extensions(
+    extensions: &[u8],
+) -> Result<(Option<BasicConstraints>, Option<KeyUsage>), X509Error> {
+    let mut basic_constraints = None;
+    let mut key_usage = None;
+    let mut extensions = Reader::new(parse_single(extensions, TAG_SEQUENCE)?);
+    while !extensions.is_empty() {
+        let mut extension = extensions.read_sequence()?;
+        let oid = extension.read(TAG_OID)?;
+        let critical = if extension.peek_tag() == Some(TAG_BOOLEAN) {
+            extension.read_bool()?
+        } else {
+            false
+        };
+        let value = extension.read(TAG_OCTET_STRING)?;
+        extension.finish()?;
+
+        match oid {
+            OID_BASIC_CONSTRAINTS if basic_constraints.is_none() => {
+                basic_constraints = Some(parse_basic_constraints(value)?);
+            }
+            OID_KEY_USAGE if key_usage.is_none() => {
+                key_usage = Some(parse_key_usage(value)?);
+            }
+            OID_BASIC_CONSTRAINTS | OID_KEY_USAGE => return Err(X509Error::InvalidExtension),
+            _ if critical => return Err(X509Error::UnsupportedCriticalExtension),
+            _ => {}
+        }
+    }
+    Ok((basic_constraints, key_usage))
+}
+
+/// Check that `issuer` may have issued `cert`, with `below` intermediates
+/// between the issuer and the leaf
+fn check_issuer(cert: &Certificate, issuer: &Certificate, below: usize) -> Result<(), X509Error> {
+    if cert.issuer != issuer.subject {
+        return Err(X509Error::IssuerMismatch);
+    }
+    match issuer.basic_constraints {
+        Some(BasicConstraints { ca: true, path_len }) => {
+            if path_len.is_some_and(|max| below > max as usize) {
+                return Err(X509Error::PathLenExceeded);
+            }
+        }
+        _ => return Err(X509Error::NotCa),
+    }
+    // Without the extension the key may be used for any purpose
+    if issuer
+        .key_usage
+        .is_some_and(|usage| !usage.contains(KeyUsage::KEY_CERT_SIGN))
+    {
+        return Err(X509Error::KeyUsage);
+    }
+    Ok(())
+}
+
+/// Verify the signature on `cert` with the issuer's `key`
+fn verify_signature<D: DelayNs + Clone>(
+    engine: &mut SecureEngine<D>,
+    hace: &mut HaceController,
+    cert: &Certificate,
+    key: &PublicKeyInfo,
+) -> Result<(), X509Error> {
+    let hash_len = cert.signature_algorithm.hash_len();
+    let mut digest = [0u8; 64];
+    digest_parts(hace, &[cert.tbs], &mut digest[..hash_len]).map_err(|_| X509Error::Hash)?;
+    let digest = &digest[..hash_len];
+
+    match (cert.signature_algorithm, key) {
+        (SignatureAlgorithm::Ecdsa { .. }, PublicKeyInfo::Ec(key)) => match key.curve {
+            EcCurve::P256 => {
+                let key = key.to_p256()?;
+                let signature = Signature::<Scalar32>::from_der(cert.signature)
+                    .map_err(|_| X509Error::InvalidSignature)?;
+                // A longer hash is truncated to its leftmost bits
+                let mut z = Scalar32::default();
+                z.0.copy_from_slice(&digest[..Scalar32::LEN]);
+                Ok(EcdsaVerify::<Secp256r1Curve>::verify(
+                    &mut *engine.ecc(),
+                    &key,
+                    z,
+                    &signature,
+                )?)
+            }
+            EcCurve::P384 => {
+                let key = key.to_p384()?;
+                let signature = Signature::<Scalar48>::from_der(cert.signature)
+                    .map_err(|_| X509Error::InvalidSignature)?;
+                // A shorter hash keeps its value, a longer one is truncated
+                let len = hash_len.min(Scalar48::LEN);
+                let mut z = Scalar48::default();
+                z.0[Scalar48::LEN - len..].copy_from_slice(&digest[..len]);
+                Ok(EcdsaVerify::<Secp384r1Curve>::verify(
+                    &mut *engine.ecc(),
+                    &key,
+                    z,
+                    &signature,
+                )?)
+            }
+        },
+        (
+            SignatureAlgorithm::RsaPkcs1v15 { .. } | SignatureAlgorithm::RsaPss { .. },
+            PublicKeyInfo::Rsa(key),
+        ) => {
+            // The signature is an integer of exactly the modulus size
+            if cert.signature.len() != ((key.m_bits + 7) / 8) as usize {
+                return Err(X509Error::InvalidSignature);
+            }
+            let mut signature = RsaSignatureData {
+                data: [0; 512],
+                len: cert.signature.len(),
+            };
+            signature.data[..signature.len].copy_from_slice(cert.signature);
+            let mut message = RsaDigest {
+                data: [0; 64],
+                len: hash_len,
+            };
+            message.data[..hash_len].copy_from_slice(digest);
+
+            let mut rsa = engine.rsa();
+            match cert.signature_algorithm {
+                SignatureAlgorithm::RsaPss { salt_len, .. } => {
+                    rsa.set_pss_verify_salt_len(Some(salt_len));
+                    rsa.verify_pss(hace, key, &message, &signature)?;
+                }
+                _ => {
+                    rsa.verify(key, message, PaddingMode::Pkcs1v15, &signature)?;
+                }
+            }
+            Ok(())
+        }
+        _ => Err(X509Error::KeyMismatch),
+    }
+}
+
+/// Validate `chain` at time `now` up to the trusted `root`, returning the
+/// decoded leaf.
+///
+/// `chain` holds DER certificates leaf first, each followed by its issuer;
+/// the last one must be issued by `root`. Every certificate, the root
+/// included, must be valid at `now`. Every issuer must be a CA whose key
+/// usage, if present, includes keyCertSign, and whose pathLenConstraint
+/// admits the intermediates below it. The root is trusted as provisioned,
+/// so its own signature is not checked.
+///
+/// Each signature is verified in its own engine session.
+pub fn verify_chain<'a, D: DelayNs + Clone>(
+    engine: &mut SecureEngine<D>,
+    hace: &mut HaceController,
+    chain: &[&'a [u8]],
+    root: &[u8],
+    now: Time,
+) -> Result<Certificate<'a>, X509Error> {
+    if chain.is_empty() || chain.len() > MAX_CHAIN_LEN {
+        return Err(X509Error::InvalidChainLength);
+    }
+    let root = Certificate::from_der(root)?;
+    root.check_validity(now)?;
+
+    for (index, der) in chain.iter().enumerate() {
+        let cert = Certificate::from_der(der)?;
+        cert.check_validity(now)?;
+        let next;
+        let issuer = match chain.get(index + 1) {
+            Some(der) => {
+                next = Certificate::from_der(der)?;
+                &next
+            }
+            None => &root,
+        };
+        // The certificates below the issuer are `index` intermediates and
+        // the leaf
+        check_issuer(&cert, issuer, index)?;
+        verify_signature(engine, hace, &cert, &issuer.public_key)?;
+    }
+    Certificate::from_der(chain[0])
+}